@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::domain::is_sensitive_key;
 use crate::error::{AppResult, EnvironmentError};
 pub use crate::ports::{EnvFile, EnvironmentConfig};
 use crate::ports::{EnvPreview, EnvironmentRepository};
@@ -192,7 +193,7 @@ fn parse_env_preview(contents: &str) -> Vec<(String, String)> {
     entries
 }
 
-fn parse_env_defaults(contents: &str) -> HashMap<String, String> {
+pub fn parse_env_defaults(contents: &str) -> HashMap<String, String> {
     let mut defaults = HashMap::new();
 
     for line in contents.lines() {
@@ -231,11 +232,3 @@ fn strip_quotes(value: &str) -> &str {
     }
     trimmed
 }
-
-fn is_sensitive_key(key: &str) -> bool {
-    let lower = key.to_ascii_lowercase();
-    let tokens = [
-        "password", "secret", "token", "key", "api", "private", "cred",
-    ];
-    tokens.iter().any(|token| lower.contains(token))
-}
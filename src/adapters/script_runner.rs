@@ -1,27 +1,64 @@
+use crate::adapters::environments::FsEnvironmentRepository;
 use crate::adapters::system_checks::{
-    ensure_bash_installed, ensure_git_installed, ensure_jq_installed, ensure_powershell_installed,
-    ensure_python_installed,
+    check_requirement, ensure_bash_installed, ensure_powershell_installed, ensure_python_installed,
 };
+use crate::domain::{parse_output_line, OutputLine, Requirement};
 use crate::error::{AppResult, ScriptError};
-use crate::ports::{ScriptRunOutput, ScriptRunner};
+use crate::lua_api::{LuaContext, SandboxPolicy};
+use crate::lua_script;
+use crate::ports::{EnvironmentRepository, RunObserver, ScriptRunOutput, ScriptRunner};
 use crate::runtime::{command_for_script, script_kind, ScriptKind};
-use std::path::Path;
+use crate::workspace::Workspace;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::thread;
 
-pub struct MultiScriptRunner;
+pub struct MultiScriptRunner {
+    workspace_root: PathBuf,
+}
 
 impl MultiScriptRunner {
-    pub fn new() -> Self {
-        Self
+    pub fn new<P: Into<PathBuf>>(workspace_root: P) -> Self {
+        Self {
+            workspace_root: workspace_root.into(),
+        }
+    }
+
+    /// `.lua` scripts see the active environment plus any `env` overrides through `omakure.env()`.
+    fn lua_context(&self, script: &Path, env: &[(String, String)]) -> LuaContext {
+        let workspace = Workspace::new(self.workspace_root.clone());
+        let mut values = FsEnvironmentRepository::new(workspace.envs_dir())
+            .load_environment_config()
+            .map(|config| config.defaults)
+            .unwrap_or_default();
+        for (key, value) in env {
+            values.insert(key.to_ascii_lowercase(), value.clone());
+        }
+        LuaContext {
+            sandbox: SandboxPolicy::for_script(
+                &workspace,
+                script.parent().unwrap_or(workspace.root()),
+            ),
+            workspace_root: self.workspace_root.clone(),
+            env: values,
+        }
     }
 }
 
 impl ScriptRunner for MultiScriptRunner {
-    fn run(&self, script: &Path, args: &[String]) -> AppResult<ScriptRunOutput> {
+    fn run(
+        &self,
+        script: &Path,
+        args: &[String],
+        stdin: Option<&str>,
+        prompts: bool,
+        env: &[(String, String)],
+        observer: &mut dyn RunObserver,
+    ) -> AppResult<ScriptRunOutput> {
         match script_kind(script).ok_or(ScriptError::UnsupportedType)? {
             ScriptKind::Bash => {
-                ensure_git_installed()?;
                 ensure_bash_installed()?;
-                ensure_jq_installed()?;
             }
             ScriptKind::PowerShell => {
                 ensure_powershell_installed()?;
@@ -29,14 +66,192 @@ impl ScriptRunner for MultiScriptRunner {
             ScriptKind::Python => {
                 ensure_python_installed()?;
             }
+            ScriptKind::Lua => {
+                let context = self.lua_context(script, env);
+                return lua_script::run_script(script, args, stdin, &context, observer);
+            }
+            ScriptKind::Executable => {}
         }
 
-        let output = command_for_script(script)?.args(args).output()?;
+        let mut child = command_for_script(script)?
+            .args(args)
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .stdin(if stdin.is_some() || prompts {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let stderr_reader = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut buffer = Vec::new();
+                let _ = stderr.read_to_end(&mut buffer);
+                buffer
+            })
+        });
+
+        // A payload is written from its own thread so a chatty child can't deadlock on stdout.
+        let mut child_stdin = child.stdin.take();
+        let stdin_writer = stdin.and_then(|payload| {
+            let mut pipe = child_stdin.take()?;
+            let payload = payload.to_string();
+            Some(thread::spawn(move || {
+                let _ = pipe.write_all(payload.as_bytes());
+            }))
+        });
+        let mut stdout = String::new();
+        let mut annotations = Vec::new();
+        if let Some(child_stdout) = child.stdout.take() {
+            let mut reader = BufReader::new(child_stdout);
+            let mut buffer = Vec::new();
+            loop {
+                buffer.clear();
+                if reader.read_until(b'\n', &mut buffer)? == 0 {
+                    break;
+                }
+                let raw = String::from_utf8_lossy(&buffer);
+                let line = raw.trim_end_matches(['\n', '\r']);
+                let parsed = parse_output_line(line);
+                match &parsed {
+                    OutputLine::Progress { .. } | OutputLine::Prompt(_) => {}
+                    OutputLine::Annotation(annotation) => {
+                        annotations.push(annotation.clone());
+                        stdout.push_str(&raw);
+                    }
+                    _ => stdout.push_str(&raw),
+                }
+                observer.on_line(&parsed);
+                if let OutputLine::Prompt(prompt) = &parsed {
+                    let Some(pipe) = child_stdin.as_mut() else {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        let id = prompt.id.clone();
+                        return Err(match stdin {
+                            Some(_) => ScriptError::PromptWithStdin { id },
+                            None => ScriptError::PromptsUndeclared { id },
+                        }
+                        .into());
+                    };
+                    let answer = observer.on_prompt(prompt).map(|answer| (pipe, answer));
+                    if let Some((stdin, answer)) = answer {
+                        writeln!(stdin, "{}", answer).and_then(|_| stdin.flush())?;
+                    } else {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Err(ScriptError::PromptUnanswered {
+                            id: prompt.id.clone(),
+                        }
+                        .into());
+                    }
+                }
+            }
+        }
+        drop(child_stdin);
+        if let Some(handle) = stdin_writer {
+            let _ = handle.join();
+        }
+
+        let status = child.wait()?;
+        let stderr = stderr_reader
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default();
         Ok(ScriptRunOutput {
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-            exit_code: output.status.code(),
-            success: output.status.success(),
+            stdout,
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            exit_code: status.code(),
+            success: status.success(),
+            annotations,
         })
     }
+
+    fn check_requirements(&self, requires: &[Requirement]) -> Vec<ScriptError> {
+        requires
+            .iter()
+            .filter_map(|requirement| check_requirement(requirement).err())
+            .collect()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::domain::PromptRequest;
+    use crate::error::AppError;
+    use std::fs;
+
+    struct Answer;
+
+    impl RunObserver for Answer {
+        fn on_line(&mut self, _line: &OutputLine) {}
+
+        fn on_prompt(&mut self, _prompt: &PromptRequest) -> Option<String> {
+            Some("yes".to_string())
+        }
+    }
+
+    #[test]
+    fn test_prompt_with_stdin_payload_names_the_conflict() {
+        let root =
+            std::env::temp_dir().join(format!("omakure-runner-prompt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let script = root.join("ask.sh");
+        fs::write(
+            &script,
+            "echo '::prompt id=confirm type=bool'\nread -r answer\necho \"$answer\"\n",
+        )
+        .unwrap();
+        let runner = MultiScriptRunner::new(&root);
+
+        let err = runner
+            .run(&script, &[], Some("ids\n"), true, &[], &mut Answer)
+            .unwrap_err();
+        assert!(
+            matches!(&err, AppError::Script(ScriptError::PromptWithStdin { id }) if id == "confirm"),
+            "{}",
+            err
+        );
+        let output = runner
+            .run(&script, &[], None, true, &[], &mut Answer)
+            .unwrap();
+        assert_eq!(output.stdout, "yes\n");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_executable_with_sidecar_is_listed_and_run_directly() {
+        use crate::adapters::workspace_repository::{stray_sidecars, FsWorkspaceRepository};
+        use crate::ports::ScriptRepository;
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("omakure-runner-exec-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let tool = root.join("tool");
+        fs::write(&tool, "#!/bin/sh\necho \"tool $*\"\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(
+            root.join("tool.omakure.json"),
+            r#"{"Name": "tool", "Fields": []}"#,
+        )
+        .unwrap();
+        fs::write(root.join("notes"), "not runnable").unwrap();
+        fs::write(root.join("notes.omakure.json"), r#"{"Name": "notes"}"#).unwrap();
+
+        let repo = FsWorkspaceRepository::new(&root);
+        assert_eq!(repo.list_scripts_recursive().unwrap(), vec![tool.clone()]);
+        assert_eq!(repo.read_schema(&tool).unwrap().name, "tool");
+        assert_eq!(
+            stray_sidecars(&root).unwrap(),
+            vec![root.join("notes.omakure.json")]
+        );
+        let output = MultiScriptRunner::new(&root)
+            .run(&tool, &["--a".to_string()], None, false, &[], &mut Answer)
+            .unwrap();
+        assert_eq!(output.stdout, "tool --a\n");
+        let _ = fs::remove_dir_all(root);
+    }
 }
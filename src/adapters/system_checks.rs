@@ -1,5 +1,8 @@
+use std::env;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::domain::{extract_version, version_at_least, Requirement};
 use crate::error::ScriptError;
 use crate::runtime::{powershell_program, python_program};
 
@@ -65,10 +68,6 @@ pub(crate) fn ensure_bash_installed() -> Result<(), ScriptError> {
     )
 }
 
-pub(crate) fn ensure_jq_installed() -> Result<(), ScriptError> {
-    ensure_command("jq", &["--version"], "Install jq and ensure it is in PATH")
-}
-
 pub(crate) fn ensure_powershell_installed() -> Result<(), ScriptError> {
     let program = powershell_program();
     ensure_command(
@@ -86,3 +85,120 @@ pub(crate) fn ensure_python_installed() -> Result<(), ScriptError> {
         &format!("Install Python and ensure {} is in PATH", program),
     )
 }
+
+/// Check a schema `Requires` entry: presence on PATH, then the version when a minimum is set.
+pub(crate) fn check_requirement(requirement: &Requirement) -> Result<(), ScriptError> {
+    let name = requirement.command.as_str();
+    let hint = requirement
+        .hint
+        .clone()
+        .unwrap_or_else(|| format!("Install {} and ensure it is in PATH", name));
+    let Some(program) = find_in_path(name) else {
+        return Err(ScriptError::DependencyMissing {
+            name: name.to_string(),
+            hint,
+        });
+    };
+    let Some(minimum) = &requirement.min_version else {
+        return Ok(());
+    };
+
+    let default_args = vec!["--version".to_string()];
+    let args = requirement.version_args.as_ref().unwrap_or(&default_args);
+    let output = Command::new(program).args(args).output().map_err(|err| {
+        ScriptError::DependencyCheckFailed {
+            name: name.to_string(),
+            message: err.to_string(),
+        }
+    })?;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let found = extract_version(&text).ok_or_else(|| ScriptError::DependencyCheckFailed {
+        name: name.to_string(),
+        message: format!(
+            "could not read a version from `{} {}`",
+            name,
+            args.join(" ")
+        ),
+    })?;
+    if version_at_least(&found, minimum) {
+        Ok(())
+    } else {
+        Err(ScriptError::DependencyOutdated {
+            name: name.to_string(),
+            found,
+            required: minimum.clone(),
+            hint,
+        })
+    }
+}
+
+/// Resolve a command the way the shell would, without running it.
+pub(crate) fn find_in_path(command: &str) -> Option<PathBuf> {
+    let direct = Path::new(command);
+    if direct.components().count() > 1 {
+        return is_executable(direct).then(|| direct.to_path_buf());
+    }
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths).find_map(|dir| {
+        executable_names(command)
+            .into_iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| is_executable(candidate))
+    })
+}
+
+/// A regular file the shell would run: on unix, one with an execute bit set.
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(windows)]
+fn executable_names(command: &str) -> Vec<String> {
+    let extensions = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let mut names = vec![command.to_string()];
+    names.extend(
+        extensions
+            .split(';')
+            .filter(|ext| !ext.is_empty())
+            .map(|ext| format!("{}{}", command, ext.to_ascii_lowercase())),
+    );
+    names
+}
+
+#[cfg(not(windows))]
+fn executable_names(command: &str) -> Vec<String> {
+    vec![command.to_string()]
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_find_in_path_skips_files_without_execute_bit() {
+        let dir = std::env::temp_dir().join(format!("omakure-find-in-path-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let tool = dir.join("tool");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(find_in_path(tool.to_str().unwrap()), None);
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(find_in_path(tool.to_str().unwrap()), Some(tool));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
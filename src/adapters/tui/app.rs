@@ -1,19 +1,35 @@
 use crate::adapters::environments::FsEnvironmentRepository;
-use crate::domain::Schema;
-use crate::history::HistoryEntry;
+use crate::adapters::system_checks::check_requirement;
+use crate::adapters::workspace_repository::sidecar_owner;
+use crate::domain::{Requirement, Schema};
+use crate::history::{self, HistoryEntry};
+use crate::lint::lint_workspace;
+use crate::lua_api::{LuaContext, SandboxPolicy};
+use crate::lua_hooks::{HookedRun, Hooks, Trigger};
 use crate::lua_widget::{self, WidgetData};
 use crate::ports::{WorkspaceEntry, WorkspaceEntryKind};
-use crate::search_index::SearchIndex;
+use crate::presets::{self, Preset};
+use crate::runtime;
+use crate::search_index::{SearchIndex, SearchStatus};
 use crate::use_cases::{EnvironmentService, ScriptService};
 use crate::workspace::Workspace;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, TryRecvError};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::time::Instant;
 
+use super::clipboard;
 pub(crate) use super::state::HistoryFocus;
 use super::state::{
-    EnvironmentState, FieldInputState, HistoryState, NavigationState, SearchState, WidgetLoadResult,
+    EnvironmentState, FieldInputState, HistoryState, JsonViewState, NavigationState, OutputView,
+    PendingPrompt, PresetDialog, ProblemsState, RunMessage, RunningState, SearchState,
+    WidgetLoadResult,
 };
 use super::theme::Theme;
+use super::watcher::WatchChanges;
+
+/// A finished `Requires` check: the requirement and what is wrong with it, if anything.
+type RequirementCheck = (Requirement, Option<String>);
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub(crate) enum Screen {
@@ -22,6 +38,7 @@ pub(crate) enum Screen {
     Environments,
     FieldInput,
     History,
+    Problems,
     Running,
     RunResult,
     Error,
@@ -35,6 +52,15 @@ pub(crate) struct SchemaPreview {
     pub(crate) fields: Vec<SchemaFieldPreview>,
     pub(crate) outputs: Vec<SchemaOutputPreview>,
     pub(crate) queue: Option<QueuePreview>,
+    pub(crate) requires: Vec<RequirementPreview>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct RequirementPreview {
+    pub(crate) label: String,
+    pub(crate) problem: Option<String>,
+    /// Set until the background check reports.
+    pub(crate) checking: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -93,11 +119,27 @@ pub(crate) struct App<'a> {
     pub(crate) environment: EnvironmentState,
     pub(crate) search: SearchState,
     pub(crate) history: HistoryState,
+    pub(crate) problems: ProblemsState,
     pub(crate) field_input: FieldInputState,
-    pub(crate) result: Option<(PathBuf, Vec<String>)>,
+    pub(crate) json_view: JsonViewState,
+    pub(crate) running: RunningState,
+    pub(crate) result: Option<(PathBuf, Vec<String>, Option<String>)>,
+    /// Runs requested by `after_run` hooks, started one after another.
+    trigger_queue: VecDeque<Trigger>,
+    /// Set while a hook-triggered run is active; its own triggers are ignored.
+    triggered_run: bool,
+    /// `Requires` results for the schema preview, so moving the selection doesn't spawn
+    /// `--version` checks again; cleared by a status refresh.
+    requirement_checks: HashMap<Requirement, Option<String>>,
+    /// Checks running on background threads, reporting through `requirement_results`.
+    requirement_pending: HashSet<Requirement>,
+    requirement_results: (Sender<RequirementCheck>, Receiver<RequirementCheck>),
     pub(crate) should_quit: bool,
     pub(crate) run_output_scroll: u16,
+    pub(crate) output_groups_expanded: bool,
     pub(crate) error_message: Option<String>,
+    /// Shown in the script list footer when live refresh fell back to polling or is off.
+    pub(crate) watch_notice: Option<String>,
 }
 
 impl<'a> App<'a> {
@@ -127,14 +169,24 @@ impl<'a> App<'a> {
             environment,
             search,
             history,
+            problems: ProblemsState::new(),
             field_input,
+            json_view: JsonViewState::new(),
+            running: RunningState::new(),
             result: None,
+            trigger_queue: VecDeque::new(),
+            triggered_run: false,
+            requirement_checks: HashMap::new(),
+            requirement_pending: HashSet::new(),
+            requirement_results: mpsc::channel(),
             should_quit: false,
             run_output_scroll: 0,
+            output_groups_expanded: false,
             error_message: None,
+            watch_notice: None,
         };
-        app.start_widget_load();
         app.load_env_config();
+        app.start_widget_load();
         app.update_schema_preview();
         app.update_env_preview();
         app
@@ -218,7 +270,10 @@ impl<'a> App<'a> {
             .clone();
         let service = self.environment_service();
         match service.set_active_env(Some(&name)) {
-            Ok(()) => self.load_env_config(),
+            Ok(()) => {
+                self.load_env_config();
+                self.start_widget_load();
+            }
             Err(err) => self.environment.error = Some(err.to_string()),
         }
     }
@@ -226,7 +281,10 @@ impl<'a> App<'a> {
     pub(crate) fn deactivate_env(&mut self) {
         let service = self.environment_service();
         match service.set_active_env(None) {
-            Ok(()) => self.load_env_config(),
+            Ok(()) => {
+                self.load_env_config();
+                self.start_widget_load();
+            }
             Err(err) => self.environment.error = Some(err.to_string()),
         }
     }
@@ -234,8 +292,13 @@ impl<'a> App<'a> {
     pub(crate) fn refresh_search_status(&mut self) {
         let status = self.search_index.status();
         if status != self.search.status {
+            // Progress ticks alone don't change what a query returns.
+            let still_indexing = matches!(
+                (&self.search.status, &status),
+                (SearchStatus::Indexing { .. }, SearchStatus::Indexing { .. })
+            );
             self.search.status = status.clone();
-            if self.screen == Screen::Search {
+            if self.screen == Screen::Search && !still_indexing {
                 self.refresh_search_results();
             }
         }
@@ -276,6 +339,66 @@ impl<'a> App<'a> {
         self.load_schema(script_path);
     }
 
+    pub(crate) fn enter_problems(&mut self) {
+        self.screen = Screen::Problems;
+        self.refresh_problems();
+    }
+
+    pub(crate) fn refresh_problems(&mut self) {
+        match lint_workspace(self.workspace.root()) {
+            Ok(problems) => {
+                self.problems.problems = problems;
+                self.problems.error = None;
+            }
+            Err(err) => {
+                self.problems.problems.clear();
+                self.problems.error = Some(err.to_string());
+            }
+        }
+        let last = self.problems.problems.len().saturating_sub(1);
+        self.problems.selection = self.problems.selection.min(last);
+        self.problems
+            .list_state
+            .select((!self.problems.problems.is_empty()).then_some(self.problems.selection));
+    }
+
+    pub(crate) fn move_problem_selection(&mut self, delta: isize) {
+        if self.problems.problems.is_empty() {
+            return;
+        }
+        let last = self.problems.problems.len() as isize - 1;
+        self.problems.selection =
+            (self.problems.selection as isize + delta).clamp(0, last) as usize;
+        self.problems
+            .list_state
+            .select(Some(self.problems.selection));
+    }
+
+    /// Shows the script of the selected problem in the list, with its schema preview.
+    pub(crate) fn open_selected_problem(&mut self) {
+        let Some(problem) = self.problems.problems.get(self.problems.selection) else {
+            return;
+        };
+        let path = self.workspace.root().join(&problem.script);
+        let script = sidecar_owner(&path).unwrap_or(path);
+        let Some(dir) = script.parent() else {
+            return;
+        };
+        self.navigation.current_dir = dir.to_path_buf();
+        self.refresh_entries();
+        if let Some(index) = self
+            .navigation
+            .entries
+            .iter()
+            .position(|entry| entry.path == script)
+        {
+            self.navigation.selection = index;
+            self.navigation.list_state.select(Some(index));
+            self.update_schema_preview();
+        }
+        self.screen = Screen::ScriptSelect;
+    }
+
     pub(crate) fn enter_selected(&mut self) {
         let entry = match self.selected_entry() {
             Some(entry) => entry.clone(),
@@ -303,6 +426,26 @@ impl<'a> App<'a> {
         }
     }
 
+    /// Runs the selected history entry again with the arguments and stdin it was run with.
+    pub(crate) fn rerun_history_entry(&mut self) {
+        let Some(entry) = self.history.entries.get(self.history.selection) else {
+            return;
+        };
+        if entry.stdin_truncated {
+            self.error_message = Some(format!(
+                "Can't rerun {}: its stdin was cut at [history] stdin_limit",
+                entry.script.display()
+            ));
+            self.screen = Screen::Error;
+            return;
+        }
+        self.result = Some((
+            self.workspace.root().join(&entry.script),
+            entry.args.clone(),
+            entry.stdin.clone(),
+        ));
+    }
+
     pub(crate) fn move_history_selection(&mut self, delta: isize) {
         if self.history.entries.is_empty() {
             return;
@@ -341,30 +484,23 @@ impl<'a> App<'a> {
             Ok(mut schema) => {
                 self.load_env_config();
                 schema.fields.sort_by_key(|field| field.order);
-                let tags = schema.tags.clone();
-                let outputs = schema.outputs.clone();
-                let queue = schema.queue.clone();
+                self.navigation.schema_cache = Some((script.clone(), schema.clone()));
                 self.field_input.schema_name = Some(schema.name);
                 self.field_input.schema_description = schema.description;
+                self.field_input.output_format = schema.output_format;
+                self.field_input.stdin = schema.stdin;
+                self.field_input.stdin_payload = None;
                 self.field_input.fields = schema.fields;
                 self.field_input.field_index = 0;
                 self.field_input.field_inputs = self.build_field_inputs();
                 self.field_input.args.clear();
                 self.field_input.error = None;
                 self.field_input.selected_script = Some(script.clone());
-                self.navigation.schema_cache = Some((
-                    script.clone(),
-                    Schema {
-                        name: self.field_input.schema_name.clone().unwrap_or_default(),
-                        description: self.field_input.schema_description.clone(),
-                        tags,
-                        fields: self.field_input.fields.clone(),
-                        outputs,
-                        queue,
-                    },
-                ));
+                self.field_input.preset_dialog = None;
+                self.field_input.message = None;
+                self.load_presets();
                 if self.field_input.fields.is_empty() {
-                    self.result = Some((script, Vec::new()));
+                    self.result = Some((script, Vec::new(), None));
                 } else {
                     self.screen = Screen::FieldInput;
                 }
@@ -421,6 +557,7 @@ impl<'a> App<'a> {
         }
 
         let mut args = Vec::new();
+        let mut stdin_payload = None;
         for (idx, field) in self.field_input.fields.iter().enumerate() {
             let input = self
                 .field_input
@@ -430,13 +567,25 @@ impl<'a> App<'a> {
                 .unwrap_or("");
             match crate::domain::normalize_input(field, input) {
                 Ok(value) => {
-                    if let Some(value) = value {
-                        let arg = field
-                            .arg
-                            .clone()
-                            .unwrap_or_else(|| format!("--{}", field.name));
-                        args.push(arg);
-                        args.push(value);
+                    let stdin = self
+                        .field_input
+                        .stdin
+                        .as_ref()
+                        .filter(|stdin| stdin.field == field.name);
+                    if let Some(stdin) = stdin {
+                        let Some(value) = value else {
+                            continue;
+                        };
+                        match runtime::stdin_payload(stdin.source, &value, self.workspace.root()) {
+                            Ok(payload) => stdin_payload = Some(payload),
+                            Err(err) => {
+                                self.field_input.error = Some(format!("{}: {}", field.name, err));
+                                self.field_input.field_index = idx;
+                                return;
+                            }
+                        }
+                    } else if let Some(value) = value {
+                        args.extend(field.cli_args(value));
                     }
                 }
                 Err(message) => {
@@ -448,13 +597,18 @@ impl<'a> App<'a> {
         }
 
         self.field_input.args = args;
+        self.field_input.stdin_payload = stdin_payload;
         self.field_input.error = None;
         self.finish();
     }
 
     fn finish(&mut self) {
         if let Some(script) = &self.field_input.selected_script {
-            self.result = Some((script.clone(), self.field_input.args.clone()));
+            self.result = Some((
+                script.clone(),
+                self.field_input.args.clone(),
+                self.field_input.stdin_payload.clone(),
+            ));
         } else {
             self.should_quit = true;
         }
@@ -481,9 +635,66 @@ impl<'a> App<'a> {
         }
     }
 
+    /// Applies a debounced batch of filesystem changes reported by the workspace watcher.
+    pub(crate) fn apply_watch_changes(&mut self, changes: WatchChanges) {
+        if changes.theme {
+            self.theme = super::configured_theme(&self.workspace);
+        }
+        if changes.envs {
+            self.load_env_config();
+        }
+        if changes.widgets || changes.envs {
+            self.start_widget_load();
+        }
+        if changes.scripts {
+            self.navigation.schema_cache = None;
+            self.navigation.preview_script = None;
+            self.reload_entries();
+            self.search_index
+                .start_background_rebuild(self.workspace.root().to_path_buf());
+            if self.screen == Screen::Problems {
+                self.refresh_problems();
+            }
+        } else if changes.envs {
+            self.navigation.preview_script = None;
+            self.update_schema_preview();
+        }
+    }
+
+    /// Re-lists the current folder, keeping the selection on the same entry when it still exists.
+    fn reload_entries(&mut self) {
+        let selected = self.selected_entry().map(|entry| entry.path.clone());
+        let Ok(entries) = self.service.list_entries(&self.navigation.current_dir) else {
+            return;
+        };
+        self.navigation.entries = entries;
+        self.navigation.selection = selected
+            .and_then(|path| {
+                self.navigation
+                    .entries
+                    .iter()
+                    .position(|entry| entry.path == path)
+            })
+            .unwrap_or(0)
+            .min(self.navigation.entries.len().saturating_sub(1));
+        self.navigation
+            .list_state
+            .select(if self.navigation.entries.is_empty() {
+                None
+            } else {
+                Some(self.navigation.selection)
+            });
+        self.update_schema_preview();
+    }
+
     pub(crate) fn refresh_status(&mut self) {
-        self.start_widget_load();
         self.load_env_config();
+        self.start_widget_load();
+        self.requirement_checks.clear();
+        // Checks still running report into the old channel and are dropped with it.
+        self.requirement_pending.clear();
+        self.requirement_results = mpsc::channel();
+        self.navigation.preview_script = None;
         self.update_schema_preview();
     }
 
@@ -491,15 +702,353 @@ impl<'a> App<'a> {
         self.screen = Screen::ScriptSelect;
         self.field_input.schema_name = None;
         self.field_input.schema_description = None;
+        self.field_input.output_format = None;
+        self.field_input.stdin = None;
+        self.field_input.stdin_payload = None;
         self.field_input.fields.clear();
         self.field_input.field_index = 0;
         self.field_input.field_inputs.clear();
         self.field_input.args.clear();
         self.field_input.error = None;
         self.field_input.selected_script = None;
+        self.field_input.presets.clear();
+        self.field_input.preset_dialog = None;
+        self.field_input.message = None;
         self.result = None;
     }
 
+    fn active_env_name(&self) -> Option<String> {
+        self.environment
+            .config
+            .as_ref()
+            .and_then(|config| config.active.clone())
+    }
+
+    fn load_presets(&mut self) {
+        let Some(script) = &self.field_input.selected_script else {
+            return;
+        };
+        match presets::load_presets(&self.workspace, script) {
+            Ok(saved) => {
+                self.field_input.presets =
+                    presets::available(&saved, self.active_env_name().as_deref());
+            }
+            Err(err) => {
+                self.field_input.presets.clear();
+                self.field_input.error = Some(err);
+            }
+        }
+    }
+
+    pub(crate) fn open_preset_save(&mut self) {
+        self.field_input.message = None;
+        self.field_input.preset_dialog = Some(PresetDialog::Save {
+            name: String::new(),
+            scoped: false,
+        });
+    }
+
+    pub(crate) fn open_preset_picker(&mut self) {
+        if self.field_input.presets.is_empty() {
+            self.field_input.message =
+                Some("No presets for this script yet; Ctrl+S saves one.".to_string());
+            return;
+        }
+        self.field_input.message = None;
+        self.field_input.preset_dialog = Some(PresetDialog::Pick { selection: 0 });
+    }
+
+    pub(crate) fn close_preset_dialog(&mut self) {
+        self.field_input.preset_dialog = None;
+    }
+
+    pub(crate) fn append_preset_char(&mut self, ch: char) {
+        if let Some(PresetDialog::Save { name, .. }) = self.field_input.preset_dialog.as_mut() {
+            name.push(ch);
+        }
+    }
+
+    pub(crate) fn pop_preset_char(&mut self) {
+        if let Some(PresetDialog::Save { name, .. }) = self.field_input.preset_dialog.as_mut() {
+            name.pop();
+        }
+    }
+
+    /// Scopes the preset being saved to the active environment, when there is one.
+    pub(crate) fn toggle_preset_scope(&mut self) {
+        let has_env = self.active_env_name().is_some();
+        if let Some(PresetDialog::Save { scoped, .. }) = self.field_input.preset_dialog.as_mut() {
+            *scoped = has_env && !*scoped;
+        }
+    }
+
+    pub(crate) fn move_preset_selection(&mut self, delta: isize) {
+        let last = self.field_input.presets.len().saturating_sub(1) as isize;
+        if let Some(PresetDialog::Pick { selection }) = self.field_input.preset_dialog.as_mut() {
+            *selection = (*selection as isize + delta).clamp(0, last) as usize;
+        }
+    }
+
+    /// Saves the form values under the typed name, or applies the picked preset.
+    pub(crate) fn confirm_preset_dialog(&mut self) {
+        let Some(script) = self.field_input.selected_script.clone() else {
+            return;
+        };
+        match self.field_input.preset_dialog.take() {
+            Some(PresetDialog::Save { name, scoped }) => {
+                let name = name.trim();
+                if name.is_empty() {
+                    self.field_input.preset_dialog = Some(PresetDialog::Save {
+                        name: String::new(),
+                        scoped,
+                    });
+                    return;
+                }
+                let env = if scoped { self.active_env_name() } else { None };
+                let preset = Preset::from_inputs(
+                    name,
+                    env,
+                    &self.field_input.fields,
+                    &self.field_input.field_inputs,
+                );
+                let label = preset.label();
+                match presets::save_preset(&self.workspace, &script, preset) {
+                    Ok(_) => {
+                        self.field_input.message = Some(format!("Saved preset {}.", label));
+                        self.load_presets();
+                    }
+                    Err(err) => self.field_input.error = Some(err),
+                }
+            }
+            Some(PresetDialog::Pick { selection }) => {
+                let Some(preset) = self.field_input.presets.get(selection).cloned() else {
+                    return;
+                };
+                for (field, input) in self
+                    .field_input
+                    .fields
+                    .iter()
+                    .zip(self.field_input.field_inputs.iter_mut())
+                {
+                    if let Some(value) = preset.value_for(field) {
+                        *input = value.to_string();
+                    }
+                }
+                self.field_input.error = None;
+                self.field_input.message = Some(format!("Applied preset {}.", preset.label()));
+            }
+            None => {}
+        }
+    }
+
+    pub(crate) fn delete_selected_preset(&mut self) {
+        let Some(script) = self.field_input.selected_script.clone() else {
+            return;
+        };
+        let Some(PresetDialog::Pick { selection }) = &self.field_input.preset_dialog else {
+            return;
+        };
+        let Some(preset) = self.field_input.presets.get(*selection).cloned() else {
+            return;
+        };
+        match presets::delete_preset(
+            &self.workspace,
+            &script,
+            &preset.name,
+            preset.env.as_deref(),
+        ) {
+            Ok(_) => {
+                self.field_input.message = Some(format!("Deleted preset {}.", preset.label()));
+                self.load_presets();
+                if self.field_input.presets.is_empty() {
+                    self.field_input.preset_dialog = None;
+                } else {
+                    self.move_preset_selection(0);
+                }
+            }
+            Err(err) => {
+                self.field_input.preset_dialog = None;
+                self.field_input.error = Some(err);
+            }
+        }
+    }
+
+    pub(crate) fn start_run(
+        &mut self,
+        script: PathBuf,
+        args: Vec<String>,
+        stdin: Option<String>,
+        receiver: Receiver<RunMessage>,
+    ) {
+        self.running = RunningState::new();
+        self.running.script = Some(script);
+        self.running.args = args;
+        self.running.stdin = stdin;
+        self.running.receiver = Some(receiver);
+        self.running.started_at = Some(Instant::now());
+        self.screen = Screen::Running;
+    }
+
+    /// Drains output from the running script and finalizes the run once it exits.
+    pub(crate) fn poll_run(&mut self) {
+        let Some(receiver) = self.running.receiver.take() else {
+            return;
+        };
+
+        loop {
+            match receiver.try_recv() {
+                Ok(RunMessage::Line(line)) => self.running.apply_line(line),
+                Ok(RunMessage::Prompt(prompt, reply)) => {
+                    self.running.prompt = Some(PendingPrompt {
+                        field: prompt.to_field(),
+                        input: String::new(),
+                        error: None,
+                        reply,
+                    });
+                }
+                Ok(RunMessage::Finished(run)) => {
+                    self.finish_run(*run);
+                    return;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finish_run(HookedRun {
+                        args: self.running.args.clone(),
+                        result: Err("Script runner stopped unexpectedly".into()),
+                        notes: Vec::new(),
+                        triggers: Vec::new(),
+                    });
+                    return;
+                }
+            }
+        }
+        self.running.receiver = Some(receiver);
+    }
+
+    fn finish_run(&mut self, run: HookedRun) {
+        let script = self.running.script.clone().unwrap_or_default();
+        let mut entry = match run.result {
+            Ok(output) => history::success_entry(&self.workspace, &script, &run.args, output),
+            Err(err) => history::error_entry(&self.workspace, &script, &run.args, err.to_string()),
+        };
+        entry.notes = run.notes;
+        if !self.triggered_run {
+            self.trigger_queue.extend(run.triggers);
+        }
+        history::attach_stdin(&self.workspace, &mut entry, self.running.stdin.as_deref());
+        let _ = history::record_entry(&self.workspace, &entry);
+        self.add_history_entry(entry);
+        self.load_run_result_view();
+        self.back_to_script_select();
+        self.reset_run_output_scroll();
+        self.screen = Screen::RunResult;
+        // Queued after the reset above, which clears `result`.
+        self.triggered_run = false;
+        if let Some(trigger) = self.trigger_queue.pop_front() {
+            self.result = Some((trigger.script, trigger.args, None));
+            self.triggered_run = true;
+        }
+    }
+
+    pub(crate) fn append_prompt_char(&mut self, ch: char) {
+        if let Some(prompt) = self.running.prompt.as_mut() {
+            prompt.input.push(ch);
+            prompt.error = None;
+        }
+    }
+
+    pub(crate) fn pop_prompt_char(&mut self) {
+        if let Some(prompt) = self.running.prompt.as_mut() {
+            prompt.input.pop();
+            prompt.error = None;
+        }
+    }
+
+    /// Validates the prompt answer like a form field and hands it to the script.
+    pub(crate) fn submit_prompt(&mut self) {
+        let Some(prompt) = self.running.prompt.as_mut() else {
+            return;
+        };
+        match crate::domain::normalize_input(&prompt.field, &prompt.input) {
+            Ok(value) => {
+                let _ = prompt.reply.send(Some(value.unwrap_or_default()));
+                self.running.prompt = None;
+            }
+            Err(message) => prompt.error = Some(message.to_string()),
+        }
+    }
+
+    /// Declines the prompt, which stops the run.
+    pub(crate) fn cancel_prompt(&mut self) {
+        if let Some(prompt) = self.running.prompt.take() {
+            let _ = prompt.reply.send(None);
+        }
+    }
+
+    pub(crate) fn toggle_output_groups(&mut self) {
+        self.output_groups_expanded = !self.output_groups_expanded;
+    }
+
+    /// Prepares the structured JSON view for the latest run output.
+    pub(crate) fn load_run_result_view(&mut self) {
+        let prefer = self
+            .field_input
+            .output_format
+            .as_deref()
+            .map(|format| format.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        let stdout = self
+            .history
+            .entries
+            .first()
+            .map(|entry| entry.stdout.as_str())
+            .unwrap_or("");
+        self.json_view.load(stdout, prefer);
+    }
+
+    pub(crate) fn copy_output_cell(&mut self) {
+        let text = self.json_view.selected_value_text();
+        self.copy_output_text(text, "Copied value to clipboard.");
+    }
+
+    pub(crate) fn copy_output_row(&mut self) {
+        if self.json_view.view != OutputView::Table {
+            self.copy_output_cell();
+            return;
+        }
+        let text = self.json_view.selected_row_text();
+        self.copy_output_text(text, "Copied row to clipboard.");
+    }
+
+    pub(crate) fn export_output_csv(&mut self) {
+        let Some(table) = self.json_view.table.as_ref() else {
+            self.json_view.message = Some("Only tabular output can be exported.".to_string());
+            return;
+        };
+        let csv = table.to_csv(&self.json_view.visible_columns());
+        let name = self
+            .history
+            .entries
+            .first()
+            .map(|entry| format!("{}-output.csv", entry.timestamp))
+            .unwrap_or_else(|| "output.csv".to_string());
+        let path = self.workspace.history_dir().join(name);
+        self.json_view.message = Some(match std::fs::write(&path, csv) {
+            Ok(()) => format!("Exported {}", self.display_path(&path)),
+            Err(err) => format!("Export failed: {}", err),
+        });
+    }
+
+    fn copy_output_text(&mut self, text: Option<String>, success: &str) {
+        let Some(text) = text else {
+            return;
+        };
+        self.json_view.message = Some(match clipboard::copy_to_clipboard(&text) {
+            Ok(()) => success.to_string(),
+            Err(err) => format!("Copy failed: {}", err),
+        });
+    }
+
     pub(crate) fn reset_run_output_scroll(&mut self) {
         self.run_output_scroll = 0;
     }
@@ -520,15 +1069,54 @@ impl<'a> App<'a> {
             .to_string()
     }
 
+    /// Collects the `hooks.lua` files that wrap a run of `script`.
+    pub(crate) fn hooks_for(&self, script: &Path) -> Hooks {
+        Hooks::discover(&self.workspace, script, self.active_env_defaults())
+    }
+
+    fn active_env_defaults(&self) -> HashMap<String, String> {
+        self.environment
+            .config
+            .as_ref()
+            .map(|config| config.defaults.clone())
+            .unwrap_or_default()
+    }
+
     fn start_widget_load(&mut self) {
+        self.navigation.widget = None;
+        self.navigation.widget_error = None;
+        self.navigation.widget_refresh = None;
+        self.spawn_widget_load();
+    }
+
+    /// Re-runs a widget that asked for `refresh`, keeping its current content on screen.
+    pub(crate) fn tick_widget_refresh(&mut self) {
+        if self.screen != Screen::ScriptSelect {
+            return;
+        }
+        let (Some(interval), Some(loaded_at)) = (
+            self.navigation.widget_refresh,
+            self.navigation.widget_loaded_at,
+        ) else {
+            return;
+        };
+        if !self.navigation.widget_loading && loaded_at.elapsed() >= interval {
+            self.spawn_widget_load();
+        }
+    }
+
+    fn spawn_widget_load(&mut self) {
         let dir = self.navigation.current_dir.clone();
+        let context = LuaContext {
+            workspace_root: self.workspace.root().to_path_buf(),
+            env: self.active_env_defaults(),
+            sandbox: SandboxPolicy::for_dir(&self.workspace, &dir),
+        };
         let (tx, rx) = mpsc::channel();
         self.navigation.widget_loading = true;
-        self.navigation.widget = None;
-        self.navigation.widget_error = None;
         self.navigation.widget_receiver = Some(rx);
         std::thread::spawn(move || {
-            let (widget, error) = load_widget_state(&dir);
+            let (widget, error) = load_widget_state(&dir, &context);
             let _ = tx.send(WidgetLoadResult { widget, error });
         });
     }
@@ -540,6 +1128,12 @@ impl<'a> App<'a> {
 
         match receiver.try_recv() {
             Ok(result) => {
+                // A failed reload keeps the previous interval so the widget can recover.
+                if result.error.is_none() {
+                    self.navigation.widget_refresh =
+                        result.widget.as_ref().and_then(|widget| widget.refresh);
+                }
+                self.navigation.widget_loaded_at = Some(Instant::now());
                 self.navigation.widget = result.widget;
                 self.navigation.widget_error = result.error;
                 self.navigation.widget_loading = false;
@@ -688,6 +1282,57 @@ impl<'a> App<'a> {
         }
     }
 
+    /// Runs `<command> --version` off the UI thread, once per requirement until a refresh.
+    fn spawn_requirement_check(&mut self, requirement: &Requirement) {
+        if self.requirement_checks.contains_key(requirement)
+            || !self.requirement_pending.insert(requirement.clone())
+        {
+            return;
+        }
+        let requirement = requirement.clone();
+        let sender = self.requirement_results.0.clone();
+        std::thread::spawn(move || {
+            let problem = check_requirement(&requirement)
+                .err()
+                .map(|err| err.to_string());
+            let _ = sender.send((requirement, problem));
+        });
+    }
+
+    /// Collects finished requirement checks and updates the schema preview with them.
+    pub(crate) fn poll_requirement_checks(&mut self) {
+        let mut changed = false;
+        while let Ok((requirement, problem)) = self.requirement_results.1.try_recv() {
+            self.requirement_pending.remove(&requirement);
+            self.requirement_checks.insert(requirement, problem);
+            changed = true;
+        }
+        if changed {
+            self.fill_requirement_checks();
+        }
+    }
+
+    fn fill_requirement_checks(&mut self) {
+        let (Some(preview), Some((path, schema))) = (
+            self.navigation.schema_preview.as_mut(),
+            self.navigation.schema_cache.as_ref(),
+        ) else {
+            return;
+        };
+        if self.navigation.preview_script.as_ref() != Some(path) {
+            return;
+        }
+        for (requirement, item) in schema.requires.iter().flatten().zip(&mut preview.requires) {
+            match self.requirement_checks.get(requirement) {
+                Some(problem) => {
+                    item.problem = problem.clone();
+                    item.checking = false;
+                }
+                None => item.checking = true,
+            }
+        }
+    }
+
     fn update_schema_preview(&mut self) {
         let (entry_path, entry_kind) = match self.selected_entry() {
             Some(entry) => (entry.path.clone(), entry.kind),
@@ -710,17 +1355,31 @@ impl<'a> App<'a> {
             return;
         }
 
-        match self.service.load_schema(&entry_path) {
+        let loaded = match self
+            .search_index
+            .cached_schema(&entry_path, self.workspace.root())
+        {
+            Some(cached) => cached,
+            None => self
+                .service
+                .load_schema(&entry_path)
+                .map_err(|err| err.to_string()),
+        };
+        match loaded {
             Ok(mut schema) => {
                 schema.fields.sort_by_key(|field| field.order);
+                for requirement in schema.requires.iter().flatten() {
+                    self.spawn_requirement_check(requirement);
+                }
                 self.navigation.schema_preview = Some(schema_to_preview(&schema));
                 self.navigation.schema_preview_error = None;
                 self.navigation.preview_script = Some(entry_path.clone());
                 self.navigation.schema_cache = Some((entry_path, schema));
+                self.fill_requirement_checks();
             }
             Err(err) => {
                 self.navigation.schema_preview = None;
-                self.navigation.schema_preview_error = Some(err.to_string());
+                self.navigation.schema_preview_error = Some(err);
                 self.navigation.preview_script = Some(entry_path);
             }
         }
@@ -776,8 +1435,8 @@ impl ExecutionStatus {
     }
 }
 
-fn load_widget_state(dir: &Path) -> (Option<WidgetData>, Option<String>) {
-    match lua_widget::load_widget(dir) {
+fn load_widget_state(dir: &Path, context: &LuaContext) -> (Option<WidgetData>, Option<String>) {
+    match lua_widget::load_widget(dir, context) {
         Ok(widget) => (widget, None),
         Err(err) => (None, Some(err)),
     }
@@ -850,5 +1509,156 @@ fn schema_to_preview(schema: &Schema) -> SchemaPreview {
         fields,
         outputs,
         queue,
+        requires: schema
+            .requires
+            .iter()
+            .flatten()
+            .map(|requirement| RequirementPreview {
+                label: requirement.label(),
+                problem: None,
+                checking: false,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapters::script_runner::MultiScriptRunner;
+    use crate::adapters::tui::theme::default_theme;
+    use crate::adapters::workspace_repository::FsWorkspaceRepository;
+    use crate::ports::ScriptRunOutput;
+    use std::fs;
+    use std::time::Duration;
+
+    fn finished(args: Vec<String>, triggers: Vec<Trigger>) -> HookedRun {
+        HookedRun {
+            args,
+            result: Ok(ScriptRunOutput {
+                stdout: String::new(),
+                stderr: String::new(),
+                exit_code: Some(0),
+                success: true,
+                annotations: Vec::new(),
+            }),
+            notes: Vec::new(),
+            triggers,
+        }
+    }
+
+    #[test]
+    fn test_after_run_trigger_is_dispatched_once() {
+        let root = std::env::temp_dir().join(format!("omakure-app-trigger-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let workspace = Workspace::new(root.clone());
+        workspace.ensure_layout().unwrap();
+        fs::write(root.join("deploy.bash"), "echo deploy\n").unwrap();
+        fs::write(root.join("smoke.bash"), "echo smoke\n").unwrap();
+        fs::write(
+            root.join("hooks.lua"),
+            r#"function after_run(ctx, result)
+                 if ctx.script == "deploy.bash" then return { run = { script = "smoke" } } end
+               end"#,
+        )
+        .unwrap();
+        let service = ScriptService::new(
+            Box::new(FsWorkspaceRepository::new(root.clone())),
+            Box::new(MultiScriptRunner::new(&root)),
+        );
+        let search_index = SearchIndex::new(workspace.search_db_path());
+        let mut app = App::new(
+            &service,
+            workspace,
+            Vec::new(),
+            Vec::new(),
+            search_index,
+            default_theme(),
+        );
+
+        let deploy = root.join("deploy.bash");
+        let hooks = app.hooks_for(&deploy);
+        let run = finished(Vec::new(), Vec::new());
+        let after = hooks.after_run(&deploy, &Default::default(), &run.result, Duration::ZERO);
+        app.running.script = Some(deploy.clone());
+        app.finish_run(finished(Vec::new(), after.triggers));
+        assert_eq!(app.screen, Screen::RunResult);
+        assert_eq!(
+            app.result,
+            Some((root.join("smoke.bash"), Vec::new(), None))
+        );
+        assert!(app.triggered_run);
+
+        // The triggered run finishes; its own triggers are ignored and the flag is reset.
+        let (smoke, _, _) = app.result.take().unwrap();
+        app.running.script = Some(smoke.clone());
+        let again = Trigger {
+            script: smoke,
+            args: Vec::new(),
+        };
+        app.finish_run(finished(Vec::new(), vec![again.clone()]));
+        assert_eq!(app.result, None);
+        assert!(!app.triggered_run);
+
+        // A later manual run gets its triggers dispatched again.
+        app.running.script = Some(deploy);
+        app.finish_run(finished(Vec::new(), vec![again]));
+        assert!(app.result.is_some());
+        assert!(app.triggered_run);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_requirement_checks_report_in_the_background() {
+        let root =
+            std::env::temp_dir().join(format!("omakure-app-requires-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let workspace = Workspace::new(root.clone());
+        workspace.ensure_layout().unwrap();
+        let missing = format!("omakure-missing-tool-{}", std::process::id());
+        fs::write(
+            root.join("deploy.bash"),
+            format!(
+                "# OMAKURE_SCHEMA_START\n# {{\"Name\": \"deploy\", \"Fields\": [], \"Requires\": [{{\"Command\": \"{}\"}}]}}\n# OMAKURE_SCHEMA_END\n",
+                missing
+            ),
+        )
+        .unwrap();
+        let service = ScriptService::new(
+            Box::new(FsWorkspaceRepository::new(root.clone())),
+            Box::new(MultiScriptRunner::new(&root)),
+        );
+        let entries = service.list_entries(&root).unwrap();
+        let search_index = SearchIndex::new(workspace.search_db_path());
+        let mut app = App::new(
+            &service,
+            workspace,
+            entries,
+            Vec::new(),
+            search_index,
+            default_theme(),
+        );
+
+        app.navigation.selection = app
+            .navigation
+            .entries
+            .iter()
+            .position(|entry| entry.kind == WorkspaceEntryKind::Script)
+            .unwrap();
+        app.update_schema_preview();
+        let requires = |app: &App| {
+            let preview = app.navigation.schema_preview.as_ref();
+            preview.expect("schema preview").requires[0].clone()
+        };
+        assert!(requires(&app).checking);
+        let started = std::time::Instant::now();
+        while requires(&app).checking && started.elapsed() < Duration::from_secs(5) {
+            app.poll_requirement_checks();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let checked = requires(&app);
+        assert!(!checked.checking);
+        assert!(checked.problem.unwrap().contains(&missing));
+        let _ = fs::remove_dir_all(root);
     }
 }
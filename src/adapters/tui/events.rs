@@ -1,6 +1,7 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use super::app::{App, HistoryFocus, Screen};
+use super::state::{OutputView, PresetDialog};
 
 pub(crate) fn handle_key_event(app: &mut App, key: KeyEvent) {
     match app.screen {
@@ -9,7 +10,8 @@ pub(crate) fn handle_key_event(app: &mut App, key: KeyEvent) {
         Screen::Environments => handle_envs_key(app, key),
         Screen::FieldInput => handle_input_key(app, key),
         Screen::History => handle_history_key(app, key),
-        Screen::Running => {}
+        Screen::Problems => handle_problems_key(app, key),
+        Screen::Running => handle_running_key(app, key),
         Screen::RunResult => handle_run_result_key(app, key),
         Screen::Error => handle_error_key(app, key),
     }
@@ -40,6 +42,7 @@ fn handle_list_key(app: &mut App, key: KeyEvent) {
             app.history.focus = HistoryFocus::List;
             app.reset_run_output_scroll();
         }
+        KeyCode::Char('p') | KeyCode::Char('P') => app.enter_problems(),
         KeyCode::Backspace | KeyCode::Left => app.navigate_up(),
         _ if app.navigation.entries.is_empty() => {}
         KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
@@ -69,7 +72,22 @@ fn handle_search_key(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_problems_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.screen = Screen::ScriptSelect,
+        KeyCode::Char('r') | KeyCode::Char('R') | KeyCode::F(5) => app.refresh_problems(),
+        KeyCode::Down | KeyCode::Char('j') => app.move_problem_selection(1),
+        KeyCode::Up | KeyCode::Char('k') => app.move_problem_selection(-1),
+        KeyCode::Enter => app.open_selected_problem(),
+        _ => {}
+    }
+}
+
 fn handle_input_key(app: &mut App, key: KeyEvent) {
+    if app.field_input.preset_dialog.is_some() {
+        handle_preset_dialog_key(app, key);
+        return;
+    }
     match key.code {
         KeyCode::Esc => app.back_to_script_select(),
         KeyCode::Char('b') | KeyCode::Char('B')
@@ -78,6 +96,16 @@ fn handle_input_key(app: &mut App, key: KeyEvent) {
             app.back_to_script_select()
         }
         KeyCode::Enter => app.submit_form(),
+        KeyCode::Char('s') | KeyCode::Char('S')
+            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            app.open_preset_save()
+        }
+        KeyCode::Char('p') | KeyCode::Char('P')
+            if key.modifiers.contains(KeyModifiers::CONTROL) =>
+        {
+            app.open_preset_picker()
+        }
         KeyCode::Tab => app.move_field_selection(1),
         KeyCode::BackTab => app.move_field_selection(-1),
         KeyCode::Down => app.move_field_selection(1),
@@ -88,6 +116,43 @@ fn handle_input_key(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_preset_dialog_key(app: &mut App, key: KeyEvent) {
+    match app.field_input.preset_dialog {
+        Some(PresetDialog::Save { .. }) => match key.code {
+            KeyCode::Esc => app.close_preset_dialog(),
+            KeyCode::Enter => app.confirm_preset_dialog(),
+            KeyCode::Tab => app.toggle_preset_scope(),
+            KeyCode::Backspace => app.pop_preset_char(),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.append_preset_char(c)
+            }
+            _ => {}
+        },
+        Some(PresetDialog::Pick { .. }) => match key.code {
+            KeyCode::Esc => app.close_preset_dialog(),
+            KeyCode::Enter => app.confirm_preset_dialog(),
+            KeyCode::Down | KeyCode::Char('j') => app.move_preset_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => app.move_preset_selection(-1),
+            KeyCode::Delete | KeyCode::Char('d') => app.delete_selected_preset(),
+            _ => {}
+        },
+        None => {}
+    }
+}
+
+fn handle_running_key(app: &mut App, key: KeyEvent) {
+    if app.running.prompt.is_none() {
+        return;
+    }
+    match key.code {
+        KeyCode::Esc => app.cancel_prompt(),
+        KeyCode::Enter => app.submit_prompt(),
+        KeyCode::Backspace => app.pop_prompt_char(),
+        KeyCode::Char(c) => app.append_prompt_char(c),
+        _ => {}
+    }
+}
+
 fn handle_error_key(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Char('q') | KeyCode::Esc => app.should_quit = true,
@@ -114,6 +179,7 @@ fn handle_history_key(app: &mut App, key: KeyEvent) {
                 app.history.focus = HistoryFocus::Output;
                 app.reset_run_output_scroll();
             }
+            KeyCode::Char('r') => app.rerun_history_entry(),
             _ => {}
         },
         HistoryFocus::Output => match key.code {
@@ -127,13 +193,20 @@ fn handle_history_key(app: &mut App, key: KeyEvent) {
             KeyCode::PageUp => app.scroll_run_output(-10),
             KeyCode::Home => app.run_output_scroll = 0,
             KeyCode::End => app.run_output_scroll = u16::MAX,
+            KeyCode::Char('g') | KeyCode::Char('G') => app.toggle_output_groups(),
             _ => {}
         },
     }
 }
 
 fn handle_run_result_key(app: &mut App, key: KeyEvent) {
+    if app.json_view.view != OutputView::Text {
+        handle_json_view_key(app, key);
+        return;
+    }
     match key.code {
+        KeyCode::Char('v') | KeyCode::Char('V') => app.json_view.cycle_view(),
+        KeyCode::Char('g') | KeyCode::Char('G') => app.toggle_output_groups(),
         KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => app.screen = Screen::ScriptSelect,
         KeyCode::Char('h') | KeyCode::Char('H') => {
             app.screen = Screen::History;
@@ -149,6 +222,32 @@ fn handle_run_result_key(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn handle_json_view_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => app.screen = Screen::ScriptSelect,
+        KeyCode::Char('v') | KeyCode::Char('V') => app.json_view.cycle_view(),
+        KeyCode::Char('h') | KeyCode::Char('H') => {
+            app.screen = Screen::History;
+            app.history.focus = HistoryFocus::List;
+            app.reset_run_output_scroll();
+        }
+        KeyCode::Down | KeyCode::Char('j') => app.json_view.move_row(1),
+        KeyCode::Up | KeyCode::Char('k') => app.json_view.move_row(-1),
+        KeyCode::PageDown => app.json_view.move_row(10),
+        KeyCode::PageUp => app.json_view.move_row(-10),
+        KeyCode::Left => app.json_view.move_column(-1),
+        KeyCode::Right => app.json_view.move_column(1),
+        KeyCode::Char('s') => app.json_view.sort_by_column(),
+        KeyCode::Char('x') => app.json_view.hide_column(),
+        KeyCode::Char('a') => app.json_view.show_all_columns(),
+        KeyCode::Enter | KeyCode::Char(' ') => app.json_view.toggle_node(),
+        KeyCode::Char('c') => app.copy_output_cell(),
+        KeyCode::Char('C') => app.copy_output_row(),
+        KeyCode::Char('e') => app.export_output_csv(),
+        _ => {}
+    }
+}
+
 fn handle_envs_key(app: &mut App, key: KeyEvent) {
     match key.code {
         KeyCode::Char('q') | KeyCode::Esc => app.exit_envs(),
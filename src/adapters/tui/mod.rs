@@ -1,10 +1,14 @@
 mod app;
+mod clipboard;
 mod events;
 mod state;
 pub(crate) mod theme;
 mod ui;
+mod watcher;
 mod widgets;
 
+use crate::domain::{OutputLine, PromptRequest};
+use crate::ports::RunObserver;
 use crate::search_index::SearchIndex;
 use crate::use_cases::ScriptService;
 use crate::workspace::Workspace;
@@ -17,14 +21,19 @@ use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::error::Error;
 use std::io;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
 use std::time::Duration;
 
 use crate::history;
+use crate::lua_hooks::run_with_hooks;
 use crate::theme_config;
 use app::{App, Screen};
 use events::handle_key_event;
-use theme::load_theme;
+use state::RunMessage;
+use theme::{load_theme, Theme};
 use ui::{render_loading, render_ui};
+use watcher::{WatchPaths, WorkspaceWatcher};
 
 pub fn setup_terminal() -> Result<Terminal<CrosstermBackend<io::Stdout>>, Box<dyn Error>> {
     enable_raw_mode()?;
@@ -49,31 +58,31 @@ pub fn run_app(
     service: &ScriptService,
     workspace: Workspace,
 ) -> Result<(), Box<dyn Error>> {
-    let theme_layout = theme_config::ensure_theme_layout().ok();
-    let theme_dir = theme_layout
-        .as_ref()
-        .map(|layout| layout.themes_dir.as_path());
-    let global_theme = theme_layout
-        .as_ref()
-        .and_then(|layout| theme_config::load_theme_name(&layout.config_path));
-    let workspace_theme = theme_config::load_theme_name(workspace.config_path());
-    let theme_name = workspace_theme.or(global_theme);
-    let theme = load_theme(theme_name.as_deref(), theme_dir);
+    let theme = configured_theme(&workspace);
     terminal.draw(|frame| render_loading(frame, &theme))?;
     let entries = service.list_entries(workspace.root())?;
-    let history = match history::load_entries(&workspace) {
-        Ok(entries) => entries,
-        Err(_) => Vec::new(),
-    };
+    let history = history::load_entries(&workspace).unwrap_or_default();
     let search_index = SearchIndex::new(workspace.search_db_path());
     search_index.start_background_rebuild(workspace.root().to_path_buf());
+    let watcher = WorkspaceWatcher::start(watch_paths(&workspace));
     let mut app = App::new(service, workspace, entries, history, search_index, theme);
+    app.watch_notice = match &watcher {
+        Ok(watcher) => watcher.notice().map(str::to_string),
+        Err(err) => Some(format!("Live refresh off ({}), press r to refresh", err)),
+    };
+    let mut watcher = watcher.ok();
 
-    loop {
+    thread::scope(|scope| loop {
+        if let Some(changes) = watcher.as_mut().and_then(|watcher| watcher.poll()) {
+            app.apply_watch_changes(changes);
+        }
         if app.screen == Screen::Search {
             app.refresh_search_status();
         }
         app.poll_widget_load();
+        app.poll_requirement_checks();
+        app.tick_widget_refresh();
+        app.poll_run();
         let theme = app.theme.clone();
         terminal.draw(|frame| render_ui(frame, &mut app, &theme))?;
 
@@ -89,20 +98,69 @@ pub fn run_app(
         if app.should_quit {
             return Ok(());
         }
-        if let Some((script, args)) = app.result.take() {
-            app.screen = Screen::Running;
-            let theme = app.theme.clone();
-            terminal.draw(|frame| render_ui(frame, &mut app, &theme))?;
-            let run_result = service.run_script(&script, &args);
-            let entry = match run_result {
-                Ok(output) => history::success_entry(&app.workspace, &script, &args, output),
-                Err(err) => history::error_entry(&app.workspace, &script, &args, err.to_string()),
-            };
-            let _ = history::record_entry(&app.workspace, &entry);
-            app.add_history_entry(entry);
-            app.back_to_script_select();
-            app.reset_run_output_scroll();
-            app.screen = Screen::RunResult;
+        if let Some((script, args, stdin)) = app.result.take() {
+            let (tx, rx) = mpsc::channel();
+            let hooks = app.hooks_for(&script);
+            app.start_run(script.clone(), args.clone(), stdin.clone(), rx);
+            scope.spawn(move || {
+                let mut observer = ChannelObserver { sender: tx };
+                let run = run_with_hooks(
+                    service,
+                    &hooks,
+                    &script,
+                    &args,
+                    stdin.as_deref(),
+                    &mut observer,
+                );
+                let _ = observer.sender.send(RunMessage::Finished(Box::new(run)));
+            });
         }
+    })
+}
+
+/// Workspace theme first, then the global one, falling back to the default theme.
+fn configured_theme(workspace: &Workspace) -> Theme {
+    let theme_layout = theme_config::ensure_theme_layout().ok();
+    let theme_dir = theme_layout
+        .as_ref()
+        .map(|layout| layout.themes_dir.as_path());
+    let global_theme = theme_layout
+        .as_ref()
+        .and_then(|layout| theme_config::load_theme_name(&layout.config_path));
+    let workspace_theme = theme_config::load_theme_name(workspace.config_path());
+    let theme_name = workspace_theme.or(global_theme);
+    load_theme(theme_name.as_deref(), theme_dir)
+}
+
+fn watch_paths(workspace: &Workspace) -> WatchPaths {
+    let theme_paths = theme_config::ensure_theme_layout()
+        .map(|layout| vec![layout.config_dir, layout.themes_dir])
+        .unwrap_or_default();
+    WatchPaths {
+        root: workspace.root().to_path_buf(),
+        history_dir: workspace.history_dir().to_path_buf(),
+        envs_dir: workspace.envs_dir().to_path_buf(),
+        presets_dir: workspace.presets_dir().to_path_buf(),
+        workspace_config: workspace.config_path().to_path_buf(),
+        theme_paths,
+    }
+}
+
+/// Forwards run events to the UI thread; prompts block until the UI replies.
+struct ChannelObserver {
+    sender: Sender<RunMessage>,
+}
+
+impl RunObserver for ChannelObserver {
+    fn on_line(&mut self, line: &OutputLine) {
+        let _ = self.sender.send(RunMessage::Line(line.clone()));
+    }
+
+    fn on_prompt(&mut self, prompt: &PromptRequest) -> Option<String> {
+        let (reply, answer) = mpsc::channel();
+        self.sender
+            .send(RunMessage::Prompt(prompt.clone(), reply))
+            .ok()?;
+        answer.recv().ok().flatten()
     }
 }
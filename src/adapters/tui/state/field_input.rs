@@ -1,15 +1,29 @@
-use crate::domain::Field;
+use crate::domain::{Field, StdinSpec};
+use crate::presets::Preset;
 use std::path::PathBuf;
 
+/// Overlay over the form for saving the current values or picking a saved preset.
+pub(crate) enum PresetDialog {
+    Save { name: String, scoped: bool },
+    Pick { selection: usize },
+}
+
 pub(crate) struct FieldInputState {
     pub(crate) schema_name: Option<String>,
     pub(crate) schema_description: Option<String>,
+    pub(crate) output_format: Option<String>,
+    pub(crate) stdin: Option<StdinSpec>,
+    pub(crate) stdin_payload: Option<String>,
     pub(crate) fields: Vec<Field>,
     pub(crate) field_index: usize,
     pub(crate) field_inputs: Vec<String>,
     pub(crate) args: Vec<String>,
     pub(crate) error: Option<String>,
     pub(crate) selected_script: Option<PathBuf>,
+    /// Presets of the selected script usable with the active environment.
+    pub(crate) presets: Vec<Preset>,
+    pub(crate) preset_dialog: Option<PresetDialog>,
+    pub(crate) message: Option<String>,
 }
 
 impl FieldInputState {
@@ -17,12 +31,18 @@ impl FieldInputState {
         Self {
             schema_name: None,
             schema_description: None,
+            output_format: None,
+            stdin: None,
+            stdin_payload: None,
             fields: Vec::new(),
             field_index: 0,
             field_inputs: Vec::new(),
             args: Vec::new(),
             error: None,
             selected_script: None,
+            presets: Vec::new(),
+            preset_dialog: None,
+            message: None,
         }
     }
 }
@@ -0,0 +1,227 @@
+use crate::json_output::{self, JsonTable, TreeRow};
+use ratatui::widgets::{ListState, TableState};
+use serde_json::Value;
+use std::collections::HashSet;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum OutputView {
+    Text,
+    Table,
+    Tree,
+}
+
+pub(crate) struct JsonViewState {
+    pub(crate) view: OutputView,
+    pub(crate) value: Option<Value>,
+    pub(crate) table: Option<JsonTable>,
+    pub(crate) table_state: TableState,
+    pub(crate) column: usize,
+    pub(crate) hidden_columns: HashSet<usize>,
+    pub(crate) sort: Option<(usize, bool)>,
+    pub(crate) expanded: HashSet<String>,
+    pub(crate) tree_rows: Vec<TreeRow>,
+    pub(crate) tree_state: ListState,
+    pub(crate) message: Option<String>,
+}
+
+impl JsonViewState {
+    pub(crate) fn new() -> Self {
+        Self {
+            view: OutputView::Text,
+            value: None,
+            table: None,
+            table_state: TableState::default(),
+            column: 0,
+            hidden_columns: HashSet::new(),
+            sort: None,
+            expanded: HashSet::new(),
+            tree_rows: Vec::new(),
+            tree_state: ListState::default(),
+            message: None,
+        }
+    }
+
+    /// Loads stdout into the structured view, preferring it when `prefer` is set.
+    pub(crate) fn load(&mut self, stdout: &str, prefer: bool) {
+        *self = Self::new();
+        let Some(value) = json_output::parse_json_output(stdout) else {
+            if prefer {
+                self.message = Some("Output is not valid JSON; showing text.".to_string());
+            }
+            return;
+        };
+        self.table = json_output::table_from_value(&value);
+        if self.table.is_some() {
+            self.table_state.select(Some(0));
+        }
+        self.value = Some(value);
+        self.rebuild_tree();
+        if prefer {
+            self.view = self.structured_views()[0];
+        }
+    }
+
+    pub(crate) fn is_available(&self) -> bool {
+        self.value.is_some()
+    }
+
+    pub(crate) fn cycle_view(&mut self) {
+        if !self.is_available() {
+            return;
+        }
+        let mut views = vec![OutputView::Text];
+        views.extend(self.structured_views());
+        let current = views
+            .iter()
+            .position(|view| *view == self.view)
+            .unwrap_or(0);
+        self.view = views[(current + 1) % views.len()];
+        self.message = None;
+    }
+
+    pub(crate) fn visible_columns(&self) -> Vec<usize> {
+        let Some(table) = &self.table else {
+            return Vec::new();
+        };
+        (0..table.columns.len())
+            .filter(|idx| !self.hidden_columns.contains(idx))
+            .collect()
+    }
+
+    pub(crate) fn move_row(&mut self, delta: isize) {
+        match self.view {
+            OutputView::Table => {
+                let len = self
+                    .table
+                    .as_ref()
+                    .map(|table| table.rows.len())
+                    .unwrap_or(0);
+                let next = step(self.table_state.selected().unwrap_or(0), delta, len);
+                self.table_state.select(next);
+            }
+            OutputView::Tree => {
+                let next = step(
+                    self.tree_state.selected().unwrap_or(0),
+                    delta,
+                    self.tree_rows.len(),
+                );
+                self.tree_state.select(next);
+            }
+            OutputView::Text => {}
+        }
+    }
+
+    pub(crate) fn move_column(&mut self, delta: isize) {
+        let visible = self.visible_columns();
+        if visible.is_empty() {
+            return;
+        }
+        let position = visible
+            .iter()
+            .position(|idx| *idx == self.column)
+            .unwrap_or(0);
+        let next = step(position, delta, visible.len()).unwrap_or(0);
+        self.column = visible[next];
+    }
+
+    pub(crate) fn sort_by_column(&mut self) {
+        let column = self.column;
+        let descending = matches!(self.sort, Some((current, false)) if current == column);
+        if let Some(table) = self.table.as_mut() {
+            table.sort_by(column, descending);
+            self.sort = Some((column, descending));
+        }
+    }
+
+    pub(crate) fn hide_column(&mut self) {
+        if self.visible_columns().len() <= 1 {
+            self.message = Some("At least one column must stay visible.".to_string());
+            return;
+        }
+        self.hidden_columns.insert(self.column);
+        self.move_column(1);
+        if self.hidden_columns.contains(&self.column) {
+            self.move_column(-1);
+        }
+    }
+
+    pub(crate) fn show_all_columns(&mut self) {
+        self.hidden_columns.clear();
+    }
+
+    pub(crate) fn toggle_node(&mut self) {
+        let Some(row) = self
+            .tree_state
+            .selected()
+            .and_then(|idx| self.tree_rows.get(idx))
+        else {
+            return;
+        };
+        if !row.expandable || row.path.is_empty() {
+            return;
+        }
+        let path = row.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.rebuild_tree();
+    }
+
+    /// Text for the selected table cell or tree node.
+    pub(crate) fn selected_value_text(&self) -> Option<String> {
+        match self.view {
+            OutputView::Table => {
+                let table = self.table.as_ref()?;
+                let row = table.rows.get(self.table_state.selected()?)?;
+                row.get(self.column).cloned()
+            }
+            OutputView::Tree => {
+                let row = self.tree_rows.get(self.tree_state.selected()?)?;
+                let value = json_output::value_at(self.value.as_ref()?, &row.path)?;
+                Some(match value {
+                    Value::Object(_) | Value::Array(_) => {
+                        serde_json::to_string_pretty(value).ok()?
+                    }
+                    other => json_output::cell_text(other),
+                })
+            }
+            OutputView::Text => None,
+        }
+    }
+
+    pub(crate) fn selected_row_text(&self) -> Option<String> {
+        let table = self.table.as_ref()?;
+        table.row_text(self.table_state.selected()?, &self.visible_columns())
+    }
+
+    fn structured_views(&self) -> Vec<OutputView> {
+        if self.table.is_some() {
+            vec![OutputView::Table, OutputView::Tree]
+        } else {
+            vec![OutputView::Tree]
+        }
+    }
+
+    fn rebuild_tree(&mut self) {
+        let Some(value) = &self.value else {
+            self.tree_rows.clear();
+            self.tree_state.select(None);
+            return;
+        };
+        self.tree_rows = json_output::flatten_tree(value, &self.expanded);
+        let selected = self
+            .tree_state
+            .selected()
+            .unwrap_or(0)
+            .min(self.tree_rows.len().saturating_sub(1));
+        self.tree_state.select(Some(selected));
+    }
+}
+
+fn step(current: usize, delta: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let next = (current as isize + delta).clamp(0, len as isize - 1);
+    Some(next as usize)
+}
@@ -1,11 +1,17 @@
 mod environment;
 mod field_input;
 mod history;
+mod json_view;
 mod navigation;
+mod problems;
+mod running;
 mod search;
 
 pub(crate) use environment::EnvironmentState;
-pub(crate) use field_input::FieldInputState;
+pub(crate) use field_input::{FieldInputState, PresetDialog};
 pub(crate) use history::{HistoryFocus, HistoryState};
+pub(crate) use json_view::{JsonViewState, OutputView};
 pub(crate) use navigation::{NavigationState, WidgetLoadResult};
+pub(crate) use problems::ProblemsState;
+pub(crate) use running::{PendingPrompt, RunMessage, RunningState};
 pub(crate) use search::SearchState;
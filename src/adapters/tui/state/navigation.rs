@@ -4,6 +4,7 @@ use crate::ports::WorkspaceEntry;
 use ratatui::widgets::ListState;
 use std::path::PathBuf;
 use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
 
 use super::super::app::SchemaPreview;
 
@@ -22,6 +23,8 @@ pub(crate) struct NavigationState {
     pub(crate) widget_error: Option<String>,
     pub(crate) widget_loading: bool,
     pub(crate) widget_receiver: Option<Receiver<WidgetLoadResult>>,
+    pub(crate) widget_refresh: Option<Duration>,
+    pub(crate) widget_loaded_at: Option<Instant>,
     pub(crate) schema_preview: Option<SchemaPreview>,
     pub(crate) schema_preview_error: Option<String>,
     pub(crate) preview_script: Option<PathBuf>,
@@ -43,6 +46,8 @@ impl NavigationState {
             widget_error: None,
             widget_loading: false,
             widget_receiver: None,
+            widget_refresh: None,
+            widget_loaded_at: None,
             schema_preview: None,
             schema_preview_error: None,
             preview_script: None,
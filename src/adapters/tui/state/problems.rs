@@ -0,0 +1,20 @@
+use crate::lint::Problem;
+use ratatui::widgets::ListState;
+
+pub(crate) struct ProblemsState {
+    pub(crate) problems: Vec<Problem>,
+    pub(crate) list_state: ListState,
+    pub(crate) selection: usize,
+    pub(crate) error: Option<String>,
+}
+
+impl ProblemsState {
+    pub(crate) fn new() -> Self {
+        Self {
+            problems: Vec::new(),
+            list_state: ListState::default(),
+            selection: 0,
+            error: None,
+        }
+    }
+}
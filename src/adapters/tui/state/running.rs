@@ -0,0 +1,77 @@
+use crate::domain::{Annotation, Field, OutputLine, PromptRequest};
+use crate::lua_hooks::HookedRun;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::Instant;
+
+const RECENT_LINE_LIMIT: usize = 200;
+
+pub(crate) enum RunMessage {
+    Line(OutputLine),
+    Prompt(PromptRequest, Sender<Option<String>>),
+    Finished(Box<HookedRun>),
+}
+
+/// A `::prompt` waiting for the user; the run thread blocks until `reply` is used.
+pub(crate) struct PendingPrompt {
+    pub(crate) field: Field,
+    pub(crate) input: String,
+    pub(crate) error: Option<String>,
+    pub(crate) reply: Sender<Option<String>>,
+}
+
+pub(crate) struct RunningState {
+    pub(crate) script: Option<PathBuf>,
+    pub(crate) args: Vec<String>,
+    pub(crate) stdin: Option<String>,
+    pub(crate) receiver: Option<Receiver<RunMessage>>,
+    pub(crate) progress: Option<u8>,
+    pub(crate) progress_message: Option<String>,
+    pub(crate) annotations: Vec<Annotation>,
+    pub(crate) recent_lines: VecDeque<String>,
+    pub(crate) started_at: Option<Instant>,
+    pub(crate) prompt: Option<PendingPrompt>,
+}
+
+impl RunningState {
+    pub(crate) fn new() -> Self {
+        Self {
+            script: None,
+            args: Vec::new(),
+            stdin: None,
+            receiver: None,
+            progress: None,
+            progress_message: None,
+            annotations: Vec::new(),
+            recent_lines: VecDeque::new(),
+            started_at: None,
+            prompt: None,
+        }
+    }
+
+    pub(crate) fn apply_line(&mut self, line: OutputLine) {
+        match line {
+            OutputLine::Progress { percent, message } => {
+                self.progress = Some(percent);
+                if message.is_some() {
+                    self.progress_message = message;
+                }
+            }
+            OutputLine::Annotation(annotation) => self.annotations.push(annotation),
+            OutputLine::Text(text) => self.push_recent(text),
+            OutputLine::Group(name) => self.push_recent(format!("> {}", name)),
+            OutputLine::EndGroup => {}
+            OutputLine::Prompt(prompt) => {
+                self.push_recent(format!("? {}", prompt.text.unwrap_or(prompt.id)))
+            }
+        }
+    }
+
+    fn push_recent(&mut self, line: String) {
+        if self.recent_lines.len() == RECENT_LINE_LIMIT {
+            self.recent_lines.pop_front();
+        }
+        self.recent_lines.push_back(line);
+    }
+}
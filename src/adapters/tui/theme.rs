@@ -169,7 +169,7 @@ impl HexColor {
     }
 
     pub(crate) fn color(&self) -> Color {
-        self.0.clone()
+        self.0
     }
 }
 
@@ -336,6 +336,10 @@ fn parse_hex_color(value: &str) -> Result<Color, ThemeParseError> {
     Ok(Color::Rgb(red, green, blue))
 }
 
+pub(crate) fn selection_symbol_str() -> &'static str {
+    "> "
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,7 +419,3 @@ error = "#ffff00"
         assert!(load_theme_from_str(toml).is_err());
     }
 }
-
-pub(crate) fn selection_symbol_str() -> &'static str {
-    "> "
-}
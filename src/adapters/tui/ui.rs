@@ -8,7 +8,7 @@ use super::app::{App, Screen};
 use super::theme::Theme;
 use super::widgets::{
     environment, envs, error as error_widget, field_input, history, loading as loading_widget,
-    run_result, running, schema, scripts, search,
+    problems, run_result, running, schema, scripts, search,
 };
 
 pub(crate) fn render_ui(frame: &mut Frame, app: &mut App, theme: &Theme) {
@@ -18,7 +18,8 @@ pub(crate) fn render_ui(frame: &mut Frame, app: &mut App, theme: &Theme) {
         Screen::Environments => envs::render_envs(frame, frame.size(), app, theme),
         Screen::FieldInput => field_input::render_field_input(frame, frame.size(), app, theme),
         Screen::History => history::render_history(frame, frame.size(), app, theme),
-        Screen::Running => running::render_running(frame, frame.size(), app),
+        Screen::Problems => problems::render_problems(frame, frame.size(), app, theme),
+        Screen::Running => running::render_running(frame, frame.size(), app, theme),
         Screen::RunResult => run_result::render_run_result(frame, frame.size(), app, theme),
         Screen::Error => render_error(frame, app, theme),
     }
@@ -35,6 +36,7 @@ fn render_script_select(frame: &mut Frame, app: &mut App, theme: &Theme) {
         app.navigation.widget.as_ref(),
         app.navigation.widget_error.as_deref(),
         app.navigation.widget_loading,
+        theme,
     );
     let info_height = info_lines.len() as u16 + 2;
 
@@ -102,23 +104,32 @@ fn render_script_select(frame: &mut Frame, app: &mut App, theme: &Theme) {
     }
 
     let mut footer_text = if app.navigation.entries.is_empty() {
-        "Folder is empty. r refresh, h history, Ctrl+S search, Alt+E envs, q quit".to_string()
+        "Folder is empty. r refresh, h history, p problems, Ctrl+S search, Alt+E envs, q quit"
+            .to_string()
     } else {
-        "Up/Down move, Enter open/run, r refresh, h history, Ctrl+S search, Alt+E envs, q quit"
+        "Up/Down move, Enter open/run, r refresh, h history, p problems, Ctrl+S search, Alt+E envs, q quit"
             .to_string()
     };
     if app.navigation.current_dir != app.workspace.root() {
         if app.navigation.entries.is_empty() {
             footer_text =
-                "Folder is empty. Backspace up, r refresh, h history, Ctrl+S search, Alt+E envs, q quit"
+                "Folder is empty. Backspace up, r refresh, h history, p problems, Ctrl+S search, Alt+E envs, q quit"
                     .to_string();
         } else {
             footer_text =
-                "Up/Down move, Enter open/run, Backspace up, r refresh, h history, Ctrl+S search, Alt+E envs, q quit"
+                "Up/Down move, Enter open/run, Backspace up, r refresh, h history, p problems, Ctrl+S search, Alt+E envs, q quit"
                     .to_string();
         }
     }
-    let footer = Paragraph::new(footer_text).style(theme.text_secondary());
+    let mut footer_line = Vec::new();
+    if let Some(notice) = &app.watch_notice {
+        footer_line.push(Span::styled(
+            format!("{}. ", notice),
+            theme.status_error_style(),
+        ));
+    }
+    footer_line.push(Span::raw(footer_text));
+    let footer = Paragraph::new(Line::from(footer_line)).style(theme.text_secondary());
     frame.render_widget(footer, chunks[2]);
 }
 
@@ -0,0 +1,224 @@
+use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last event before changes are applied, so an editor's
+/// write-rename-chmod burst results in a single refresh.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What a batch of filesystem events touched.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WatchChanges {
+    pub(crate) scripts: bool,
+    pub(crate) widgets: bool,
+    pub(crate) envs: bool,
+    pub(crate) theme: bool,
+}
+
+impl WatchChanges {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: WatchChanges) {
+        self.scripts |= other.scripts;
+        self.widgets |= other.widgets;
+        self.envs |= other.envs;
+        self.theme |= other.theme;
+    }
+}
+
+/// Paths the watcher sorts events into.
+#[derive(Debug, Clone)]
+pub(crate) struct WatchPaths {
+    pub(crate) root: PathBuf,
+    pub(crate) history_dir: PathBuf,
+    pub(crate) envs_dir: PathBuf,
+    /// Saved presets, which are read when a form opens.
+    pub(crate) presets_dir: PathBuf,
+    pub(crate) workspace_config: PathBuf,
+    /// Global config and theme folders, when they could be resolved.
+    pub(crate) theme_paths: Vec<PathBuf>,
+}
+
+impl WatchPaths {
+    fn classify(&self, path: &Path) -> WatchChanges {
+        let mut changes = WatchChanges::default();
+        let ignored = path.starts_with(&self.history_dir)
+            || path.starts_with(&self.presets_dir)
+            || path.components().any(|part| part.as_os_str() == ".git");
+        if ignored {
+            return changes;
+        }
+        if self.theme_paths.iter().any(|theme| path.starts_with(theme)) {
+            changes.theme = true;
+        } else if path == self.workspace_config {
+            // `[schema] strict` changes which schemas load, so scripts are re-read too.
+            changes.theme = true;
+            changes.widgets = true;
+            changes.scripts = true;
+        } else if path.starts_with(&self.envs_dir) {
+            changes.envs = true;
+        } else if path.file_name().is_some_and(|name| name == "index.lua") {
+            changes.widgets = true;
+        } else if path.starts_with(&self.root) {
+            changes.scripts = true;
+        }
+        changes
+    }
+}
+
+/// Watches the workspace (inotify/FSEvents/..., or polling when those are unavailable)
+/// and reports debounced changes.
+pub(crate) struct WorkspaceWatcher {
+    _watcher: Box<dyn Watcher>,
+    events: Receiver<notify::Result<Event>>,
+    paths: WatchPaths,
+    pending: WatchChanges,
+    last_event: Option<Instant>,
+    /// Set when native watching failed (e.g. the inotify watch limit) and polling took over.
+    notice: Option<String>,
+}
+
+impl WorkspaceWatcher {
+    pub(crate) fn start(paths: WatchPaths) -> Result<Self, String> {
+        let (tx, events) = mpsc::channel();
+        let native =
+            RecommendedWatcher::new(tx.clone(), Config::default()).and_then(|mut watcher| {
+                watcher.watch(&paths.root, RecursiveMode::Recursive)?;
+                Ok(watcher)
+            });
+        let (mut watcher, notice): (Box<dyn Watcher>, _) = match native {
+            Ok(watcher) => (Box::new(watcher), None),
+            Err(err) => {
+                let mut watcher =
+                    PollWatcher::new(tx, Config::default().with_poll_interval(POLL_INTERVAL))
+                        .map_err(|err| err.to_string())?;
+                watcher
+                    .watch(&paths.root, RecursiveMode::Recursive)
+                    .map_err(|err| err.to_string())?;
+                let notice = format!(
+                    "File watching unavailable ({}), polling every {}s",
+                    err,
+                    POLL_INTERVAL.as_secs()
+                );
+                (Box::new(watcher), Some(notice))
+            }
+        };
+        for theme_path in &paths.theme_paths {
+            let _ = watcher.watch(theme_path, RecursiveMode::NonRecursive);
+        }
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            paths,
+            pending: WatchChanges::default(),
+            last_event: None,
+            notice,
+        })
+    }
+
+    /// Why live refresh is degraded, if it is.
+    pub(crate) fn notice(&self) -> Option<&str> {
+        self.notice.as_deref()
+    }
+
+    /// Drains queued events; returns the accumulated changes once things have been quiet
+    /// for the debounce period.
+    pub(crate) fn poll(&mut self) -> Option<WatchChanges> {
+        loop {
+            match self.events.try_recv() {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Access(_)) {
+                        continue;
+                    }
+                    for path in &event.paths {
+                        let changes = self.paths.classify(path);
+                        if !changes.is_empty() {
+                            self.pending.merge(changes);
+                            self.last_event = Some(Instant::now());
+                        }
+                    }
+                }
+                Ok(Err(_)) => {}
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        let quiet = self
+            .last_event
+            .is_some_and(|last| last.elapsed() >= DEBOUNCE);
+        if quiet && !self.pending.is_empty() {
+            self.last_event = None;
+            return Some(std::mem::take(&mut self.pending));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_paths() {
+        let root = PathBuf::from("/ws");
+        let paths = WatchPaths {
+            root: root.clone(),
+            history_dir: root.join(".history"),
+            envs_dir: root.join(".omaken/envs"),
+            presets_dir: root.join(".omaken/presets"),
+            workspace_config: root.join("omakure.toml"),
+            theme_paths: vec![PathBuf::from("/cfg/themes")],
+        };
+        let classify = |path: &str| paths.classify(Path::new(path));
+
+        assert!(classify("/ws/.history/search-index.sqlite").is_empty());
+        assert!(classify("/ws/.git/index").is_empty());
+        assert!(classify("/ws/.omaken/presets/deploy.sh.toml").is_empty());
+        assert!(classify("/ws/azure/rg.bash").scripts);
+        assert!(classify("/ws/azure/index.lua").widgets);
+        assert!(classify("/ws/.omaken/envs/active").envs);
+        assert!(classify("/cfg/themes/dark.toml").theme);
+        let config = classify("/ws/omakure.toml");
+        assert!(config.theme && config.widgets && config.scripts);
+    }
+
+    #[test]
+    fn test_changes_are_debounced_into_one_batch() {
+        let root = std::env::temp_dir().join(format!("omakure-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join(".history")).unwrap();
+        let mut watcher = WorkspaceWatcher::start(WatchPaths {
+            root: root.clone(),
+            history_dir: root.join(".history"),
+            envs_dir: root.join(".omaken/envs"),
+            presets_dir: root.join(".omaken/presets"),
+            workspace_config: root.join("omakure.toml"),
+            theme_paths: Vec::new(),
+        })
+        .unwrap();
+        assert_eq!(watcher.notice(), None);
+
+        std::fs::write(root.join(".history/run.log"), "ignored").unwrap();
+        std::fs::write(root.join("a.bash"), "echo a").unwrap();
+        std::fs::write(root.join("index.lua"), "return {}").unwrap();
+        let started = Instant::now();
+        let mut batches = Vec::new();
+        while started.elapsed() < Duration::from_secs(5) && batches.is_empty() {
+            batches.extend(watcher.poll());
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(
+            batches,
+            vec![WatchChanges {
+                scripts: true,
+                widgets: true,
+                envs: false,
+                theme: false,
+            }]
+        );
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
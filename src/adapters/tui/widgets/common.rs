@@ -1,8 +1,11 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::style::Style;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
 
 use super::super::app::ExecutionStatus;
 use super::super::theme::Theme;
+use crate::domain::{parse_output_line, Annotation, AnnotationLevel, OutputLine};
+use crate::history::{self, HistoryEntry};
 
 pub(crate) fn status_label_and_style(status: &ExecutionStatus, theme: &Theme) -> (String, Style) {
     match status {
@@ -43,3 +46,132 @@ pub(crate) fn horizontal_split(area: Rect, left_percent: u16) -> [Rect; 2] {
 
     [chunks[0], chunks[1]]
 }
+
+/// Builds the annotation summary and output body for a history entry.
+pub(crate) fn history_output_lines(
+    entry: &HistoryEntry,
+    groups_expanded: bool,
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    if !entry.annotations.is_empty() {
+        lines.extend(annotation_summary_lines(&entry.annotations, theme));
+        lines.push(Line::from(""));
+    }
+    if !entry.notes.is_empty() {
+        lines.extend(entry.notes.iter().map(|note| {
+            Line::from(Span::styled(
+                format!("NOTE: {}", note),
+                theme.text_secondary(),
+            ))
+        }));
+        lines.push(Line::from(""));
+    }
+    if let Some(stdin) = &entry.stdin {
+        let truncated = if entry.stdin_truncated {
+            ", truncated"
+        } else {
+            ""
+        };
+        lines.push(Line::from(Span::styled(
+            format!("STDIN: {} bytes{}", stdin.len(), truncated),
+            theme.text_secondary(),
+        )));
+        lines.push(Line::from(""));
+    }
+
+    if entry.error.is_some() || entry.stdout.trim().is_empty() {
+        let output = history::format_output(entry);
+        if output.trim().is_empty() {
+            lines.push(Line::from("(no output)"));
+        } else {
+            lines.extend(output.lines().map(|line| Line::from(line.to_string())));
+        }
+        return lines;
+    }
+
+    lines.push(Line::from("STDOUT:"));
+    lines.extend(stdout_lines(
+        entry.stdout.trim_end(),
+        groups_expanded,
+        theme,
+    ));
+    if !entry.stderr.trim().is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("STDERR:"));
+        lines.extend(
+            entry
+                .stderr
+                .trim_end()
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        );
+    }
+    lines
+}
+
+pub(crate) fn annotation_line(annotation: &Annotation, theme: &Theme) -> Line<'static> {
+    let (label, color) = match annotation.level {
+        AnnotationLevel::Warning => ("warning", theme.semantic.warning.color()),
+        AnnotationLevel::Error => ("error", theme.semantic.error.color()),
+    };
+    Line::from(vec![
+        Span::styled(
+            format!("{}: ", label),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(annotation.message.clone(), Style::default().fg(color)),
+    ])
+}
+
+fn annotation_summary_lines(annotations: &[Annotation], theme: &Theme) -> Vec<Line<'static>> {
+    let warnings = annotations
+        .iter()
+        .filter(|annotation| annotation.level == AnnotationLevel::Warning)
+        .count();
+    let errors = annotations.len() - warnings;
+    let mut lines = vec![Line::from(format!(
+        "Annotations: {} warning(s), {} error(s)",
+        warnings, errors
+    ))];
+    lines.extend(
+        annotations
+            .iter()
+            .map(|annotation| annotation_line(annotation, theme)),
+    );
+    lines
+}
+
+fn stdout_lines(stdout: &str, groups_expanded: bool, theme: &Theme) -> Vec<Line<'static>> {
+    let parsed: Vec<OutputLine> = stdout.lines().map(parse_output_line).collect();
+    let group_style = Style::default()
+        .fg(theme.semantic.info.color())
+        .add_modifier(Modifier::BOLD);
+    let mut lines = Vec::new();
+    let mut idx = 0;
+    while idx < parsed.len() {
+        match &parsed[idx] {
+            OutputLine::Text(text) => lines.push(Line::from(text.clone())),
+            OutputLine::Annotation(annotation) => lines.push(annotation_line(annotation, theme)),
+            OutputLine::Group(name) if groups_expanded => {
+                lines.push(Line::from(Span::styled(format!("v {}", name), group_style)));
+            }
+            OutputLine::Group(name) => {
+                let end = parsed[idx + 1..]
+                    .iter()
+                    .position(|line| *line == OutputLine::EndGroup)
+                    .map(|offset| idx + 1 + offset)
+                    .unwrap_or(parsed.len());
+                let hidden = end - idx - 1;
+                lines.push(Line::from(Span::styled(
+                    format!("> {} ({} lines)", name, hidden),
+                    group_style,
+                )));
+                idx = end;
+            }
+            OutputLine::EndGroup | OutputLine::Progress { .. } | OutputLine::Prompt(_) => {}
+        }
+        idx += 1;
+    }
+    lines
+}
@@ -1,11 +1,16 @@
 use crate::app_meta;
-use crate::lua_widget::WidgetData;
+use crate::lua_widget::{WidgetColor, WidgetData, WidgetLine, WidgetSpan};
 use crate::workspace::Workspace;
 use ratatui::layout::Rect;
-use ratatui::text::Line;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
+use super::super::theme::Theme;
+
+const GAUGE_WIDTH: usize = 20;
+
 pub(crate) fn render_environment(
     frame: &mut Frame,
     area: Rect,
@@ -23,7 +28,12 @@ pub(crate) fn status_info(
     widget: Option<&WidgetData>,
     widget_error: Option<&str>,
     widget_loading: bool,
+    theme: &Theme,
 ) -> (String, Vec<Line<'static>>) {
+    if let Some(widget) = widget {
+        return (widget.title.clone(), widget_lines(widget, theme));
+    }
+
     if widget_loading {
         return (
             "Loading".to_string(),
@@ -34,15 +44,6 @@ pub(crate) fn status_info(
         );
     }
 
-    if let Some(widget) = widget {
-        let lines = widget
-            .lines
-            .iter()
-            .map(|line| Line::from(line.clone()))
-            .collect();
-        return (widget.title.clone(), lines);
-    }
-
     if let Some(message) = widget_error {
         return (
             "Widget Error".to_string(),
@@ -64,3 +65,120 @@ pub(crate) fn status_info(
     lines.push(Line::from(format!("Repo: {}", repo)));
     ("Workspace".to_string(), lines)
 }
+
+fn widget_lines(widget: &WidgetData, theme: &Theme) -> Vec<Line<'static>> {
+    let key_width = widget
+        .lines
+        .iter()
+        .filter_map(|line| match line {
+            WidgetLine::KeyValue { key, .. } => Some(key.chars().count()),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for line in &widget.lines {
+        match line {
+            WidgetLine::Text(spans) => lines.push(Line::from(styled_spans(spans, theme))),
+            WidgetLine::KeyValue { key, value } => {
+                let mut spans = vec![Span::styled(
+                    format!("{:<width$}  ", key, width = key_width),
+                    Style::default().fg(theme.ui.text_secondary.color()),
+                )];
+                spans.extend(styled_spans(value, theme));
+                lines.push(Line::from(spans));
+            }
+            WidgetLine::Gauge {
+                label,
+                percent,
+                color,
+            } => {
+                let filled = usize::from(*percent) * GAUGE_WIDTH / 100;
+                let color =
+                    color.map_or(theme.semantic.info.color(), |color| color_for(color, theme));
+                let mut spans = Vec::new();
+                if let Some(label) = label {
+                    spans.push(Span::raw(format!("{} ", label)));
+                }
+                spans.push(Span::styled("█".repeat(filled), Style::default().fg(color)));
+                spans.push(Span::styled(
+                    "░".repeat(GAUGE_WIDTH - filled),
+                    Style::default().fg(theme.ui.text_muted.color()),
+                ));
+                spans.push(Span::raw(format!(" {:>3}%", percent)));
+                lines.push(Line::from(spans));
+            }
+            WidgetLine::Table { header, rows } => {
+                lines.extend(table_lines(header.as_deref(), rows, theme));
+            }
+        }
+    }
+    lines
+}
+
+fn table_lines(
+    header: Option<&[String]>,
+    rows: &[Vec<String>],
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in header.into_iter().chain(rows.iter().map(Vec::as_slice)) {
+        for (index, cell) in row.iter().enumerate() {
+            let len = cell.chars().count();
+            match widths.get_mut(index) {
+                Some(width) => *width = (*width).max(len),
+                None => widths.push(len),
+            }
+        }
+    }
+    let format_row = |row: &[String]| {
+        row.iter()
+            .enumerate()
+            .map(|(index, cell)| format!("{:<width$}", cell, width = widths[index]))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = Vec::new();
+    if let Some(header) = header {
+        lines.push(Line::from(Span::styled(
+            format_row(header),
+            Style::default()
+                .fg(theme.ui.text_secondary.color())
+                .add_modifier(Modifier::BOLD),
+        )));
+    }
+    lines.extend(rows.iter().map(|row| Line::from(format_row(row))));
+    lines
+}
+
+fn styled_spans(spans: &[WidgetSpan], theme: &Theme) -> Vec<Span<'static>> {
+    spans
+        .iter()
+        .map(|span| {
+            let mut style = Style::default();
+            if let Some(color) = span.color {
+                style = style.fg(color_for(color, theme));
+            }
+            if span.bold {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            Span::styled(span.text.clone(), style)
+        })
+        .collect()
+}
+
+fn color_for(color: WidgetColor, theme: &Theme) -> ratatui::style::Color {
+    match color {
+        WidgetColor::Success => theme.semantic.success.color(),
+        WidgetColor::Warning => theme.semantic.warning.color(),
+        WidgetColor::Error => theme.semantic.error.color(),
+        WidgetColor::Info => theme.semantic.info.color(),
+        WidgetColor::Accent => theme.brand.accent.color(),
+        WidgetColor::Muted => theme.ui.text_muted.color(),
+        WidgetColor::Secondary => theme.ui.text_secondary.color(),
+    }
+}
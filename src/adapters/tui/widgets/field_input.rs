@@ -1,10 +1,12 @@
+use crate::domain::Field;
 use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
 use super::super::app::App;
+use super::super::state::PresetDialog;
 use super::super::theme::Theme;
 use super::common::standard_screen_layout;
 
@@ -42,13 +44,21 @@ pub(crate) fn render_field_input(frame: &mut Frame, area: Rect, app: &mut App, t
             Style::default().fg(theme.semantic.error.color()),
         )));
     }
+    if let Some(message) = &app.field_input.message {
+        header_lines.push(Line::from(Span::styled(
+            message.as_str(),
+            Style::default().fg(theme.semantic.info.color()),
+        )));
+    }
     let header_height = header_lines.len() as u16 + 2;
     let header = Paragraph::new(header_lines)
         .block(Block::default().borders(Borders::ALL).title("Schema"))
         .wrap(Wrap { trim: true });
 
-    let footer = Paragraph::new("Tab/Shift+Tab to move, Enter to run, Ctrl+B back, Esc quit")
-        .style(theme.text_secondary());
+    let footer = Paragraph::new(
+        "Tab/Shift+Tab to move, Enter to run, Ctrl+S save preset, Ctrl+P presets, Ctrl+B back, Esc quit",
+    )
+    .style(theme.text_secondary());
 
     let footer_height = 1u16;
     let chunks = standard_screen_layout(area, header_height, footer_height);
@@ -56,6 +66,107 @@ pub(crate) fn render_field_input(frame: &mut Frame, area: Rect, app: &mut App, t
     frame.render_widget(header, chunks[0]);
     render_field_boxes(frame, chunks[1], app, theme);
     frame.render_widget(footer, chunks[2]);
+    render_preset_dialog(frame, chunks[1], app, theme);
+}
+
+/// Draws the preset save or picker dialog over the fields.
+fn render_preset_dialog(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let Some(dialog) = &app.field_input.preset_dialog else {
+        return;
+    };
+    let label_style = theme.text_secondary();
+    let (title, lines, hint) = match dialog {
+        PresetDialog::Save { name, scoped } => {
+            let active = app
+                .environment
+                .config
+                .as_ref()
+                .and_then(|config| config.active.as_deref());
+            let scope = match (active, scoped) {
+                (Some(env), true) => format!("only with {}", env),
+                _ => "all environments".to_string(),
+            };
+            let lines = vec![
+                Line::from(vec![
+                    Span::styled("Name: ", label_style),
+                    Span::styled(
+                        format!("{}_", name),
+                        Style::default().fg(theme.semantic.info.color()),
+                    ),
+                ]),
+                Line::from(vec![Span::styled("Scope: ", label_style), Span::raw(scope)]),
+                Line::from(Span::styled("Secret fields are not saved.", label_style)),
+            ];
+            let hint = if active.is_some() {
+                "Enter to save, Tab to toggle scope, Esc to cancel"
+            } else {
+                "Enter to save, Esc to cancel"
+            };
+            ("Save preset", lines, hint)
+        }
+        PresetDialog::Pick { selection } => {
+            let lines = app
+                .field_input
+                .presets
+                .iter()
+                .enumerate()
+                .map(|(idx, preset)| {
+                    if idx == *selection {
+                        Line::from(Span::styled(
+                            format!("> {}", preset.label()),
+                            Style::default()
+                                .fg(theme.semantic.info.color())
+                                .add_modifier(Modifier::BOLD),
+                        ))
+                    } else {
+                        Line::from(Span::raw(format!("  {}", preset.label())))
+                    }
+                })
+                .collect();
+            (
+                "Presets",
+                lines,
+                "Enter to apply, d to delete, Esc to cancel",
+            )
+        }
+    };
+
+    let width = area.width.min(60);
+    let height = (lines.len() as u16 + 3).min(area.height);
+    let rect = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, rect);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(theme.ui.border_active.color()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    let list_height = inner.height.saturating_sub(1);
+    let selected = match dialog {
+        PresetDialog::Pick { selection } => *selection as u16,
+        PresetDialog::Save { .. } => 0,
+    };
+    let scroll = selected.saturating_sub(list_height.saturating_sub(1));
+    frame.render_widget(
+        Paragraph::new(lines).scroll((scroll, 0)),
+        Rect {
+            height: list_height,
+            ..inner
+        },
+    );
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(hint, label_style))),
+        Rect {
+            y: inner.y + list_height,
+            height: inner.height.min(1),
+            ..inner
+        },
+    );
 }
 
 fn render_field_boxes(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
@@ -84,62 +195,81 @@ fn render_field_boxes(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
 
     let mut y = inner.y;
     for idx in start..end {
-        let field = &app.field_input.fields[idx];
-        let required = field.required.unwrap_or(false);
-        let required_label = if required { "required" } else { "optional" };
-        let title = format!("{} ({}, {})", field.name, field.kind, required_label);
-        let is_selected = idx == app.field_input.field_index;
-        let border_style = if is_selected {
-            Style::default()
-                .fg(theme.ui.border_active.color())
-                .add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(theme.ui.border_inactive.color())
-        };
         let value = app
             .field_input
             .field_inputs
             .get(idx)
             .map(String::as_str)
             .unwrap_or("");
-        let value_text = if value.trim().is_empty() {
-            field
-                .default
-                .as_deref()
-                .map(|default| format!("<default: {}>", default))
-                .unwrap_or_else(|| "<empty>".to_string())
-        } else {
-            value.to_string()
-        };
-        let prompt = field.prompt.as_deref().unwrap_or(&field.name);
-        let value_style = if is_selected {
-            Style::default().fg(theme.semantic.info.color())
-        } else {
-            theme.text_secondary()
-        };
-
-        let lines = vec![
-            Line::from(vec![
-                Span::styled("Prompt: ", theme.text_secondary()),
-                Span::raw(prompt),
-            ]),
-            Line::from(vec![
-                Span::styled("Value: ", theme.text_secondary()),
-                Span::styled(value_text, value_style),
-            ]),
-        ];
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .border_style(border_style);
         let rect = Rect {
             x: inner.x,
             y,
             width: inner.width,
             height: box_height,
         };
-        let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
-        frame.render_widget(paragraph, rect);
+        render_field_box(
+            frame,
+            rect,
+            &app.field_input.fields[idx],
+            value,
+            idx == app.field_input.field_index,
+            theme,
+        );
         y = y.saturating_add(box_height);
     }
 }
+
+/// Renders one field with its prompt and current value; shared with run-time prompts.
+pub(crate) fn render_field_box(
+    frame: &mut Frame,
+    rect: Rect,
+    field: &Field,
+    value: &str,
+    is_selected: bool,
+    theme: &Theme,
+) {
+    let required = field.required.unwrap_or(false);
+    let required_label = if required { "required" } else { "optional" };
+    let title = format!("{} ({}, {})", field.name, field.kind, required_label);
+    let border_style = if is_selected {
+        Style::default()
+            .fg(theme.ui.border_active.color())
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(theme.ui.border_inactive.color())
+    };
+    let value_text = if value.trim().is_empty() {
+        field
+            .default
+            .as_deref()
+            .map(|default| format!("<default: {}>", default))
+            .unwrap_or_else(|| "<empty>".to_string())
+    } else if field.is_secret() {
+        "*".repeat(value.chars().count())
+    } else {
+        value.to_string()
+    };
+    let prompt = field.prompt.as_deref().unwrap_or(&field.name);
+    let value_style = if is_selected {
+        Style::default().fg(theme.semantic.info.color())
+    } else {
+        theme.text_secondary()
+    };
+
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("Prompt: ", theme.text_secondary()),
+            Span::raw(prompt),
+        ]),
+        Line::from(vec![
+            Span::styled("Value: ", theme.text_secondary()),
+            Span::styled(value_text, value_style),
+        ]),
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(border_style);
+    let paragraph = Paragraph::new(lines).block(block).wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, rect);
+}
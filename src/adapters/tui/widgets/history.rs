@@ -6,7 +6,7 @@ use ratatui::Frame;
 
 use super::super::app::{App, ExecutionStatus, HistoryFocus};
 use super::super::theme::Theme;
-use super::common::status_label_and_style;
+use super::common::{history_output_lines, status_label_and_style};
 use crate::history;
 
 pub(crate) fn render_history(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
@@ -26,9 +26,11 @@ pub(crate) fn render_history(frame: &mut Frame, area: Rect, app: &mut App, theme
 
     let footer_text = match app.history.focus {
         HistoryFocus::List => {
-            "Up/Down to select, Enter to view output, Alt+E envs, Esc/q to go back"
+            "Up/Down to select, Enter to view output, r to rerun, Alt+E envs, Esc/q to go back"
+        }
+        HistoryFocus::Output => {
+            "Up/Down to scroll, PgUp/PgDn, g groups, Esc to return, q to go back"
         }
-        HistoryFocus::Output => "Up/Down to scroll, PgUp/PgDn, Esc to return, q to go back",
     };
     let footer = Paragraph::new(footer_text).style(theme.text_secondary());
     frame.render_widget(footer, chunks[1]);
@@ -108,12 +110,11 @@ fn render_history_output(frame: &mut Frame, area: Rect, app: &mut App, theme: &T
             Span::styled(status_label, status_style),
         ]));
         lines.push(Line::from(""));
-        let output = history::format_output(entry);
-        if output.trim().is_empty() {
-            lines.push(Line::from("(no output)"));
-        } else {
-            lines.extend(output.lines().map(|line| Line::from(line.to_string())));
-        }
+        lines.extend(history_output_lines(
+            entry,
+            app.output_groups_expanded,
+            theme,
+        ));
     } else {
         lines.push(Line::from("No history selected."));
     }
@@ -0,0 +1,145 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+
+use super::super::app::App;
+use super::super::state::OutputView;
+use super::super::theme::{self, Theme};
+
+const MIN_COLUMN_WIDTH: u16 = 4;
+const MAX_COLUMN_WIDTH: u16 = 40;
+
+pub(crate) fn render_json_view(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    match app.json_view.view {
+        OutputView::Table => render_table(frame, area, app, theme),
+        OutputView::Tree => render_tree(frame, area, app, theme),
+        OutputView::Text => {}
+    }
+}
+
+fn render_table(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let visible = app.json_view.visible_columns();
+    let Some(table) = app.json_view.table.as_ref() else {
+        let empty = Paragraph::new("No tabular data.")
+            .block(Block::default().borders(Borders::ALL).title("Table"))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let header_style = theme.text_secondary().add_modifier(Modifier::BOLD);
+    let active_style = Style::default()
+        .fg(theme.semantic.info.color())
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let header = Row::new(
+        visible
+            .iter()
+            .map(|idx| {
+                let mut label = table.columns[*idx].clone();
+                if let Some((column, descending)) = app.json_view.sort {
+                    if column == *idx {
+                        label.push_str(if descending { " v" } else { " ^" });
+                    }
+                }
+                let style = if *idx == app.json_view.column {
+                    active_style
+                } else {
+                    header_style
+                };
+                Cell::from(Span::styled(label, style))
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let rows: Vec<Row> = table
+        .rows
+        .iter()
+        .map(|row| {
+            Row::new(
+                visible
+                    .iter()
+                    .map(|idx| {
+                        let value = row[*idx].replace('\n', " ");
+                        if *idx == app.json_view.column {
+                            Cell::from(Span::styled(
+                                value,
+                                Style::default().fg(theme.semantic.info.color()),
+                            ))
+                        } else {
+                            Cell::from(value)
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect();
+
+    let widths: Vec<Constraint> = visible
+        .iter()
+        .map(|idx| {
+            let longest = table
+                .rows
+                .iter()
+                .map(|row| row[*idx].chars().count())
+                .chain(std::iter::once(table.columns[*idx].chars().count() + 2))
+                .max()
+                .unwrap_or(0) as u16;
+            Constraint::Length(longest.clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH))
+        })
+        .collect();
+
+    let title = format!(
+        "Table ({} rows, {}/{} columns)",
+        table.rows.len(),
+        visible.len(),
+        table.columns.len()
+    );
+    let widget = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(theme.selection_style())
+        .highlight_symbol(theme::selection_symbol_str());
+    frame.render_stateful_widget(widget, area, &mut app.json_view.table_state);
+}
+
+fn render_tree(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let items: Vec<ListItem> = app
+        .json_view
+        .tree_rows
+        .iter()
+        .map(|row| {
+            let marker = if !row.expandable {
+                "  "
+            } else if row.expanded {
+                "- "
+            } else {
+                "+ "
+            };
+            let summary_style = if row.expandable {
+                theme.text_muted()
+            } else {
+                Style::default().fg(theme.semantic.info.color())
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw("  ".repeat(row.depth)),
+                Span::raw(marker),
+                Span::styled(
+                    row.label.clone(),
+                    Style::default()
+                        .fg(theme.semantic.warning.color())
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(": "),
+                Span::styled(row.summary.clone(), summary_style),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Tree"))
+        .highlight_style(theme.selection_style())
+        .highlight_symbol(theme::selection_symbol_str());
+    frame.render_stateful_widget(list, area, &mut app.json_view.tree_state);
+}
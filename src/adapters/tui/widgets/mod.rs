@@ -4,7 +4,9 @@ pub(crate) mod envs;
 pub(crate) mod error;
 pub(crate) mod field_input;
 pub(crate) mod history;
+pub(crate) mod json_view;
 pub(crate) mod loading;
+pub(crate) mod problems;
 pub(crate) mod run_result;
 pub(crate) mod running;
 pub(crate) mod schema;
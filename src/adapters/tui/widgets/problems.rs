@@ -0,0 +1,66 @@
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
+use ratatui::Frame;
+
+use super::super::app::App;
+use super::super::theme::{self, Theme};
+use super::common::standard_screen_layout;
+use crate::domain::Severity;
+
+pub(crate) fn render_problems(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
+    let outer = Block::default().borders(Borders::ALL).title("Problems");
+    let inner = outer.inner(area);
+    frame.render_widget(outer, area);
+
+    let chunks = standard_screen_layout(inner, 0, 2);
+    let errors = app
+        .problems
+        .problems
+        .iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .count();
+    let warnings = app.problems.problems.len() - errors;
+    let title = format!("Schemas ({} errors, {} warnings)", errors, warnings);
+
+    if app.problems.problems.is_empty() {
+        let message = match &app.problems.error {
+            Some(err) => format!("Lint failed: {}", err),
+            None => "No problems found.".to_string(),
+        };
+        let empty = Paragraph::new(message)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: true });
+        frame.render_widget(empty, chunks[1]);
+    } else {
+        let items: Vec<ListItem> = app
+            .problems
+            .problems
+            .iter()
+            .map(|problem| {
+                let color = match problem.severity {
+                    Severity::Error => theme.semantic.error.color(),
+                    Severity::Warning => theme.semantic.warning.color(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<8}", problem.severity.label()),
+                        Style::default().fg(color).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(format!("{}  ", problem.location()), theme.text_secondary()),
+                    Span::raw(problem.message.clone()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(theme.selection_style())
+            .highlight_symbol(theme::selection_symbol_str());
+        frame.render_stateful_widget(list, chunks[1], &mut app.problems.list_state);
+    }
+
+    let footer = Paragraph::new("Up/Down move, Enter go to script, r re-check, Esc/q back")
+        .style(theme.text_secondary());
+    frame.render_widget(footer, chunks[2]);
+}
@@ -1,12 +1,14 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use ratatui::Frame;
 
 use super::super::app::{App, ExecutionStatus};
+use super::super::state::OutputView;
 use super::super::theme::Theme;
-use super::common::status_label_and_style;
-use crate::history;
+use super::common::{history_output_lines, status_label_and_style};
+use super::json_view;
 
 pub(crate) fn render_run_result(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
     let chunks = Layout::default()
@@ -14,6 +16,14 @@ pub(crate) fn render_run_result(frame: &mut Frame, area: Rect, app: &mut App, th
         .constraints([Constraint::Min(3), Constraint::Length(2)])
         .split(area);
 
+    let footer = Paragraph::new(footer_line(app, theme)).style(theme.text_secondary());
+    frame.render_widget(footer, chunks[1]);
+
+    if app.json_view.view != OutputView::Text {
+        json_view::render_json_view(frame, chunks[0], app, theme);
+        return;
+    }
+
     let lines = render_lines(app, theme);
     let view_height = chunks[0].height.saturating_sub(2) as usize;
     let max_scroll = lines.len().saturating_sub(view_height);
@@ -32,10 +42,33 @@ pub(crate) fn render_run_result(frame: &mut Frame, area: Rect, app: &mut App, th
         .wrap(Wrap { trim: false })
         .scroll((app.run_output_scroll, 0));
     frame.render_widget(output, chunks[0]);
+}
 
-    let footer = Paragraph::new("Up/Down to scroll, PgUp/PgDn, Enter/Esc to return, h for history")
-        .style(theme.text_secondary());
-    frame.render_widget(footer, chunks[1]);
+fn footer_line(app: &App, theme: &Theme) -> Line<'static> {
+    let hint = match app.json_view.view {
+        OutputView::Text if app.json_view.is_available() => {
+            "Up/Down to scroll, PgUp/PgDn, g groups, v structured view, Enter/Esc to return, h for history"
+        }
+        OutputView::Text => {
+            "Up/Down to scroll, PgUp/PgDn, g groups, Enter/Esc to return, h for history"
+        }
+        OutputView::Table => {
+            "Up/Down rows, Left/Right columns, s sort, x hide, a show all, c copy cell, C copy row, e export CSV, v view, Esc return"
+        }
+        OutputView::Tree => {
+            "Up/Down move, Enter/Space expand, c copy value, v view, Esc return"
+        }
+    };
+    match &app.json_view.message {
+        Some(message) => Line::from(vec![
+            Span::styled(
+                format!("{} ", message),
+                Style::default().fg(theme.semantic.info.color()),
+            ),
+            Span::raw(hint),
+        ]),
+        None => Line::from(hint),
+    }
 }
 
 fn render_lines(app: &App, theme: &Theme) -> Vec<Line<'static>> {
@@ -63,11 +96,10 @@ fn render_lines(app: &App, theme: &Theme) -> Vec<Line<'static>> {
         Span::styled(status_label, status_style),
     ]));
     lines.push(Line::from(""));
-    let output = history::format_output(entry);
-    if output.trim().is_empty() {
-        lines.push(Line::from("(no output)"));
-    } else {
-        lines.extend(output.lines().map(|line| Line::from(line.to_string())));
-    }
+    lines.extend(history_output_lines(
+        entry,
+        app.output_groups_expanded,
+        theme,
+    ));
     lines
 }
@@ -1,35 +1,137 @@
-use ratatui::layout::{Alignment, Rect};
-use ratatui::text::Line;
-use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap};
 use ratatui::Frame;
 
 use super::super::app::App;
+use super::super::theme::Theme;
+use super::common::annotation_line;
+use super::field_input::render_field_box;
 
-pub(crate) fn render_running(frame: &mut Frame, area: Rect, app: &mut App) {
+pub(crate) fn render_running(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
     let script_name = app
-        .field_input
-        .selected_script
+        .running
+        .script
         .as_ref()
         .and_then(|path| path.file_name())
         .and_then(|name| name.to_str())
         .unwrap_or("<unknown>");
-    let args = if app.field_input.args.is_empty() {
+    let args = if app.running.args.is_empty() {
         "-".to_string()
     } else {
-        app.field_input.args.join(" ")
+        app.running.args.join(" ")
     };
+    let elapsed = app
+        .running
+        .started_at
+        .map(|started| started.elapsed().as_secs())
+        .unwrap_or(0);
 
-    let lines = vec![
-        Line::from("Running script..."),
-        Line::from(""),
+    let outer = Block::default().borders(Borders::ALL).title("Executing");
+    let inner = outer.inner(area);
+    frame.render_widget(outer, area);
+
+    let annotation_height = app.running.annotations.len().min(5) as u16;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(annotation_height),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+    let header = Paragraph::new(vec![
         Line::from(format!("Script: {}", script_name)),
         Line::from(format!("Args: {}", args)),
-        Line::from(""),
-        Line::from("Please wait."),
-    ];
-    let block = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Executing"))
-        .alignment(Alignment::Center)
-        .wrap(Wrap { trim: true });
-    frame.render_widget(block, area);
+        Line::from(format!("Elapsed: {}s", elapsed)),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    frame.render_widget(header, chunks[0]);
+
+    let label = app
+        .running
+        .progress_message
+        .clone()
+        .unwrap_or_else(|| "Running script...".to_string());
+    match app.running.progress {
+        Some(percent) => {
+            let gauge = Gauge::default()
+                .block(Block::default().borders(Borders::ALL).title(label))
+                .gauge_style(Style::default().fg(theme.brand.accent.color()))
+                .percent(percent as u16);
+            frame.render_widget(gauge, chunks[1]);
+        }
+        None => {
+            let waiting = Paragraph::new("Please wait.")
+                .block(Block::default().borders(Borders::ALL).title(label))
+                .alignment(Alignment::Center);
+            frame.render_widget(waiting, chunks[1]);
+        }
+    }
+
+    let skip = app.running.annotations.len() - annotation_height as usize;
+    let annotations: Vec<Line> = app.running.annotations[skip..]
+        .iter()
+        .map(|annotation| annotation_line(annotation, theme))
+        .collect();
+    frame.render_widget(Paragraph::new(annotations), chunks[2]);
+
+    let visible = chunks[3].height.saturating_sub(2) as usize;
+    let start = app.running.recent_lines.len().saturating_sub(visible);
+    let output: Vec<Line> = app
+        .running
+        .recent_lines
+        .iter()
+        .skip(start)
+        .map(|line| Line::from(line.clone()))
+        .collect();
+    let output = Paragraph::new(output)
+        .block(Block::default().borders(Borders::ALL).title("Output"))
+        .style(theme.text_secondary());
+    frame.render_widget(output, chunks[3]);
+
+    render_prompt(frame, inner, app, theme);
+}
+
+/// Draws the pending `::prompt` as a modal over the run output.
+fn render_prompt(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
+    let Some(prompt) = &app.running.prompt else {
+        return;
+    };
+    let width = area.width.min(70);
+    let height = 7u16.min(area.height);
+    let rect = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+    frame.render_widget(Clear, rect);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Input requested")
+        .border_style(Style::default().fg(theme.semantic.warning.color()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Length(1)])
+        .split(inner);
+    render_field_box(frame, chunks[0], &prompt.field, &prompt.input, true, theme);
+    let footer = match &prompt.error {
+        Some(message) => Line::from(Span::styled(
+            format!("Error: {}", message),
+            Style::default().fg(theme.semantic.error.color()),
+        )),
+        None => Line::from(Span::styled(
+            "Enter to answer, Esc to stop the run",
+            theme.text_secondary(),
+        )),
+    };
+    frame.render_widget(Paragraph::new(footer), chunks[1]);
 }
@@ -59,6 +59,67 @@ fn build_lines(
     if !preview.tags.is_empty() {
         lines.push(Line::from(format!("Tags: {}", preview.tags.join(", "))));
     }
+    if !preview.requires.is_empty() {
+        let missing = preview
+            .requires
+            .iter()
+            .filter(|requirement| requirement.problem.is_some())
+            .count();
+        let checking = preview
+            .requires
+            .iter()
+            .any(|requirement| requirement.checking);
+        let (summary, summary_style) = if missing == 0 && checking {
+            ("checking…".to_string(), theme.text_muted())
+        } else if missing == 0 {
+            (
+                "all found".to_string(),
+                Style::default().fg(theme.semantic.success.color()),
+            )
+        } else {
+            (
+                format!("{} missing", missing),
+                Style::default().fg(theme.semantic.error.color()),
+            )
+        };
+        lines.push(Line::from(vec![
+            Span::raw("Requires: "),
+            Span::styled(summary, summary_style),
+        ]));
+        for requirement in &preview.requires {
+            if requirement.checking {
+                lines.push(Line::from(vec![
+                    Span::raw("- "),
+                    Span::styled(
+                        format!("{} (checking…)", requirement.label),
+                        theme.text_muted(),
+                    ),
+                ]));
+                continue;
+            }
+            match &requirement.problem {
+                Some(problem) => {
+                    lines.push(Line::from(vec![
+                        Span::raw("- "),
+                        Span::styled(
+                            requirement.label.clone(),
+                            Style::default()
+                                .fg(theme.semantic.error.color())
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ]));
+                    lines.push(Line::from(format!("    {}", problem)));
+                }
+                None => lines.push(Line::from(vec![
+                    Span::raw("- "),
+                    Span::styled(
+                        requirement.label.clone(),
+                        Style::default().fg(theme.semantic.success.color()),
+                    ),
+                ])),
+            }
+        }
+    }
     lines.push(Line::from(""));
     if preview.fields.is_empty() {
         lines.push(Line::from(Span::styled("(no fields)", theme.text_muted())));
@@ -1,4 +1,5 @@
 use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Wrap};
 use ratatui::Frame;
@@ -7,7 +8,7 @@ use super::super::app::{App, SchemaFieldPreview, SchemaPreview};
 use super::super::theme::{self, Theme};
 use super::common::{horizontal_split, standard_screen_layout};
 use super::schema;
-use crate::search_index::{SearchDetails, SearchResult, SearchStatus};
+use crate::search_index::{Highlighted, SearchDetails, SearchResult, SearchStatus};
 
 pub(crate) fn render_search(frame: &mut Frame, area: Rect, app: &mut App, theme: &Theme) {
     let outer = Block::default().borders(Borders::ALL).title("Search");
@@ -23,13 +24,19 @@ pub(crate) fn render_search(frame: &mut Frame, area: Rect, app: &mut App, theme:
 
 fn render_search_input(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let title = match &app.search.status {
-        SearchStatus::Indexing => "Search (indexing...)".to_string(),
+        SearchStatus::Indexing { done, total } if *total > 0 => {
+            format!("Search (indexing {}/{})", done, total)
+        }
+        SearchStatus::Indexing { .. } => "Search (indexing...)".to_string(),
         SearchStatus::Ready { script_count } => format!("Search ({} scripts)", script_count),
         SearchStatus::Error(_) => "Search (index error)".to_string(),
         SearchStatus::Idle => "Search".to_string(),
     };
     let query_line = if app.search.query.is_empty() {
-        Line::from(Span::styled("Type to search...", theme.text_muted()))
+        Line::from(Span::styled(
+            "Type to search... (tag:, kind:, field:, path:, has:queue|outputs|error)",
+            theme.text_muted(),
+        ))
     } else {
         Line::from(app.search.query.clone())
     };
@@ -43,8 +50,12 @@ fn render_search_body(frame: &mut Frame, area: Rect, app: &mut App, theme: &Them
     if app.search.results.is_empty() {
         let message = if let Some(err) = &app.search.error {
             format!("Search error: {}", err)
-        } else if matches!(app.search.status, SearchStatus::Indexing) {
-            "Indexing scripts...".to_string()
+        } else if let SearchStatus::Indexing { done, total } = app.search.status {
+            if total > 0 {
+                format!("Indexing scripts ({}/{})...", done, total)
+            } else {
+                "Indexing scripts...".to_string()
+            }
         } else {
             "No scripts found for this search.".to_string()
         };
@@ -66,11 +77,21 @@ fn render_search_results(frame: &mut Frame, area: Rect, app: &mut App, theme: &T
         .search
         .results
         .iter()
-        .map(|result| ListItem::new(result_label(result)))
+        .map(|result| ListItem::new(result_label(result, theme)))
         .collect();
+    let title = if app
+        .search
+        .results
+        .first()
+        .is_some_and(|result| result.fuzzy)
+    {
+        "Results (close matches)"
+    } else {
+        "Results"
+    };
 
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Results"))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(theme.selection_style())
         .highlight_symbol(theme::selection_symbol_str());
 
@@ -99,7 +120,7 @@ fn render_search_schema(frame: &mut Frame, area: Rect, app: &App, theme: &Theme)
 
 fn render_search_footer(frame: &mut Frame, area: Rect, app: &App, theme: &Theme) {
     let hint = match &app.search.status {
-        SearchStatus::Indexing => {
+        SearchStatus::Indexing { .. } => {
             "Type to search, Enter open, Alt+E envs, Esc back. Indexing in background."
         }
         SearchStatus::Error(_) => "Type to search, Enter open, Alt+E envs, Esc back. Index error.",
@@ -109,13 +130,32 @@ fn render_search_footer(frame: &mut Frame, area: Rect, app: &App, theme: &Theme)
     frame.render_widget(footer, area);
 }
 
-fn result_label(result: &SearchResult) -> String {
+fn result_label(result: &SearchResult, theme: &Theme) -> Line<'static> {
     let path = result.script_path.to_string_lossy();
     if result.display_name == path {
-        path.to_string()
-    } else {
-        format!("{} ({})", result.display_name, path)
+        return Line::from(highlighted_spans(&result.path_highlight, theme));
     }
+    let mut spans = highlighted_spans(&result.name_highlight, theme);
+    spans.push(Span::raw(" ("));
+    spans.extend(highlighted_spans(&result.path_highlight, theme));
+    spans.push(Span::raw(")"));
+    Line::from(spans)
+}
+
+fn highlighted_spans(text: &Highlighted, theme: &Theme) -> Vec<Span<'static>> {
+    let matched = Style::default()
+        .fg(theme.brand.accent.color())
+        .add_modifier(Modifier::BOLD);
+    text.segments
+        .iter()
+        .map(|(segment, is_match)| {
+            if *is_match {
+                Span::styled(segment.clone(), matched)
+            } else {
+                Span::raw(segment.clone())
+            }
+        })
+        .collect()
 }
 
 fn schema_title(selected: Option<&SearchResult>) -> String {
@@ -148,6 +188,7 @@ fn build_schema_preview_from_details(details: &SearchDetails) -> SchemaPreview {
         fields,
         outputs: Vec::new(),
         queue: None,
+        requires: Vec::new(),
     }
 }
 
@@ -159,5 +200,6 @@ fn build_schema_preview_from_result(result: &SearchResult) -> SchemaPreview {
         fields: Vec::new(),
         outputs: Vec::new(),
         queue: None,
+        requires: Vec::new(),
     }
 }
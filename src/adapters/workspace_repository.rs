@@ -1,20 +1,108 @@
-use crate::domain::{extract_schema_block, parse_schema, Schema};
-use crate::error::{AppResult, ScriptError};
+use crate::domain::{
+    locate_schema_block, parse_document, parse_schema_as, spec_violations, FieldLibrary, Schema,
+    SchemaFormat,
+};
+use crate::error::{AppResult, SchemaError, ScriptError};
+use crate::lua_api::{LuaContext, SandboxPolicy};
+use crate::lua_script;
 use crate::ports::{ScriptRepository, WorkspaceEntry, WorkspaceEntryKind};
-use crate::runtime::{script_kind, ScriptKind};
+use crate::runtime::{schema_comment_prefixes, script_kind, ScriptKind};
+use crate::workspace::Workspace;
+use serde_json::Value;
+use std::collections::HashMap;
 
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use crate::util::read_dir_or_empty;
+
+/// Library files with their modification times, and the library loaded from them.
+type CachedLibrary = (Vec<(PathBuf, Option<SystemTime>)>, Arc<FieldLibrary>);
+
 pub struct FsWorkspaceRepository {
     root: PathBuf,
+    fields: Mutex<Option<CachedLibrary>>,
+    /// `[schema] strict` with the modification time of the `omakure.toml` it was read from.
+    strict: Mutex<Option<(Option<SystemTime>, bool)>>,
 }
 
 impl FsWorkspaceRepository {
     pub fn new<P: Into<PathBuf>>(root: P) -> Self {
-        Self { root: root.into() }
+        Self {
+            root: root.into(),
+            fields: Mutex::new(None),
+            strict: Mutex::new(None),
+        }
+    }
+
+    /// Whether schemas with unknown properties or mistyped values are rejected
+    /// (`[schema] strict`), re-read when `omakure.toml` changes.
+    pub fn strict(&self) -> bool {
+        let workspace = Workspace::new(self.root.clone());
+        let modified = fs::metadata(workspace.config_path())
+            .and_then(|meta| meta.modified())
+            .ok();
+        let mut cache = self.strict.lock().unwrap_or_else(|err| err.into_inner());
+        match *cache {
+            Some((cached, strict)) if cached == modified => strict,
+            _ => {
+                let strict = workspace.load_config().schema.strict;
+                *cache = Some((modified, strict));
+                strict
+            }
+        }
+    }
+
+    /// The schema as written, before `$ref` fields are expanded. An embedded JSON block must
+    /// hold exactly one object.
+    pub fn schema_document(&self, script: &Path) -> AppResult<Value> {
+        if let Some(sidecar) = sidecar_schema_paths(script).first() {
+            let contents = fs::read_to_string(sidecar)?;
+            return Ok(parse_document(&contents, format_of(sidecar))?);
+        }
+        let prefixes = match script_kind(script) {
+            Some(ScriptKind::Lua) => {
+                return lua_script::read_schema_document(script, &self.lua_context(script));
+            }
+            Some(kind) => schema_comment_prefixes(kind),
+            None => return Err(ScriptError::UnsupportedType.into()),
+        };
+        let contents = fs::read_to_string(script)?;
+        let block = locate_schema_block(&contents, prefixes)?;
+        Ok(parse_document(&block.text, block.format)?)
+    }
+
+    fn lua_context(&self, script: &Path) -> LuaContext {
+        let workspace = Workspace::new(self.root.clone());
+        LuaContext {
+            workspace_root: self.root.clone(),
+            env: HashMap::new(),
+            sandbox: SandboxPolicy::for_dir(&workspace, script.parent().unwrap_or(&self.root)),
+        }
+    }
+
+    /// Shared field definitions, reloaded when a library file is added, removed or edited.
+    /// Broken library files are skipped here; `omakure lint` reports them.
+    pub fn field_library(&self) -> Arc<FieldLibrary> {
+        let stamps: Vec<(PathBuf, Option<SystemTime>)> = field_library_files(&self.root)
+            .into_iter()
+            .map(|(_, path)| {
+                let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+                (path, modified)
+            })
+            .collect();
+        let mut cache = self.fields.lock().unwrap_or_else(|err| err.into_inner());
+        match cache.as_ref() {
+            Some((cached, library)) if *cached == stamps => Arc::clone(library),
+            _ => {
+                let library = Arc::new(load_field_library(&self.root).0);
+                *cache = Some((stamps, Arc::clone(&library)));
+                library
+            }
+        }
     }
 }
 
@@ -61,20 +149,158 @@ impl ScriptRepository for FsWorkspaceRepository {
     }
 
     fn read_schema(&self, script: &Path) -> AppResult<Schema> {
+        if self.strict() {
+            let violations = spec_violations(&self.schema_document(script)?);
+            if let Some(first) = violations.first() {
+                let more = match violations.len() - 1 {
+                    0 => String::new(),
+                    count => format!(" (and {} more; run `omakure schema check`)", count),
+                };
+                return Err(SchemaError::Strict(format!(
+                    "{}: {}{}",
+                    first.pointer, first.message, more
+                ))
+                .into());
+            }
+        }
+        if let Some(sidecar) = sidecar_schema_paths(script).first() {
+            return read_sidecar_schema(sidecar, &self.field_library());
+        }
         let prefixes = match script_kind(script) {
-            Some(ScriptKind::Bash) => vec!["#"],
-            Some(ScriptKind::PowerShell) => vec!["#", ";"],
-            Some(ScriptKind::Python) => vec!["#"],
+            Some(ScriptKind::Lua) => {
+                return lua_script::read_schema(script, &self.lua_context(script));
+            }
+            Some(kind) => schema_comment_prefixes(kind),
             None => return Err(ScriptError::UnsupportedType.into()),
         };
 
         let contents = fs::read_to_string(script)?;
-        let block = extract_schema_block(&contents, &prefixes)?;
-        Ok(parse_schema(&block)?)
+        let block = locate_schema_block(&contents, prefixes)?;
+        Ok(parse_schema_as(
+            &block.text,
+            block.format,
+            &self.field_library(),
+        )?)
+    }
+}
+
+/// Sidecar schema extensions, in precedence order.
+const SIDECAR_EXTENSIONS: [&str; 4] = ["json", "toml", "yaml", "yml"];
+
+/// Existing sidecar schema files for `script` (`deploy.py` -> `deploy.py.omakure.toml`), in
+/// precedence order. The first one wins over any schema embedded in the script.
+pub fn sidecar_schema_paths(script: &Path) -> Vec<PathBuf> {
+    let Some(file_name) = script.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    SIDECAR_EXTENSIONS
+        .iter()
+        .map(|extension| script.with_file_name(format!("{}.omakure.{}", file_name, extension)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Script a sidecar schema file belongs to: `deploy.py.omakure.toml` -> `deploy.py`.
+pub fn sidecar_owner(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+    let (script, extension) = file_name.rsplit_once(".omakure.")?;
+    SIDECAR_EXTENSIONS
+        .contains(&extension)
+        .then(|| path.with_file_name(script))
+}
+
+pub fn read_sidecar_schema(path: &Path, library: &FieldLibrary) -> AppResult<Schema> {
+    let contents = fs::read_to_string(path)?;
+    Ok(parse_schema_as(&contents, format_of(path), library)?)
+}
+
+/// Format named by a file's extension; JSON when it names none.
+pub fn format_of(path: &Path) -> SchemaFormat {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(SchemaFormat::from_name)
+        .unwrap_or(SchemaFormat::Json)
+}
+
+/// Field library files with the namespace their fields are referenced under, in load order:
+/// `.omaken/<flavor>/fields.json` (as `<flavor>/...`), then `fields/<name>.json` (as
+/// `<name>/...`), so workspace definitions replace flavor ones. TOML and YAML work too.
+pub fn field_library_files(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let workspace = Workspace::new(root.to_path_buf());
+    let mut flavors: Vec<PathBuf> = read_dir_or_empty(workspace.omaken_dir())
+        .map(|entries| entries.into_iter().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    flavors.sort();
+    for flavor in flavors.iter().filter(|path| path.is_dir()) {
+        let Some(name) = flavor.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if let Some(path) = SIDECAR_EXTENSIONS
+            .iter()
+            .map(|extension| flavor.join(format!("fields.{}", extension)))
+            .find(|path| path.is_file())
+        {
+            files.push((name.to_string(), path));
+        }
     }
+
+    let mut shared: Vec<PathBuf> = read_dir_or_empty(&root.join("fields"))
+        .map(|entries| entries.into_iter().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    shared.sort();
+    for path in shared {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        if let (Some(extension), Some(stem)) = (extension, stem) {
+            if path.is_file() && SchemaFormat::from_name(extension).is_some() {
+                files.push((stem.to_string(), path.clone()));
+            }
+        }
+    }
+    files
+}
+
+/// Loads every field library file, returning the library and the files that failed to load.
+pub fn load_field_library(root: &Path) -> (FieldLibrary, Vec<(PathBuf, String)>) {
+    let mut library = FieldLibrary::default();
+    let mut errors = Vec::new();
+    for (namespace, path) in field_library_files(root) {
+        let loaded = fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| parse_document(&text, format_of(&path)).map_err(|err| err.to_string()))
+            .and_then(|document| library.add_document(&namespace, document));
+        if let Err(err) = loaded {
+            errors.push((path, err));
+        }
+    }
+    (library, errors)
+}
+
+/// Sidecar schema files whose owner isn't a script Omakure lists: the owner is missing, or is
+/// neither a supported script nor an executable file.
+pub fn stray_sidecars(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut sidecars = Vec::new();
+    collect_files(
+        root,
+        &|path| {
+            sidecar_owner(path)
+                .is_some_and(|owner| script_kind(&owner).is_none() || !owner.is_file())
+        },
+        &mut sidecars,
+    )?;
+    Ok(sidecars)
 }
 
 fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) -> io::Result<()> {
+    collect_files(dir, &|path| script_kind(path).is_some(), scripts)
+}
+
+fn collect_files(
+    dir: &Path,
+    keep: &dyn Fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
     let entries = read_dir_or_empty(dir)?;
 
     for entry in entries {
@@ -83,9 +309,9 @@ fn collect_scripts(dir: &Path, scripts: &mut Vec<PathBuf>) -> io::Result<()> {
             if should_skip_dir(&path) {
                 continue;
             }
-            collect_scripts(&path, scripts)?;
-        } else if path.is_file() && script_kind(&path).is_some() {
-            scripts.push(path);
+            collect_files(&path, keep, files)?;
+        } else if path.is_file() && keep(&path) {
+            files.push(path);
         }
     }
 
@@ -97,13 +323,23 @@ fn should_skip_dir(path: &Path) -> bool {
     if matches!(name, Some(".history") | Some(".git")) {
         return true;
     }
-    if matches!(name, Some("envs")) {
+    if matches!(name, Some("envs") | Some("presets")) {
         if let Some(parent) = path.parent().and_then(|parent| parent.file_name()) {
             if parent == ".omaken" {
                 return true;
             }
         }
     }
+    // Flavor script templates (`.omaken/<flavor>/templates`) aren't scripts themselves.
+    if matches!(name, Some("templates")) {
+        let grandparent = path
+            .parent()
+            .and_then(Path::parent)
+            .and_then(|dir| dir.file_name());
+        if grandparent.is_some_and(|dir| dir == ".omaken") {
+            return true;
+        }
+    }
     false
 }
 
@@ -0,0 +1,248 @@
+use crate::adapters::workspace_repository::sidecar_schema_paths;
+use crate::arg_check::{parsed_args, ParsedArg, HELP_FLAGS};
+use crate::domain::locate_schema_block;
+use crate::runtime::{schema_comment_prefixes, script_kind, ScriptKind};
+use std::path::Path;
+
+/// Builds a commented schema block from the options a script already parses.
+pub fn schema_block(script: &Path, contents: &str) -> Result<String, String> {
+    let kind = script_kind(script).ok_or("Unsupported script type")?;
+    if kind == ScriptKind::Lua {
+        return Err("Lua scripts declare their schema in a `schema` table".to_string());
+    }
+    // Executables are only listed with a sidecar, so they stop here.
+    if let Some(sidecar) = sidecar_schema_paths(script).first() {
+        return Err(format!(
+            "Script already has a sidecar schema: {}",
+            sidecar.display()
+        ));
+    }
+    let prefix = schema_comment_prefixes(kind)[0];
+    if locate_schema_block(contents, schema_comment_prefixes(kind)).is_ok() {
+        return Err("Script already has a schema block".to_string());
+    }
+
+    let name = script
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("script");
+    let args: Vec<ParsedArg> = parsed_args(kind, contents)
+        .into_iter()
+        .filter(|arg| {
+            !arg.flags
+                .iter()
+                .any(|flag| HELP_FLAGS.contains(&flag.as_str()))
+        })
+        .collect();
+
+    let mut json = vec![
+        "{".to_string(),
+        format!("  \"Name\": {},", quote(name)),
+        "  \"Description\": \"Describe what this script does.\",".to_string(),
+        "  \"Tags\": [],".to_string(),
+    ];
+    if args.is_empty() {
+        json.push("  \"Fields\": []".to_string());
+    } else {
+        json.push("  \"Fields\": [".to_string());
+        for (index, arg) in args.iter().enumerate() {
+            json.extend(field_lines(arg, index + 1));
+            if index + 1 < args.len() {
+                let last = json.last_mut().expect("field lines");
+                last.push(',');
+            }
+        }
+        json.push("  ]".to_string());
+    }
+    json.push("}".to_string());
+
+    let mut block = format!("{} OMAKURE_SCHEMA_START\n", prefix);
+    for line in json {
+        block.push_str(&format!("{} {}\n", prefix, line));
+    }
+    block.push_str(&format!("{} OMAKURE_SCHEMA_END\n", prefix));
+    Ok(block)
+}
+
+/// Inserts `block` at the top of the script, after a `#!` line if there is one.
+pub fn insert_block(contents: &str, block: &str) -> String {
+    match contents.split_once('\n') {
+        Some((first, rest)) if first.starts_with("#!") => {
+            format!("{}\n\n{}\n{}", first, block, rest)
+        }
+        _ if contents.starts_with("#!") => format!("{}\n\n{}", contents, block),
+        _ => format!("{}\n{}", block, contents),
+    }
+}
+
+fn field_lines(arg: &ParsedArg, order: usize) -> Vec<String> {
+    // The longest spelling usually reads best: `--resource-group` over `-g`.
+    let flag = arg
+        .flags
+        .iter()
+        .max_by_key(|flag| flag.len())
+        .expect("parsed args have a flag");
+    let name = flag.trim_start_matches('-').replace('-', "_");
+    let prompt = arg.help.clone().unwrap_or_else(|| humanize(&name));
+
+    let mut properties = vec![
+        format!("\"Name\": {}", quote(&name)),
+        format!("\"Prompt\": {}", quote(&prompt)),
+        format!("\"Type\": {}", quote(arg.kind.unwrap_or("string"))),
+        format!("\"Order\": {}", order),
+        format!("\"Required\": {}", arg.required),
+        format!("\"Arg\": {}", quote(flag)),
+    ];
+    // Omakure passes `--flag true|false` unless the field is a switch.
+    if arg.switch {
+        properties.push("\"Switch\": true".to_string());
+    }
+    if let Some(default) = &arg.default {
+        properties.push(format!("\"Default\": {}", quote(default)));
+    }
+    if !arg.choices.is_empty() {
+        let choices: Vec<String> = arg.choices.iter().map(|choice| quote(choice)).collect();
+        properties.push(format!("\"Choices\": [{}]", choices.join(", ")));
+    }
+
+    let count = properties.len();
+    let mut lines = vec!["    {".to_string()];
+    for (index, property) in properties.into_iter().enumerate() {
+        let comma = if index + 1 < count { "," } else { "" };
+        lines.push(format!("      {}{}", property, comma));
+    }
+    lines.push("    }".to_string());
+    lines
+}
+
+/// `resource_group` -> `Resource group`; `ResourceGroup` -> `Resource group`.
+pub fn humanize(name: &str) -> String {
+    let mut words = String::new();
+    for (index, ch) in name.chars().enumerate() {
+        if ch == '_' {
+            words.push(' ');
+        } else if ch.is_uppercase() && index > 0 && !words.ends_with(' ') {
+            words.push(' ');
+            words.extend(ch.to_lowercase());
+        } else if index == 0 {
+            words.extend(ch.to_uppercase());
+        } else {
+            words.push(ch);
+        }
+    }
+    words
+}
+
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{normalize_input, parse_schema_as, FieldLibrary, SchemaFormat};
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn test_adopted_python_block_round_trips() {
+        let script = r#"#!/usr/bin/env python3
+import argparse
+parser = argparse.ArgumentParser()
+parser.add_argument("-g", "--resource-group", required=True, help="Resource \"group\"")
+parser.add_argument("--count", type=int, default=3)
+parser.add_argument("--env", choices=["dev", "prod"])
+parser.add_argument("--dry-run", action="store_true")
+parser.add_argument("-h", "--help", action="help")
+"#;
+        let block = schema_block(Path::new("deploy.py"), script).unwrap();
+        let adopted = insert_block(script, &block);
+        assert!(adopted.starts_with("#!/usr/bin/env python3\n\n# OMAKURE_SCHEMA_START\n"));
+
+        let text = locate_schema_block(&adopted, &["#"]).unwrap().text;
+        let schema = parse_schema_as(&text, SchemaFormat::Json, &FieldLibrary::default()).unwrap();
+        assert_eq!(schema.name, "deploy");
+        let fields: Vec<String> = schema
+            .fields
+            .iter()
+            .map(|field| {
+                format!(
+                    "{} {} {} {} {:?} {:?} {:?} {}",
+                    field.order,
+                    field.name,
+                    field.kind,
+                    field.arg.as_deref().unwrap_or(""),
+                    field.required,
+                    field.default,
+                    field.choices,
+                    field.is_switch()
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "1 resource_group string --resource-group Some(true) None None false",
+                "2 count number --count Some(false) Some(\"3\") None false",
+                "3 env string --env Some(false) None Some([\"dev\", \"prod\"]) false",
+                "4 dry_run bool --dry-run Some(false) Some(\"false\") None true",
+            ]
+        );
+        assert_eq!(
+            schema.fields[0].prompt.as_deref(),
+            Some("Resource \"group\"")
+        );
+        assert_eq!(schema.fields[1].prompt.as_deref(), Some("Count"));
+        assert_eq!(
+            schema_block(Path::new("deploy.py"), &adopted).unwrap_err(),
+            "Script already has a schema block"
+        );
+    }
+
+    #[test]
+    fn test_adopted_bash_switch_runs() {
+        let script = r#"#!/usr/bin/env bash
+while [[ $# -gt 0 ]]; do
+  case "$1" in
+    --name) NAME="$2"; shift 2 ;;
+    --dry-run) DRY_RUN=1; shift ;;
+    *) echo "Unknown arg: $1" >&2; exit 1 ;;
+  esac
+done
+echo "${NAME}:${DRY_RUN:-0}"
+"#;
+        let block = schema_block(Path::new("greet.sh"), script).unwrap();
+        let adopted = insert_block(script, &block);
+        let text = locate_schema_block(&adopted, &["#"]).unwrap().text;
+        let schema = parse_schema_as(&text, SchemaFormat::Json, &FieldLibrary::default()).unwrap();
+        assert_eq!(schema.fields[1].kind, "bool");
+
+        let path = std::env::temp_dir().join(format!("omakure-adopt-{}.sh", std::process::id()));
+        fs::write(&path, &adopted).unwrap();
+        let run = |inputs: [&str; 2]| {
+            let mut args = Vec::new();
+            for (field, input) in schema.fields.iter().zip(inputs) {
+                if let Some(value) = normalize_input(field, input).unwrap() {
+                    args.extend(field.cli_args(value));
+                }
+            }
+            let output = Command::new("bash")
+                .arg(&path)
+                .args(&args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{:?}", args);
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        assert_eq!(run(["ada", "yes"]), "ada:1");
+        assert_eq!(run(["ada", "false"]), "ada:0");
+        assert_eq!(run(["ada", ""]), "ada:0");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(humanize("resource_group"), "Resource group");
+        assert_eq!(humanize("ResourceGroup"), "Resource group");
+    }
+}
@@ -0,0 +1,663 @@
+use crate::adapters::workspace_repository::{sidecar_schema_paths, FsWorkspaceRepository};
+use crate::domain::{locate_schema_block, Schema, Severity};
+use crate::lint::Problem;
+use crate::ports::ScriptRepository;
+use crate::runtime::{schema_comment_prefixes, script_kind, ScriptKind};
+use std::fs;
+use std::path::Path;
+
+/// Flags scripts commonly accept without declaring them as fields.
+pub const HELP_FLAGS: [&str; 2] = ["-h", "--help"];
+
+/// An option the script body parses, with whatever the parser reveals about it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedArg {
+    /// Spellings that reach the same option, e.g. `-t` and `--target`.
+    pub flags: Vec<String>,
+    pub line: usize,
+    /// `string`, `number` or `bool`, when the parser makes it clear.
+    pub kind: Option<&'static str>,
+    /// The option takes no value; it's on when present.
+    pub switch: bool,
+    pub required: bool,
+    pub choices: Vec<String>,
+    pub default: Option<String>,
+    pub help: Option<String>,
+}
+
+impl ParsedArg {
+    fn new(flags: Vec<String>, line: usize) -> Self {
+        Self {
+            flags,
+            line,
+            ..Self::default()
+        }
+    }
+}
+
+/// Options parsed by the script body, found statically for the given runtime.
+pub fn parsed_args(kind: ScriptKind, contents: &str) -> Vec<ParsedArg> {
+    match kind {
+        ScriptKind::Bash => bash_args(contents),
+        ScriptKind::PowerShell => powershell_args(contents),
+        ScriptKind::Python => python_args(contents),
+        ScriptKind::Lua | ScriptKind::Executable => Vec::new(),
+    }
+}
+
+/// Compares the flags declared in the schema (`Arg`, or `--<Name>`) with the flags the script
+/// body parses. Findings are reported relative to `root`.
+pub fn check_script(root: &Path, script: &Path) -> Vec<Problem> {
+    let relative = script.strip_prefix(root).unwrap_or(script).to_path_buf();
+    let problem = |line: usize, severity: Severity, message: String| Problem {
+        script: relative.clone(),
+        line,
+        severity,
+        message,
+    };
+
+    let Some(kind) = script_kind(script) else {
+        return Vec::new();
+    };
+    // Lua scripts receive their arguments already mapped to field names, and an executable's
+    // parser can't be read.
+    if matches!(kind, ScriptKind::Lua | ScriptKind::Executable) {
+        return Vec::new();
+    }
+    let contents = match fs::read_to_string(script) {
+        Ok(contents) => contents,
+        Err(err) => return vec![problem(1, Severity::Error, err.to_string())],
+    };
+    // A sidecar schema wins over the embedded block, so declared fields are located there.
+    let sidecar = sidecar_schema_paths(script).into_iter().next();
+    let schema_file = sidecar.as_deref().unwrap_or(script);
+    let schema_contents = match &sidecar {
+        Some(path) => fs::read_to_string(path).unwrap_or_default(),
+        None => contents.clone(),
+    };
+    let repo = FsWorkspaceRepository::new(root.to_path_buf());
+    let schema_start = match &sidecar {
+        Some(_) => 1,
+        None => locate_schema_block(&contents, schema_comment_prefixes(kind))
+            .map(|block| block.start_line)
+            .unwrap_or(1),
+    };
+    let schema = match repo.read_schema(script) {
+        Ok(schema) => schema,
+        Err(err) => {
+            return vec![problem(
+                1,
+                Severity::Error,
+                format!("{} (run `omakure lint` for details)", err),
+            )]
+        }
+    };
+
+    let parsed = parsed_args(kind, &contents);
+    let same = |a: &str, b: &str| match kind {
+        // PowerShell binds `-Target` and matches parameter names case-insensitively.
+        ScriptKind::PowerShell => a
+            .trim_start_matches('-')
+            .eq_ignore_ascii_case(b.trim_start_matches('-')),
+        _ => a == b,
+    };
+    let handles = |arg: &ParsedArg, flag: &str| arg.flags.iter().any(|parsed| same(parsed, flag));
+
+    let declared = declared_flags(&schema);
+    if parsed.is_empty() {
+        if declared.is_empty() {
+            return Vec::new();
+        }
+        return vec![problem(
+            schema_start,
+            Severity::Warning,
+            format!("No argument parsing found ({})", parser_hint(kind)),
+        )];
+    }
+
+    let mut problems = Vec::new();
+    for (name, flag, switch) in &declared {
+        let schema_problem = |severity: Severity, message: String| {
+            let line = find_line(&schema_contents, schema_start, &format!("\"{}\"", name))
+                .unwrap_or(schema_start);
+            Problem {
+                script: schema_file
+                    .strip_prefix(root)
+                    .unwrap_or(schema_file)
+                    .to_path_buf(),
+                line,
+                severity,
+                message,
+            }
+        };
+        match parsed.iter().find(|arg| handles(arg, flag)) {
+            None => problems.push(schema_problem(
+                Severity::Error,
+                format!(
+                    "Field `{}` passes `{}`, but the script doesn't parse it",
+                    name, flag
+                ),
+            )),
+            Some(arg) if arg.switch && !switch => problems.push(schema_problem(
+                Severity::Warning,
+                format!(
+                    "Field `{}` passes `{} true|false`, but the script reads `{}` as a bare switch; set \"Switch\": true",
+                    name, flag, flag
+                ),
+            )),
+            _ => {}
+        }
+    }
+    for arg in &parsed {
+        let known = declared.iter().any(|(_, flag, _)| handles(arg, flag));
+        let help = arg
+            .flags
+            .iter()
+            .any(|flag| HELP_FLAGS.contains(&flag.as_str()));
+        if !known && !help {
+            problems.push(problem(
+                arg.line,
+                Severity::Warning,
+                format!(
+                    "The script parses `{}`, but no schema field uses it",
+                    arg.flags.join("`/`")
+                ),
+            ));
+        }
+    }
+    problems
+}
+
+fn declared_flags(schema: &Schema) -> Vec<(String, String, bool)> {
+    schema
+        .fields
+        .iter()
+        .map(|field| (field.name.clone(), field.flag(), field.is_switch()))
+        .collect()
+}
+
+fn parser_hint(kind: ScriptKind) -> &'static str {
+    match kind {
+        ScriptKind::Bash => "looked for `case \"$1\"` arms and getopts",
+        ScriptKind::PowerShell => "looked for a param() block and switch arms",
+        ScriptKind::Python => "looked for argparse add_argument calls",
+        ScriptKind::Lua | ScriptKind::Executable => "",
+    }
+}
+
+/// `case "$1" in` arms such as `-t|--target)` and `getopts "t:v"` option strings.
+/// An arm that reads `$2` or shifts twice takes a value; otherwise it's a switch.
+fn bash_args(contents: &str) -> Vec<ParsedArg> {
+    let lines: Vec<&str> = contents.lines().map(strip_shell_comment).collect();
+    let mut args = Vec::new();
+    let mut in_case = false;
+    for (index, code) in lines.iter().enumerate() {
+        if code.starts_with("case ")
+            && code.ends_with(" in")
+            && (code.contains("$1") || code.contains("${1}"))
+        {
+            in_case = true;
+            continue;
+        }
+        if in_case && code.starts_with("esac") {
+            in_case = false;
+            continue;
+        }
+        if in_case {
+            let Some((pattern, rest)) = code.split_once(')') else {
+                continue;
+            };
+            let mut inline_value = false;
+            let flags: Vec<String> = pattern
+                .split('|')
+                .map(|alternative| alternative.trim().trim_matches(['"', '\'']))
+                .filter_map(|alternative| {
+                    let (flag, value) = match alternative.split_once('=') {
+                        Some((flag, _)) => (flag, true),
+                        None => (alternative, false),
+                    };
+                    // `--)` ends the options and `-*)` catches unknown ones; neither is a flag.
+                    let valid = flag.starts_with('-')
+                        && flag.len() > 1
+                        && flag != "--"
+                        && !flag.contains(|ch: char| ch.is_whitespace() || "*?[".contains(ch));
+                    inline_value |= valid && value;
+                    valid.then(|| flag.to_string())
+                })
+                .collect();
+            if flags.is_empty() {
+                continue;
+            }
+            let mut body = rest.to_string();
+            for next in lines.iter().skip(index + 1) {
+                if body.contains(";;") {
+                    break;
+                }
+                body.push('\n');
+                body.push_str(next);
+            }
+            let takes_value = inline_value
+                || body.contains("$2")
+                || body.contains("${2")
+                || body.contains("shift 2");
+            let mut arg = ParsedArg::new(flags, index + 1);
+            arg.kind = Some(if takes_value { "string" } else { "bool" });
+            arg.switch = !takes_value;
+            args.push(arg);
+        }
+        if let Some((_, rest)) = code.split_once("getopts ") {
+            let spec: Vec<char> = rest
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .trim_matches(['"', '\''])
+                .chars()
+                .collect();
+            for (position, letter) in spec.iter().enumerate() {
+                if !letter.is_ascii_alphanumeric() {
+                    continue;
+                }
+                let mut arg = ParsedArg::new(vec![format!("-{}", letter)], index + 1);
+                let takes_value = spec.get(position + 1) == Some(&':');
+                arg.kind = Some(if takes_value { "string" } else { "bool" });
+                arg.switch = !takes_value;
+                args.push(arg);
+            }
+        }
+    }
+    args
+}
+
+fn strip_shell_comment(line: &str) -> &str {
+    let code = line.trim();
+    if code.starts_with('#') {
+        return "";
+    }
+    code.split(" #").next().unwrap_or(code).trim_end()
+}
+
+/// Parameters of the script-level `param()` block, plus `"--flag" {` switch arms.
+fn powershell_args(contents: &str) -> Vec<ParsedArg> {
+    let mut args = Vec::new();
+    let lower = contents.to_ascii_lowercase();
+    if let Some(start) = lower.find("param(").or_else(|| lower.find("param (")) {
+        let open = start + lower[start..].find('(').unwrap_or(0) + 1;
+        for (offset, declaration) in top_level_args(&contents[open..]) {
+            if let Some(arg) = powershell_param(contents, open + offset, declaration) {
+                args.push(arg);
+            }
+        }
+    }
+    for (index, line) in contents.lines().enumerate() {
+        let code = line.trim();
+        let Some(literal) = string_literal(code) else {
+            continue;
+        };
+        let opens_block = code_chars(code)
+            .find(|(_, ch)| !ch.is_whitespace())
+            .is_some_and(|(_, ch)| ch == '{');
+        if literal.starts_with('-') && opens_block {
+            let mut arg = ParsedArg::new(vec![literal], index + 1);
+            arg.kind = Some("string");
+            args.push(arg);
+        }
+    }
+    args
+}
+
+/// Reads one `[Parameter(Mandatory)][ValidateSet('a')][string]$Name = 'x'` declaration.
+fn powershell_param(contents: &str, offset: usize, declaration: &str) -> Option<ParsedArg> {
+    let dollar = top_level_position(declaration, '$')?;
+    let name: String = declaration[dollar + 1..]
+        .chars()
+        .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    let line = contents[..offset + dollar].matches('\n').count() + 1;
+    let mut arg = ParsedArg::new(vec![format!("-{}", name)], line);
+
+    let mut rest = &declaration[..dollar];
+    while let Some(open) = rest.find('[') {
+        let inner = &rest[open + 1..];
+        let close = closing_bracket(inner)?;
+        let group = inner[..close].trim();
+        let lower = group.to_ascii_lowercase();
+        if lower.starts_with("parameter") {
+            let compact: String = lower.split_whitespace().collect();
+            arg.required = compact.contains("mandatory")
+                && !compact.contains("mandatory=$false")
+                && !compact.contains("mandatory=$0");
+        } else if lower.starts_with("validateset") {
+            let open = group
+                .find('(')
+                .map(|index| index + 1)
+                .unwrap_or(group.len());
+            arg.choices = top_level_args(&group[open..])
+                .into_iter()
+                .filter_map(|(_, item)| string_literal(item.trim()))
+                .collect();
+        } else if !lower.contains('(') {
+            arg.switch = lower == "switch";
+            arg.kind = match lower.trim_end_matches("[]") {
+                "switch" | "bool" => Some("bool"),
+                "int" | "int32" | "int64" | "long" | "double" | "float" | "decimal" => {
+                    Some("number")
+                }
+                _ => Some("string"),
+            };
+        }
+        rest = &inner[close + 1..];
+    }
+
+    if let Some(equals) = top_level_position(&declaration[dollar..], '=') {
+        let value = declaration[dollar + equals + 1..].trim();
+        arg.default = match value.to_ascii_lowercase().as_str() {
+            "$true" => Some("true".to_string()),
+            "$false" => Some("false".to_string()),
+            _ if value.starts_with('$') || value.starts_with('(') => None,
+            _ => string_literal(value).or_else(|| Some(value.to_string())),
+        };
+    }
+    Some(arg)
+}
+
+/// Option strings and keywords of `add_argument(...)` calls, e.g.
+/// `add_argument("-t", "--target", type=int, required=True, help="Target")`.
+fn python_args(contents: &str) -> Vec<ParsedArg> {
+    let mut args = Vec::new();
+    for (start, _) in contents.match_indices("add_argument(") {
+        let line = contents[..start].matches('\n').count() + 1;
+        let mut arg = ParsedArg::new(Vec::new(), line);
+        for (_, item) in top_level_args(&contents[start + "add_argument(".len()..]) {
+            let item = item.trim();
+            if let Some(literal) = string_literal(item) {
+                if literal.starts_with('-') {
+                    arg.flags.push(literal);
+                }
+                continue;
+            }
+            let Some((key, value)) = item.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "type" => {
+                    arg.kind = match value {
+                        "int" | "float" => Some("number"),
+                        _ => Some("string"),
+                    }
+                }
+                "action" => {
+                    if let Some(action) = string_literal(value) {
+                        if action == "store_true" || action == "store_false" {
+                            arg.kind = Some("bool");
+                            arg.switch = true;
+                            arg.default.get_or_insert_with(|| "false".to_string());
+                        }
+                    }
+                }
+                "required" => arg.required = value == "True",
+                "choices" => {
+                    arg.choices = top_level_args(value.get(1..).unwrap_or(""))
+                        .into_iter()
+                        .map(|(_, choice)| python_literal(choice.trim()))
+                        .filter(|choice| !choice.is_empty())
+                        .collect();
+                }
+                "default" if value != "None" => arg.default = Some(python_literal(value)),
+                "help" => arg.help = string_literal(value),
+                _ => {}
+            }
+        }
+        if !arg.flags.is_empty() {
+            args.push(arg);
+        }
+    }
+    args
+}
+
+fn python_literal(value: &str) -> String {
+    match value {
+        "True" => "true".to_string(),
+        "False" => "false".to_string(),
+        _ => string_literal(value).unwrap_or_else(|| value.to_string()),
+    }
+}
+
+/// Contents of a leading `"..."` or `'...'` literal, with `\"`-style escapes resolved.
+fn string_literal(text: &str) -> Option<String> {
+    let quote = text.chars().next().filter(|ch| *ch == '"' || *ch == '\'')?;
+    let mut value = String::new();
+    let mut chars = text[1..].chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => value.extend(chars.next()),
+            _ if ch == quote => return Some(value),
+            _ => value.push(ch),
+        }
+    }
+    None
+}
+
+/// Characters outside string literals, with their byte offsets.
+fn code_chars(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    text.char_indices().filter(move |&(_, ch)| {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == open {
+                quote = None;
+            }
+            return false;
+        }
+        if ch == '"' || ch == '\'' {
+            quote = Some(ch);
+            return false;
+        }
+        true
+    })
+}
+
+/// Splits a call's arguments on top-level commas, stopping at its closing bracket.
+/// Each argument comes with its byte offset in `text`.
+fn top_level_args(text: &str) -> Vec<(usize, &str)> {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, ch) in code_chars(text) {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => {
+                args.push((start, &text[start..index]));
+                return args;
+            }
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push((start, &text[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    args
+}
+
+/// Position of the bracket closing an already opened one.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, ch) in code_chars(text) {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth == 0 => return Some(index),
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// First `target` outside brackets and quotes.
+fn top_level_position(text: &str, target: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, ch) in code_chars(text) {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ if ch == target && depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_line(contents: &str, from: usize, needle: &str) -> Option<usize> {
+    contents
+        .lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .find(|(_, line)| line.contains(needle))
+        .map(|(index, _)| index + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(parsed: &[ParsedArg]) -> Vec<String> {
+        parsed.iter().flat_map(|arg| arg.flags.clone()).collect()
+    }
+
+    fn kinds(parsed: &[ParsedArg]) -> Vec<&'static str> {
+        parsed.iter().map(|arg| arg.kind.unwrap_or("-")).collect()
+    }
+
+    #[test]
+    fn test_bash_case_arms_and_getopts() {
+        let script = r#"
+while [[ $# -gt 0 ]]; do
+  case "$1" in
+    -t|--target)
+      shift 2 ;;
+    --force=*) shift ;;
+    -h|--help) usage ;;
+    --) shift; break ;;
+    -*|--[a-z]?) echo "Unknown option: $1" ;;
+    *) echo "Unknown arg: $1" ;;
+  esac
+done
+while getopts ":v:q" opt; do :; done
+"#;
+        let parsed = bash_args(script);
+        assert_eq!(
+            flags(&parsed),
+            vec!["-t", "--target", "--force", "-h", "--help", "-v", "-q"]
+        );
+        assert_eq!(
+            kinds(&parsed),
+            vec!["string", "string", "bool", "string", "bool"]
+        );
+        assert_eq!(parsed[0].line, 4);
+    }
+
+    #[test]
+    fn test_powershell_param_block_and_switch_arms() {
+        let script = r#"
+param(
+  [Parameter(Mandatory = $true)]
+  [string]$ResourceGroup,
+  [int]$Count = $Default,
+  [ValidateSet('dev', "prod")]
+  [string]$Env = 'dev',
+  [switch]$Force
+)
+switch ($args[$i]) {
+  "--target" {
+    $Target = $args[$i + 1]
+  }
+}
+"#;
+        let parsed = powershell_args(script);
+        assert_eq!(
+            flags(&parsed),
+            vec!["-ResourceGroup", "-Count", "-Env", "-Force", "--target"]
+        );
+        assert_eq!(
+            kinds(&parsed),
+            vec!["string", "number", "string", "bool", "string"]
+        );
+        assert_eq!(parsed[0].line, 4);
+        assert!(parsed[0].required && !parsed[1].required);
+        assert_eq!(parsed[1].default, None);
+        assert_eq!(parsed[2].choices, vec!["dev", "prod"]);
+        assert_eq!(parsed[2].default.as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_python_add_argument() {
+        let script = r#"
+parser.add_argument("-t", "--target", default="", help="--not-a-flag")
+parser.add_argument(
+    '--rg',
+    choices=("a", "b"),
+    required=True,
+)
+parser.add_argument("--count", type=int, default=3)
+parser.add_argument("--force", action="store_true")
+parser.add_argument("positional")
+"#;
+        let parsed = python_args(script);
+        assert_eq!(
+            flags(&parsed),
+            vec!["-t", "--target", "--rg", "--count", "--force"]
+        );
+        assert_eq!(kinds(&parsed), vec!["-", "-", "number", "bool"]);
+        assert_eq!(parsed[0].help.as_deref(), Some("--not-a-flag"));
+        assert!(parsed[1].required);
+        assert_eq!(parsed[1].choices, vec!["a", "b"]);
+        assert_eq!(parsed[2].default.as_deref(), Some("3"));
+        assert_eq!(parsed[3].default.as_deref(), Some("false"));
+    }
+
+    #[test]
+    fn test_check_script_reports_both_directions() {
+        let root = std::env::temp_dir().join(format!("omakure-args-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let script = root.join("rg.bash");
+        fs::write(
+            &script,
+            r#"# OMAKURE_SCHEMA_START
+# {"Name": "rg", "Fields": [
+#   {"Name": "resource_group", "Type": "string", "Order": 1, "Arg": "--resource-group"},
+#   {"Name": "force", "Type": "bool", "Order": 2}
+# ]}
+# OMAKURE_SCHEMA_END
+case "$1" in
+  --rg) shift 2 ;;
+  --force) shift ;;
+esac
+"#,
+        )
+        .unwrap();
+
+        let messages: Vec<String> = check_script(&root, &script)
+            .into_iter()
+            .map(|problem| format!("{} {}", problem.location(), problem.message))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "rg.bash:3 Field `resource_group` passes `--resource-group`, but the script doesn't parse it",
+                "rg.bash:4 Field `force` passes `--force true|false`, but the script reads `--force` as a bare switch; set \"Switch\": true",
+                "rg.bash:8 The script parses `--rg`, but no schema field uses it",
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,20 @@
+use super::args::AdoptArgs;
+use super::run::resolve_script_path;
+use crate::adopt::{insert_block, schema_block};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf, options: AdoptArgs) -> Result<(), Box<dyn Error>> {
+    let script = resolve_script_path(&options.script, &scripts_dir)?;
+    let contents = fs::read_to_string(&script)?;
+    let block = schema_block(&script, &contents)?;
+
+    if !options.write {
+        print!("{}", block);
+        return Ok(());
+    }
+    fs::write(&script, insert_block(&contents, &block))?;
+    println!("Added a schema to {}", script.display());
+    Ok(())
+}
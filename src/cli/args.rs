@@ -33,7 +33,25 @@ pub enum Commands {
     /// List available scripts
     Scripts,
 
-    /// Create a new script template
+    /// Report scripts with broken or suspicious schemas
+    Lint,
+
+    /// Compare schema fields with the flags each script parses
+    CheckArgs(CheckArgsArgs),
+
+    /// Generate a schema for a script from the arguments it already parses
+    Adopt(AdoptArgs),
+
+    /// Regenerate a script's argument parser from its schema
+    Codegen(CodegenArgs),
+
+    /// Run script tests from `*.omakure-test.toml` files with stubbed commands
+    Test(TestArgs),
+
+    /// Export the schema format or check schemas against it
+    Schema(SchemaArgs),
+
+    /// Create a new script from a template, with a wizard in a terminal
     Init(InitArgs),
 
     /// Show resolved paths and env
@@ -51,28 +69,123 @@ pub enum Commands {
 
     /// Manage themes
     Theme(ThemeArgs),
+
+    /// Entry point of the shims `run --record` and `run --replay` put on PATH
+    #[command(name = "__shim", hide = true, disable_help_flag = true)]
+    Shim(ShimArgs),
 }
 
 #[derive(Args, Debug)]
 pub struct RunArgs {
-    /// Script name or path
-    #[arg(value_name = "SCRIPT")]
-    pub script: String,
+    /// Script name or path (defaults to the replayed cassette's script)
+    #[arg(value_name = "SCRIPT", required_unless_present = "replay")]
+    pub script: Option<String>,
+
+    /// Answer for a `::prompt` request (repeatable, must precede SCRIPT)
+    #[arg(long = "answer", value_name = "ID=VALUE")]
+    pub answers: Vec<String>,
+
+    /// File piped into the script's stdin (`-` reads omakure's own stdin)
+    #[arg(long = "stdin-from", value_name = "FILE")]
+    pub stdin_from: Option<PathBuf>,
+
+    /// Fill fields from a preset saved in the TUI (`.omaken/presets/`); arguments after SCRIPT
+    /// take precedence
+    #[arg(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Record the external commands given by --stub into a cassette stored with the history entry
+    #[arg(long, requires = "stub")]
+    pub record: bool,
+
+    /// Commands to record, comma separated (e.g. `az,kubectl,jq`)
+    #[arg(
+        long,
+        value_name = "COMMANDS",
+        value_delimiter = ',',
+        requires = "record"
+    )]
+    pub stub: Vec<String>,
+
+    /// Commands from --stub that read stdin, comma separated; their input goes into the cassette.
+    /// Other recorded commands leave the script's stdin alone
+    #[arg(
+        long,
+        value_name = "COMMANDS",
+        value_delimiter = ',',
+        requires = "stub"
+    )]
+    pub stub_stdin: Vec<String>,
+
+    /// Serve external commands from a cassette, or from the cassette of a history entry
+    #[arg(long, value_name = "FILE", conflicts_with = "record")]
+    pub replay: Option<PathBuf>,
 
     /// Arguments forwarded to the script
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub args: Vec<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct ShimArgs {
+    #[arg(value_enum)]
+    pub mode: ShimMode,
+    pub dir: PathBuf,
+    pub command: String,
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ShimMode {
+    Record,
+    Replay,
+}
+
+#[derive(Args, Debug)]
+pub struct CheckArgsArgs {
+    /// Script name or path (defaults to every script in the workspace)
+    #[arg(value_name = "SCRIPT")]
+    pub script: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct AdoptArgs {
+    /// Script name or path
+    #[arg(value_name = "SCRIPT")]
+    pub script: String,
+    /// Insert the schema into the script instead of printing it
+    #[arg(long)]
+    pub write: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CodegenArgs {
+    /// Script name or path
+    #[arg(value_name = "SCRIPT")]
+    pub script: String,
+    /// Exit with an error if the parser is out of date instead of rewriting it
+    #[arg(long)]
+    pub check: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct InitArgs {
-    /// Script path
-    #[arg(value_name = "SCRIPT", required_unless_present = "name")]
+    /// Script path (asked for when omitted in a terminal)
+    #[arg(value_name = "SCRIPT")]
     pub script: Option<String>,
 
     /// Script path (legacy)
     #[arg(long, value_name = "SCRIPT")]
     pub name: Option<String>,
+
+    /// Template to start from: built-in, user (~/.config/omakure/templates) or flavor
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
+
+    /// Skip the wizard and use the template's defaults
+    #[arg(long, short = 'y')]
+    pub yes: bool,
 }
 
 #[derive(Args, Debug)]
@@ -128,7 +241,47 @@ pub struct ThemeSetArgs {
     pub name: String,
 }
 
+#[derive(Args, Debug)]
+pub struct TestArgs {
+    /// Scripts to test (defaults to every script with a test file)
+    #[arg(value_name = "SCRIPT")]
+    pub scripts: Vec<String>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value = "tap")]
+    pub format: TestReportFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum TestReportFormat {
+    Tap,
+    Junit,
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    #[command(subcommand)]
+    pub command: SchemaCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SchemaCommand {
+    /// Print a JSON Schema (draft 2020-12) for script schemas
+    Export,
+
+    /// Report unknown properties, wrong types and invalid values in schemas
+    Check(SchemaCheckArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SchemaCheckArgs {
+    /// Script name or path (defaults to every script)
+    #[arg(value_name = "SCRIPT")]
+    pub script: Option<String>,
+}
+
 #[derive(ValueEnum, Clone, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Shell {
     Bash,
     Zsh,
@@ -0,0 +1,27 @@
+use super::args::CheckArgsArgs;
+use super::lint::{exit_on_errors, print_problems};
+use super::run::resolve_script_path;
+use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::arg_check::check_script;
+use crate::ports::ScriptRepository;
+use std::error::Error;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf, options: CheckArgsArgs) -> Result<(), Box<dyn Error>> {
+    let scripts = match options.script {
+        Some(script) => vec![resolve_script_path(&script, &scripts_dir)?],
+        None => {
+            let repo = FsWorkspaceRepository::new(scripts_dir.clone());
+            let mut scripts = repo.list_scripts_recursive()?;
+            scripts.sort();
+            scripts
+        }
+    };
+
+    let problems: Vec<_> = scripts
+        .iter()
+        .flat_map(|script| check_script(&scripts_dir, script))
+        .collect();
+    exit_on_errors(print_problems(&problems));
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use super::args::CodegenArgs;
+use super::run::resolve_script_path;
+use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::codegen::{apply_block, args_block, parser_outside_block};
+use crate::ports::ScriptRepository;
+use crate::runtime::script_kind;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf, options: CodegenArgs) -> Result<(), Box<dyn Error>> {
+    let script = resolve_script_path(&options.script, &scripts_dir)?;
+    let kind = script_kind(&script).ok_or("Unsupported script type")?;
+    let repo = FsWorkspaceRepository::new(scripts_dir);
+    let schema = repo.read_schema(&script)?;
+    let block = args_block(kind, &schema)?;
+    let contents = fs::read_to_string(&script)?;
+    let updated = apply_block(kind, &contents, &block)?;
+
+    if updated == contents {
+        println!("{} is up to date", script.display());
+    } else if options.check {
+        return Err(format!("Argument parser of {} is out of date", script.display()).into());
+    } else {
+        fs::write(&script, &updated)?;
+        println!("Regenerated the argument parser of {}", script.display());
+    }
+    if let Some(line) = parser_outside_block(kind, &updated) {
+        eprintln!(
+            "warning: {}:{} still parses arguments outside the generated region; remove the hand-written parser",
+            script.display(),
+            line
+        );
+    }
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use crate::adapters::system_checks::{
-    ensure_bash_installed, ensure_git_installed, ensure_jq_installed, ensure_powershell_installed,
+    check_requirement, ensure_bash_installed, ensure_git_installed, ensure_powershell_installed,
     ensure_python_installed,
 };
+use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::domain::{version_at_least, Requirement};
+use crate::ports::ScriptRepository;
 use crate::workspace::Workspace;
 use std::error::Error;
 use std::path::PathBuf;
@@ -13,7 +16,6 @@ pub fn run(scripts_dir: PathBuf) -> Result<(), Box<dyn Error>> {
     println!("Checks:");
     ok &= print_required("git", ensure_git_installed());
     ok &= print_required("bash", ensure_bash_installed());
-    ok &= print_required("jq", ensure_jq_installed());
     print_optional("powershell", ensure_powershell_installed());
     print_optional("python", ensure_python_installed());
 
@@ -22,6 +24,20 @@ pub fn run(scripts_dir: PathBuf) -> Result<(), Box<dyn Error>> {
     print_workspace_path("history_dir", workspace.history_dir());
     print_workspace_path("workspace_config", workspace.config_path());
 
+    let requirements = collect_requirements(&workspace);
+    if !requirements.is_empty() {
+        println!("Script requirements:");
+        for (requirement, scripts) in &requirements {
+            let label = format!(
+                "{} (used by {} script{})",
+                requirement.label(),
+                scripts,
+                if *scripts == 1 { "" } else { "s" }
+            );
+            print_optional(&label, check_requirement(requirement));
+        }
+    }
+
     if !ok {
         println!("One or more checks failed.");
         std::process::exit(1);
@@ -31,6 +47,47 @@ pub fn run(scripts_dir: PathBuf) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Merges `Requires` entries across the workspace, keeping the highest minimum version.
+fn collect_requirements(workspace: &Workspace) -> Vec<(Requirement, usize)> {
+    let repo = FsWorkspaceRepository::new(workspace.root().to_path_buf());
+    let scripts = repo.list_scripts_recursive().unwrap_or_default();
+    let mut merged: Vec<(Requirement, usize)> = Vec::new();
+    for script in scripts {
+        let Ok(schema) = repo.read_schema(&script) else {
+            continue;
+        };
+        let mut seen: Vec<String> = Vec::new();
+        for requirement in schema.requires.unwrap_or_default() {
+            let first_in_script = !seen.contains(&requirement.command);
+            seen.push(requirement.command.clone());
+            let existing = merged
+                .iter_mut()
+                .find(|(item, _)| item.command == requirement.command);
+            let Some((item, count)) = existing else {
+                merged.push((requirement, 1));
+                continue;
+            };
+            if first_in_script {
+                *count += 1;
+            }
+            let stricter = match (&item.min_version, &requirement.min_version) {
+                (_, None) => false,
+                (None, Some(_)) => true,
+                (Some(current), Some(next)) => !version_at_least(current, next),
+            };
+            if stricter {
+                item.min_version = requirement.min_version;
+                item.version_args = requirement.version_args.or(item.version_args.take());
+            }
+            if item.hint.is_none() {
+                item.hint = requirement.hint;
+            }
+        }
+    }
+    merged.sort_by(|a, b| a.0.command.cmp(&b.0.command));
+    merged
+}
+
 fn print_required<E: std::fmt::Display>(label: &str, result: Result<(), E>) -> bool {
     match result {
         Ok(()) => {
@@ -1,38 +1,78 @@
+use crate::adopt::humanize;
 use crate::cli::args::InitArgs;
+use crate::domain::Field;
 use crate::runtime::{script_extensions, script_kind, ScriptKind};
+use crate::script_template::{
+    builtin_template, find_template, list_templates, render, ScriptTemplate, TemplateAnswers,
+    TemplateSource,
+};
 use crate::util::set_executable_permissions;
 use crate::workspace::Workspace;
 use std::error::Error;
 use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
 use std::path::{Component, Path, PathBuf};
 
 pub fn run(scripts_dir: PathBuf, options: InitArgs) -> Result<(), Box<dyn Error>> {
-    let name = options
-        .name
-        .or(options.script)
-        .ok_or_else(|| "Missing script name. Use `omakure init <script-name>`.".to_string())?;
+    let workspace = Workspace::new(scripts_dir);
+    workspace.ensure_layout()?;
+    let templates = list_templates(&workspace);
+    let interactive = !options.yes && options.template.is_none() && io::stdin().is_terminal();
+
+    let name = match options.name.or(options.script) {
+        Some(name) => name,
+        None if interactive => ask("Script path (e.g. tools/deploy.sh)", None)?,
+        // `omakure init --template azure-rg` names the script after the template.
+        None => match &options.template {
+            Some(template) => {
+                let template = find_template(&templates, template, None)?;
+                format!("{}.{}", template.name, &template.extension)
+            }
+            None => {
+                return Err("Missing script name. Use `omakure init <script-name>`.".into());
+            }
+        },
+    };
     let name = name.trim();
     if name.is_empty() {
         return Err("Script name cannot be empty".into());
     }
-    let relative_path = ensure_script_path(name)?;
 
-    let workspace = Workspace::new(scripts_dir);
-    workspace.ensure_layout()?;
+    let named_kind = script_kind(Path::new(name));
+    let template = match &options.template {
+        Some(template) => find_template(&templates, template, named_kind)?,
+        None if interactive => choose_template(&templates, named_kind)?,
+        None => builtin_template(named_kind.unwrap_or(ScriptKind::Bash)),
+    };
+    let relative_path = ensure_script_path(name, &template.extension)?;
+    let kind = script_kind(&relative_path).ok_or("Unsupported script extension")?;
+    if kind != template.kind {
+        return Err(format!(
+            "Template `{}` is not for {} scripts",
+            template.qualified_name(),
+            relative_path.display()
+        )
+        .into());
+    }
+
     let script_path = workspace.root().join(&relative_path);
     if script_path.exists() {
         return Err(format!("Script already exists: {}", script_path.display()).into());
     }
-    if let Some(parent) = script_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
     let script_id = normalize_script_id(&script_path);
     if script_id.is_empty() {
         return Err("Script name must contain letters or numbers".into());
     }
-    let kind = script_kind(&script_path).ok_or("Unsupported script extension")?;
-    let content = build_template(&script_id, kind);
+
+    let answers = if interactive {
+        ask_answers(&script_id, kind)?
+    } else {
+        TemplateAnswers::defaults(&script_id, kind)
+    };
+    let content = render(&template, &answers)?;
+    if let Some(parent) = script_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
     fs::write(&script_path, content)?;
     set_executable_permissions(&script_path)?;
 
@@ -40,7 +80,132 @@ pub fn run(scripts_dir: PathBuf, options: InitArgs) -> Result<(), Box<dyn Error>
     Ok(())
 }
 
-fn ensure_script_path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+fn choose_template(
+    templates: &[ScriptTemplate],
+    kind: Option<ScriptKind>,
+) -> Result<ScriptTemplate, Box<dyn Error>> {
+    let usable: Vec<&ScriptTemplate> = templates
+        .iter()
+        .filter(|template| kind.is_none_or(|kind| template.kind == kind))
+        .collect();
+    println!("Templates:");
+    for template in &usable {
+        let source = match &template.source {
+            TemplateSource::User => "user".to_string(),
+            TemplateSource::Flavor(flavor) => format!("flavor {}", flavor),
+            TemplateSource::BuiltIn => "built-in".to_string(),
+        };
+        println!(
+            "  {} ({}, .{})",
+            template.qualified_name(),
+            source,
+            &template.extension
+        );
+    }
+    let default = builtin_template(kind.unwrap_or(ScriptKind::Bash)).name;
+    let name = ask("Template", Some(&default))?;
+    Ok(find_template(templates, &name, kind)?)
+}
+
+/// Asks for the schema: description, tags, then fields until an empty name.
+fn ask_answers(script_id: &str, kind: ScriptKind) -> Result<TemplateAnswers, Box<dyn Error>> {
+    let mut answers = TemplateAnswers::defaults(script_id, kind);
+    answers.name = ask("Name", Some(script_id))?;
+    answers.description = ask("Description", Some(&answers.description))?;
+    answers.tags = split_list(&ask("Tags (comma separated)", Some(""))?);
+    answers.fields.clear();
+
+    println!("Fields (leave the name empty to finish):");
+    loop {
+        let name = ask("  Field name", Some(""))?;
+        if name.is_empty() {
+            break;
+        }
+        let field_kind = loop {
+            let value = ask("  Type (string, number, bool)", Some("string"))?;
+            if ["string", "number", "bool"].contains(&value.as_str()) {
+                break value;
+            }
+            println!("  Unknown type `{}`", value);
+        };
+        let prompt = ask("  Prompt", Some(&humanize(&name)))?;
+        let required = matches!(
+            ask("  Required? (y/N)", Some("n"))?
+                .to_ascii_lowercase()
+                .as_str(),
+            "y" | "yes"
+        );
+        let default = Some(ask("  Default", Some(""))?).filter(|value| !value.is_empty());
+        let choices = Some(split_list(&ask("  Choices (comma separated)", Some(""))?))
+            .filter(|choices| !choices.is_empty());
+        let arg = match kind {
+            ScriptKind::Lua => None,
+            ScriptKind::PowerShell => {
+                Some(ask("  Arg", Some(&format!("-{}", pascal_case(&name))))?)
+            }
+            _ => Some(ask(
+                "  Arg",
+                Some(&format!("--{}", name.replace('_', "-"))),
+            )?),
+        };
+        answers.fields.push(Field {
+            name,
+            prompt: Some(prompt),
+            kind: field_kind,
+            order: answers.fields.len() as u32 + 1,
+            required: Some(required),
+            default,
+            choices,
+            arg,
+            secret: None,
+            switch: None,
+        });
+    }
+    Ok(answers)
+}
+
+/// Prompts on stdout and reads a line; an empty answer takes `default`.
+fn ask(label: &str, default: Option<&str>) -> Result<String, Box<dyn Error>> {
+    match default {
+        Some(default) if !default.is_empty() => print!("{} [{}]: ", label, default),
+        _ => print!("{}: ", label),
+    }
+    io::stdout().flush()?;
+    let mut input = String::new();
+    if io::stdin().lock().read_line(&mut input)? == 0 {
+        return Err("Input ended before the wizard finished".into());
+    }
+    let input = input.trim();
+    Ok(match default {
+        Some(default) if input.is_empty() => default.to_string(),
+        _ => input.to_string(),
+    })
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `resource_group` -> `ResourceGroup`.
+fn pascal_case(name: &str) -> String {
+    name.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn ensure_script_path(name: &str, extension: &str) -> Result<PathBuf, Box<dyn Error>> {
     let mut path = PathBuf::from(name);
     if path.is_absolute() {
         return Err("Script name must be a relative path".into());
@@ -54,7 +219,7 @@ fn ensure_script_path(name: &str) -> Result<PathBuf, Box<dyn Error>> {
         }
     }
     if path.extension().is_none() {
-        path.set_extension("bash");
+        path.set_extension(extension);
     }
     if script_kind(&path).is_none() {
         let allowed = script_extensions().join(", ");
@@ -82,152 +247,3 @@ fn normalize_script_id(path: &Path) -> String {
     }
     out.trim_matches('_').to_string()
 }
-
-fn build_template(script_id: &str, kind: ScriptKind) -> String {
-    match kind {
-        ScriptKind::Bash => build_bash_template(script_id),
-        ScriptKind::PowerShell => build_powershell_template(script_id),
-        ScriptKind::Python => build_python_template(script_id),
-    }
-}
-
-fn build_bash_template(script_id: &str) -> String {
-    format!(
-        r#"#!/usr/bin/env bash
-set -euo pipefail
-
-# 1) Schema for the TUI
-# OMAKURE_SCHEMA_START
-# {{
-#   "Name": "{script_id}",
-#   "Description": "Describe what this script does.",
-#   "Tags": [],
-#   "Fields": [
-#     {{
-#       "Name": "target",
-#       "Prompt": "Target (optional)",
-#       "Type": "string",
-#       "Order": 1,
-#       "Required": false,
-#       "Arg": "--target"
-#     }}
-#   ]
-# }}
-# OMAKURE_SCHEMA_END
-
-
-# 2) Defaults
-TARGET=""
-
-# 3) Args + prompts
-prompt_if_empty() {{
-  local var_name="$1"
-  local label="$2"
-  local value="${{!var_name:-}}"
-  if [[ -z "${{value}}" ]]; then
-    read -r -p "${{label}}: " value
-    printf -v "${{var_name}}" '%s' "${{value}}"
-  fi
-}}
-
-while [[ $# -gt 0 ]]; do
-  case "$1" in
-    --target)
-      TARGET="${{2:-}}"
-      shift 2
-      ;;
-    *)
-      echo "Unknown arg: $1" >&2
-      exit 1
-      ;;
-  esac
-done
-
-prompt_if_empty TARGET "Target (optional)"
-
-# 4) Main
-echo "TODO: implement {script_id}"
-"#,
-        script_id = script_id
-    )
-}
-
-fn build_powershell_template(script_id: &str) -> String {
-    format!(
-        r#"# PowerShell script template
-
-# OMAKURE_SCHEMA_START
-# {{
-#   "Name": "{script_id}",
-#   "Description": "Describe what this script does.",
-#   "Tags": [],
-#   "Fields": [
-#     {{
-#       "Name": "target",
-#       "Prompt": "Target (optional)",
-#       "Type": "string",
-#       "Order": 1,
-#       "Required": false,
-#       "Arg": "--target"
-#     }}
-#   ]
-# }}
-# OMAKURE_SCHEMA_END
-
-$Target = ""
-for ($i = 0; $i -lt $args.Length; $i++) {{
-  switch ($args[$i]) {{
-    "--target" {{
-      $Target = $args[$i + 1]
-      $i++
-    }}
-    default {{
-      Write-Error "Unknown arg: $($args[$i])"
-      exit 1
-    }}
-  }}
-}}
-
-if (-not $Target) {{
-  $Target = Read-Host "Target (optional)"
-}}
-
-Write-Output "TODO: implement {script_id}"
-"#,
-        script_id = script_id
-    )
-}
-
-fn build_python_template(script_id: &str) -> String {
-    format!(
-        r#"#!/usr/bin/env python3
-import argparse
-
-# OMAKURE_SCHEMA_START
-# {{
-#   "Name": "{script_id}",
-#   "Description": "Describe what this script does.",
-#   "Tags": [],
-#   "Fields": [
-#     {{
-#       "Name": "target",
-#       "Prompt": "Target (optional)",
-#       "Type": "string",
-#       "Order": 1,
-#       "Required": false,
-#       "Arg": "--target"
-#     }}
-#   ]
-# }}
-# OMAKURE_SCHEMA_END
-
-parser = argparse.ArgumentParser()
-parser.add_argument("--target", default="")
-args = parser.parse_args()
-target = args.target or input("Target (optional): ")
-
-print(f"TODO: implement {script_id}")
-"#,
-        script_id = script_id
-    )
-}
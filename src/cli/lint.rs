@@ -0,0 +1,47 @@
+use crate::domain::Severity;
+use crate::lint::{lint_workspace, Problem};
+use std::error::Error;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    let problems = lint_workspace(&scripts_dir)?;
+    exit_on_errors(print_problems(&problems));
+    Ok(())
+}
+
+/// Exits with status 1 when a report found errors.
+pub(crate) fn exit_on_errors(errors: usize) {
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Prints findings as `path:line: severity: message` and returns how many are errors.
+pub(crate) fn print_problems(problems: &[Problem]) -> usize {
+    for problem in problems {
+        println!(
+            "{}: {}: {}",
+            problem.location(),
+            problem.severity.label(),
+            problem.message
+        );
+    }
+
+    let errors = problems
+        .iter()
+        .filter(|problem| problem.severity == Severity::Error)
+        .count();
+    let warnings = problems.len() - errors;
+    if problems.is_empty() {
+        println!("No problems found.");
+    } else {
+        println!(
+            "{} error{}, {} warning{}.",
+            errors,
+            if errors == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        );
+    }
+    errors
+}
@@ -1,10 +1,17 @@
+pub mod adopt;
 pub mod args;
+pub mod check_args;
+pub mod codegen;
 pub mod config;
 pub mod doctor;
 pub mod init;
+pub mod lint;
 pub mod list;
 pub mod omaken;
 pub mod run;
+pub mod schema;
+pub mod shim;
+pub mod test;
 pub mod theme;
 pub mod uninstall;
 pub mod update;
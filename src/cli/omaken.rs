@@ -28,6 +28,10 @@ fn list_omaken(workspace: &Workspace) -> Result<(), Box<dyn Error>> {
         let path = entry.path();
         if path.is_dir() {
             if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                // Environment files and presets live next to flavors.
+                if name == "envs" || name == "presets" {
+                    continue;
+                }
                 flavors.push(name.to_string());
             }
         }
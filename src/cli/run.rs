@@ -1,49 +1,248 @@
+use crate::adapters::environments::FsEnvironmentRepository;
 use crate::adapters::script_runner::MultiScriptRunner;
 use crate::adapters::workspace_repository::FsWorkspaceRepository;
 use crate::cli::args::RunArgs;
+use crate::command_stub::{Cassette, ShimDir, ShimmedRunner};
+use crate::domain::{normalize_input, AnnotationLevel, OutputLine, PromptRequest};
 use crate::history;
-use crate::ports::ScriptRunOutput;
+use crate::lua_hooks::{run_with_hooks, HookedRun, Hooks};
+use crate::ports::{RunObserver, ScriptRunOutput, ScriptRunner};
+use crate::presets;
 use crate::runtime::script_extensions;
-use crate::use_cases::ScriptService;
+use crate::use_cases::{EnvironmentService, ScriptService};
 use crate::workspace::Workspace;
+use std::collections::HashMap;
 use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
 
 pub fn run(scripts_dir: PathBuf, options: RunArgs) -> Result<(), Box<dyn Error>> {
     let workspace = Workspace::new(scripts_dir);
     workspace.ensure_layout()?;
 
-    let script_path = resolve_script_path(&options.script, workspace.root())?;
+    let replay = match &options.replay {
+        Some(path) => Some(history::load_cassette(&workspace, path)?),
+        None => None,
+    };
+    // Without a SCRIPT, the replayed run is reproduced as recorded.
+    let reuse = if options.script.is_none() {
+        replay.as_ref()
+    } else {
+        None
+    };
+    let script = match (&options.script, reuse) {
+        (Some(script), _) => script.clone(),
+        (None, Some(cassette)) => cassette.script.to_string_lossy().to_string(),
+        (None, None) => return Err("Missing SCRIPT".into()),
+    };
+    let mut args = reuse.map_or_else(|| options.args.clone(), |cassette| cassette.args.clone());
+    let script_path = resolve_script_path(&script, workspace.root())?;
+    let answers = parse_answers(&options.answers)?;
 
+    let shims = if options.record || replay.is_some() {
+        Some(ShimDir::create()?)
+    } else {
+        None
+    };
+    let mut runner: Box<dyn ScriptRunner> = Box::new(MultiScriptRunner::new(workspace.root()));
+    if let Some(shims) = &shims {
+        let commands = match &replay {
+            Some(cassette) => cassette.commands(),
+            None => options.stub.clone(),
+        };
+        for command in &commands {
+            match &replay {
+                Some(cassette) => shims.add_replayer(command, &cassette.calls)?,
+                None => shims.add_recorder(command, options.stub_stdin.contains(command))?,
+            }
+        }
+        runner = Box::new(ShimmedRunner::new(runner, shims, commands));
+    }
     let repo = Box::new(FsWorkspaceRepository::new(workspace.root().to_path_buf()));
-    let runner = Box::new(MultiScriptRunner::new());
     let service = ScriptService::new(repo, runner);
 
-    let run_result = service.run_script(&script_path, &options.args);
-    match run_result {
+    let mut preset_stdin = None;
+    if let Some(name) = &options.preset {
+        let (preset_args, stdin) = preset_inputs(&workspace, &service, &script_path, name, &args)?;
+        args.splice(0..0, preset_args);
+        preset_stdin = stdin;
+    }
+    let stdin = match reuse {
+        Some(cassette) if options.stdin_from.is_none() => cassette.stdin.clone(),
+        _ if options.stdin_from.is_none() && preset_stdin.is_some() => preset_stdin,
+        _ => read_stdin_payload(&service, &script_path, options.stdin_from.as_deref())?,
+    };
+
+    let hooks = Hooks::discover(&workspace, &script_path, active_env_defaults(&workspace));
+    let mut observer = ConsoleObserver { answers };
+    let run = run_with_hooks(
+        &service,
+        &hooks,
+        &script_path,
+        &args,
+        stdin.as_deref(),
+        &mut observer,
+    );
+    let recorder = shims.as_ref().filter(|_| options.record);
+    let cassette = recorder
+        .map(|shims| take_cassette(&workspace, shims, &script_path, &args, stdin.as_deref()));
+    let exit_code = record_run(
+        &workspace,
+        &script_path,
+        stdin.as_deref(),
+        &run,
+        cassette.as_ref(),
+    );
+
+    if replay.is_some() && !run.triggers.is_empty() {
+        eprintln!("note: after_run triggers are skipped when replaying");
+    }
+    // Scripts started by `after_run` don't trigger further runs.
+    for trigger in run.triggers.iter().filter(|_| replay.is_none()) {
+        eprintln!(
+            "==> {} (after_run hook)",
+            trigger
+                .script
+                .strip_prefix(workspace.root())
+                .unwrap_or(&trigger.script)
+                .display()
+        );
+        let hooks = Hooks::discover(&workspace, &trigger.script, active_env_defaults(&workspace));
+        let triggered = run_with_hooks(
+            &service,
+            &hooks,
+            &trigger.script,
+            &trigger.args,
+            None,
+            &mut observer,
+        );
+        let cassette = recorder
+            .map(|shims| take_cassette(&workspace, shims, &trigger.script, &trigger.args, None));
+        record_run(
+            &workspace,
+            &trigger.script,
+            None,
+            &triggered,
+            cassette.as_ref(),
+        );
+    }
+    drop(shims);
+
+    match run.result {
+        Ok(_) if exit_code != 0 => std::process::exit(exit_code),
+        Ok(_) => Ok(()),
+        Err(err) => Err(Box::new(err)),
+    }
+}
+
+/// The calls recorded since the previous run, as a cassette for `script`.
+fn take_cassette(
+    workspace: &Workspace,
+    shims: &ShimDir,
+    script: &Path,
+    args: &[String],
+    stdin: Option<&str>,
+) -> Cassette {
+    Cassette {
+        script: script
+            .strip_prefix(workspace.root())
+            .unwrap_or(script)
+            .to_path_buf(),
+        args: args.to_vec(),
+        stdin: stdin.map(str::to_string),
+        calls: shims.take_recorded_calls(),
+    }
+}
+
+/// Prints the outcome, stores it in history and returns the process exit code to use.
+fn record_run(
+    workspace: &Workspace,
+    script: &Path,
+    stdin: Option<&str>,
+    run: &HookedRun,
+    cassette: Option<&Cassette>,
+) -> i32 {
+    let (mut entry, exit_code) = match &run.result {
         Ok(output) => {
-            let success = output.success;
-            let exit_code = output.exit_code.unwrap_or(1);
-            print_output(&output);
-            let entry = history::success_entry(&workspace, &script_path, &options.args, output);
-            let _ = history::record_entry(&workspace, &entry);
-            if !success {
-                std::process::exit(exit_code);
-            }
+            print_stderr(output);
+            let exit_code = if output.success {
+                0
+            } else {
+                output.exit_code.unwrap_or(1)
+            };
+            (
+                history::success_entry(workspace, script, &run.args, output.clone()),
+                exit_code,
+            )
         }
         Err(err) => {
             eprintln!("{}", err);
-            let entry =
-                history::error_entry(&workspace, &script_path, &options.args, err.to_string());
-            let _ = history::record_entry(&workspace, &entry);
-            return Err(Box::new(err));
+            (
+                history::error_entry(workspace, script, &run.args, err.to_string()),
+                1,
+            )
+        }
+    };
+    for note in &run.notes {
+        eprintln!("note: {}", note);
+    }
+    entry.notes = run.notes.clone();
+    history::attach_stdin(workspace, &mut entry, stdin);
+    if let Some(cassette) = cassette {
+        if let Err(err) = history::attach_cassette(workspace, &mut entry, cassette) {
+            eprintln!("warning: failed to save the cassette: {}", err);
         }
     }
+    let _ = history::record_entry(workspace, &entry);
+    exit_code
+}
+
+/// Arguments and stdin from the preset `name`, looked up for the active environment.
+fn preset_inputs(
+    workspace: &Workspace,
+    service: &ScriptService,
+    script: &Path,
+    name: &str,
+    explicit_args: &[String],
+) -> Result<(Vec<String>, Option<String>), Box<dyn Error>> {
+    let repo = FsEnvironmentRepository::new(workspace.envs_dir());
+    let config = EnvironmentService::new(Box::new(repo)).load_environment_config()?;
+    let saved = presets::load_presets(workspace, script)?;
+    let preset = presets::find_preset(&saved, name, config.active.as_deref()).ok_or_else(|| {
+        let names: Vec<String> = presets::available(&saved, config.active.as_deref())
+            .iter()
+            .map(|preset| preset.label())
+            .collect();
+        if names.is_empty() {
+            format!("Unknown preset '{}': none saved for this script", name)
+        } else {
+            format!("Unknown preset '{}'; available: {}", name, names.join(", "))
+        }
+    })?;
+    let schema = service.load_schema(script)?;
+    let inputs = presets::preset_inputs(
+        &schema,
+        preset,
+        &config.defaults,
+        explicit_args,
+        workspace.root(),
+    )?;
+    Ok(inputs)
+}
 
-    Ok(())
+fn active_env_defaults(workspace: &Workspace) -> HashMap<String, String> {
+    let repo = FsEnvironmentRepository::new(workspace.envs_dir());
+    EnvironmentService::new(Box::new(repo))
+        .load_environment_config()
+        .map(|config| config.defaults)
+        .unwrap_or_default()
 }
 
-fn resolve_script_path(script: &str, scripts_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+pub(crate) fn resolve_script_path(
+    script: &str,
+    scripts_dir: &Path,
+) -> Result<PathBuf, Box<dyn Error>> {
     let has_separator = script.contains('/') || script.contains('\\');
     let path = PathBuf::from(script);
 
@@ -78,13 +277,113 @@ fn resolve_with_extensions(path: PathBuf) -> Result<PathBuf, Box<dyn Error>> {
     Err(format!("Script not found: {}", path.display()).into())
 }
 
-fn print_output(output: &ScriptRunOutput) {
-    if !output.stdout.trim().is_empty() {
-        print!("{}", output.stdout);
-        if !output.stdout.ends_with('\n') {
-            println!();
+/// Picks the payload from `--stdin-from`, or from a pipe when the schema declares `Stdin`.
+fn read_stdin_payload(
+    service: &ScriptService,
+    script: &Path,
+    stdin_from: Option<&Path>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    match stdin_from {
+        Some(path) if path == Path::new("-") => read_own_stdin().map(Some),
+        Some(path) => fs::read_to_string(path)
+            .map(Some)
+            .map_err(|err| format!("Failed to read {}: {}", path.display(), err).into()),
+        None => {
+            let declares_stdin = service
+                .load_schema(script)
+                .map(|schema| schema.stdin.is_some())
+                .unwrap_or(false);
+            if declares_stdin && !io::stdin().is_terminal() {
+                read_own_stdin().map(Some)
+            } else {
+                Ok(None)
+            }
         }
     }
+}
+
+fn read_own_stdin() -> Result<String, Box<dyn Error>> {
+    let mut payload = String::new();
+    io::stdin().read_to_string(&mut payload)?;
+    Ok(payload)
+}
+
+fn parse_answers(values: &[String]) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    let mut answers = HashMap::new();
+    for value in values {
+        let (id, answer) = value
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid --answer '{}': expected ID=VALUE", value))?;
+        answers.insert(id.trim().to_string(), answer.to_string());
+    }
+    Ok(answers)
+}
+
+/// Streams output to the terminal and answers prompts from `--answer` values.
+struct ConsoleObserver {
+    answers: HashMap<String, String>,
+}
+
+impl RunObserver for ConsoleObserver {
+    fn on_line(&mut self, line: &OutputLine) {
+        print_line(line);
+    }
+
+    fn on_prompt(&mut self, prompt: &PromptRequest) -> Option<String> {
+        let field = prompt.to_field();
+        let input = match self.answers.get(&prompt.id) {
+            Some(answer) => answer.clone(),
+            None if io::stdin().is_terminal() => ask_terminal(prompt)?,
+            None => {
+                eprintln!(
+                    "error: script asked for '{}'; pass --answer {}=<value>",
+                    prompt.id, prompt.id
+                );
+                return None;
+            }
+        };
+        match normalize_input(&field, &input) {
+            Ok(value) => Some(value.unwrap_or_default()),
+            Err(err) => {
+                eprintln!("error: invalid answer for '{}': {}", prompt.id, err);
+                None
+            }
+        }
+    }
+}
+
+fn ask_terminal(prompt: &PromptRequest) -> Option<String> {
+    let mut label = prompt.text.clone().unwrap_or_else(|| prompt.id.clone());
+    if let Some(default) = &prompt.default {
+        label.push_str(&format!(" [{}]", default));
+    }
+    eprint!("{}: ", label);
+    let _ = io::stderr().flush();
+    let mut input = String::new();
+    io::stdin().lock().read_line(&mut input).ok()?;
+    Some(input.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn print_line(line: &OutputLine) {
+    match line {
+        OutputLine::Text(text) => println!("{}", text),
+        OutputLine::Progress { percent, message } => match message {
+            Some(message) => eprintln!("[{:>3}%] {}", percent, message),
+            None => eprintln!("[{:>3}%]", percent),
+        },
+        OutputLine::Annotation(annotation) => {
+            let label = match annotation.level {
+                AnnotationLevel::Warning => "warning",
+                AnnotationLevel::Error => "error",
+            };
+            eprintln!("{}: {}", label, annotation.message);
+        }
+        OutputLine::Group(name) => println!("== {}", name),
+        OutputLine::EndGroup | OutputLine::Prompt(_) => {}
+    }
+}
+
+fn print_stderr(output: &ScriptRunOutput) {
     if !output.stderr.trim().is_empty() {
         eprint!("{}", output.stderr);
         if !output.stderr.ends_with('\n') {
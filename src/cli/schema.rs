@@ -0,0 +1,34 @@
+use super::args::{SchemaArgs, SchemaCommand};
+use super::lint::{exit_on_errors, print_problems};
+use super::run::resolve_script_path;
+use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::domain::schema_json_schema;
+use crate::lint::check_schema_strict;
+use crate::ports::ScriptRepository;
+use std::error::Error;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf, args: SchemaArgs) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        SchemaCommand::Export => {
+            println!("{}", serde_json::to_string_pretty(&schema_json_schema())?);
+        }
+        SchemaCommand::Check(options) => {
+            let scripts = match options.script {
+                Some(script) => vec![resolve_script_path(&script, &scripts_dir)?],
+                None => {
+                    let repo = FsWorkspaceRepository::new(scripts_dir.clone());
+                    let mut scripts = repo.list_scripts_recursive()?;
+                    scripts.sort();
+                    scripts
+                }
+            };
+            let problems: Vec<_> = scripts
+                .iter()
+                .flat_map(|script| check_schema_strict(&scripts_dir, script))
+                .collect();
+            exit_on_errors(print_problems(&problems));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::cli::args::{ShimArgs, ShimMode};
+use crate::command_stub::{record_call, replay_call};
+
+/// Runs a shimmed command call and exits with its exit code.
+pub fn run(args: ShimArgs) -> ! {
+    let result = match args.mode {
+        ShimMode::Record => record_call(&args.dir, &args.command, &args.args),
+        ShimMode::Replay => replay_call(&args.dir, &args.command, &args.args),
+    };
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("omakure shim for {}: {}", args.command, err);
+            std::process::exit(127)
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use super::args::{TestArgs, TestReportFormat};
+use super::run::resolve_script_path;
+use crate::adapters::script_runner::MultiScriptRunner;
+use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::error::{AppError, SchemaError};
+use crate::ports::ScriptRepository;
+use crate::script_test::{junit_report, run_test_file, tap_report, test_file_for};
+use crate::workspace::Workspace;
+use std::error::Error;
+use std::path::PathBuf;
+
+pub fn run(scripts_dir: PathBuf, options: TestArgs) -> Result<(), Box<dyn Error>> {
+    let workspace = Workspace::new(scripts_dir);
+    let repo = FsWorkspaceRepository::new(workspace.root().to_path_buf());
+    let scripts = if options.scripts.is_empty() {
+        let mut scripts = repo.list_scripts_recursive()?;
+        scripts.retain(|script| test_file_for(script).is_file());
+        scripts.sort();
+        scripts
+    } else {
+        let mut scripts = Vec::new();
+        for script in &options.scripts {
+            let script = resolve_script_path(script, workspace.root())?;
+            if !test_file_for(&script).is_file() {
+                return Err(format!(
+                    "No test file for {}: expected {}",
+                    script.display(),
+                    test_file_for(&script).display()
+                )
+                .into());
+            }
+            scripts.push(script);
+        }
+        scripts
+    };
+
+    let runner = MultiScriptRunner::new(workspace.root());
+    let mut results = Vec::new();
+    for script in &scripts {
+        let label = script
+            .strip_prefix(workspace.root())
+            .unwrap_or(script)
+            .display()
+            .to_string();
+        // A script without a schema is tested with raw arguments; a broken schema fails its cases.
+        let schema = match repo.read_schema(script) {
+            Ok(schema) => Ok(Some(schema)),
+            Err(AppError::Schema(SchemaError::BlockNotFound)) => Ok(None),
+            Err(err) => Err(err.to_string()),
+        };
+        results.extend(run_test_file(
+            &runner,
+            schema.as_ref().map(Option::as_ref).map_err(String::as_str),
+            &label,
+            script,
+            &test_file_for(script),
+        ));
+    }
+
+    print!(
+        "{}",
+        match options.format {
+            TestReportFormat::Tap => tap_report(&results),
+            TestReportFormat::Junit => junit_report(&results),
+        }
+    );
+    if results.iter().any(|result| !result.failures.is_empty()) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
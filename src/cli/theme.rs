@@ -138,7 +138,7 @@ fn format_color(color: Color) -> String {
 }
 
 fn ensure_theme_exists(name: &str, theme_dir: &Path) -> Result<(), Box<dyn Error>> {
-    let is_builtin = builtin_theme_names().iter().any(|builtin| *builtin == name);
+    let is_builtin = builtin_theme_names().contains(&name);
     if is_builtin {
         return Ok(());
     }
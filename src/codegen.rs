@@ -0,0 +1,434 @@
+use crate::arg_check::parsed_args;
+use crate::domain::{Field, Schema};
+use crate::runtime::{schema_comment_prefixes, ScriptKind};
+
+const START_MARKER: &str = "OMAKURE_ARGS_START";
+const END_MARKER: &str = "OMAKURE_ARGS_END";
+
+/// Builds the marked argument-parsing region for a schema's fields, in `Order`.
+pub fn args_block(kind: ScriptKind, schema: &Schema) -> Result<String, String> {
+    let mut fields: Vec<&Field> = schema.fields.iter().collect();
+    fields.sort_by_key(|field| field.order);
+    let body = match kind {
+        ScriptKind::Bash => bash_parser(&fields),
+        ScriptKind::PowerShell => powershell_param(&fields)?,
+        ScriptKind::Python => python_parser(&fields),
+        ScriptKind::Lua => {
+            return Err("Lua scripts receive fields by name and need no parser".to_string());
+        }
+        ScriptKind::Executable => {
+            return Err(
+                "Executables parse their own arguments; no parser can be generated".to_string(),
+            );
+        }
+    };
+    let prefix = schema_comment_prefixes(kind)[0];
+    Ok(format!(
+        "{prefix} {START_MARKER} (generated by `omakure codegen` from the schema; edits are overwritten)\n{body}{prefix} {END_MARKER}\n"
+    ))
+}
+
+/// Replaces the script's marked region with `block`. Without one, the block goes right after
+/// the schema block, or at the top of the script when the schema lives elsewhere.
+pub fn apply_block(kind: ScriptKind, contents: &str, block: &str) -> Result<String, String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let is_marker = |line: &str, marker: &str| is_marker(kind, line, marker);
+    let joined = |before: &[&str], after: &[&str]| {
+        let mut out = String::new();
+        for line in before {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.push_str(block);
+        for line in after {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    };
+
+    if let Some(start) = lines.iter().position(|line| is_marker(line, START_MARKER)) {
+        let end = lines[start..]
+            .iter()
+            .position(|line| is_marker(line, END_MARKER))
+            .map(|offset| start + offset)
+            .ok_or_else(|| format!("`{}` without a matching `{}`", START_MARKER, END_MARKER))?;
+        return Ok(joined(&lines[..start], &lines[end + 1..]));
+    }
+
+    if let Some(end) = lines
+        .iter()
+        .position(|line| is_marker(line, "OMAKURE_SCHEMA_END"))
+    {
+        let mut before = lines[..=end].to_vec();
+        before.push("");
+        let mut after = vec![""];
+        after.extend(&lines[end + 1..]);
+        if after.get(1) == Some(&"") {
+            after.remove(0);
+        }
+        return Ok(joined(&before, &after));
+    }
+
+    match lines.first() {
+        Some(first) if first.starts_with("#!") => {
+            Ok(joined(&[first, ""], &[&[""], &lines[1..]].concat()))
+        }
+        _ => Ok(joined(&[], &[&[""], &lines[..]].concat())),
+    }
+}
+
+/// Line of the first option the script still parses outside the generated region, such as a
+/// hand-written `case "$1"` loop left below a newly inserted block.
+pub fn parser_outside_block(kind: ScriptKind, contents: &str) -> Option<usize> {
+    let mut inside = false;
+    let outside: Vec<&str> = contents
+        .lines()
+        .map(|line| {
+            if is_marker(kind, line, START_MARKER) {
+                inside = true;
+            }
+            let kept = if inside { "" } else { line };
+            if is_marker(kind, line, END_MARKER) {
+                inside = false;
+            }
+            kept
+        })
+        .collect();
+    parsed_args(kind, &outside.join("\n"))
+        .into_iter()
+        .map(|arg| arg.line)
+        .min()
+}
+
+fn is_marker(kind: ScriptKind, line: &str, marker: &str) -> bool {
+    schema_comment_prefixes(kind).iter().any(|prefix| {
+        line.trim_start()
+            .strip_prefix(prefix)
+            .map(str::trim_start)
+            .is_some_and(|rest| {
+                rest.strip_prefix(marker)
+                    .is_some_and(|tail| tail.is_empty() || tail.starts_with(' '))
+            })
+    })
+}
+
+/// `resource-group` -> `ARG_RESOURCE_GROUP`; the prefix keeps fields such as `path` or `home`
+/// from overwriting `PATH` and `HOME`.
+fn bash_variable(field: &Field) -> String {
+    format!("ARG_{}", identifier(&field.name).to_ascii_uppercase())
+}
+
+/// Field name as an identifier: `resource-group` -> `resource_group`.
+fn identifier(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .collect();
+    if out.starts_with(|ch: char| ch.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn is_bool(field: &Field) -> bool {
+    matches!(field.kind.to_lowercase().as_str(), "bool" | "boolean")
+}
+
+/// Default the parser starts from; bools are always passed as `true`/`false`.
+fn default_value(field: &Field) -> Option<String> {
+    match &field.default {
+        Some(default) => Some(default.clone()),
+        None if is_bool(field) => Some("false".to_string()),
+        None => None,
+    }
+}
+
+fn required(field: &Field) -> bool {
+    field.required.unwrap_or(false) && field.default.is_none()
+}
+
+fn bash_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// PowerShell literal; single quotes keep `$` from expanding.
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn double_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn bash_parser(fields: &[&Field]) -> String {
+    let mut out = String::new();
+    for field in fields {
+        let default = default_value(field).unwrap_or_default();
+        out.push_str(&format!(
+            "{}={}\n",
+            bash_variable(field),
+            bash_quote(&default)
+        ));
+    }
+    out.push_str("\nwhile [[ $# -gt 0 ]]; do\n  case \"$1\" in\n");
+    for field in fields {
+        if field.is_switch() {
+            out.push_str(&format!(
+                "    {})\n      {}=true\n      shift\n      ;;\n",
+                field.flag(),
+                bash_variable(field)
+            ));
+            continue;
+        }
+        // Without the check a trailing flag makes `shift 2` fail and, without `set -e`, loop.
+        out.push_str(&format!(
+            "    {})\n      [[ $# -ge 2 ]] || {{ echo \"Missing value for $1\" >&2; exit 1; }}\n      {}=\"$2\"\n      shift 2\n      ;;\n",
+            field.flag(),
+            bash_variable(field)
+        ));
+    }
+    out.push_str(
+        "    *)\n      echo \"Unknown arg: $1\" >&2\n      exit 1\n      ;;\n  esac\ndone\n",
+    );
+
+    for field in fields {
+        let variable = bash_variable(field);
+        if required(field) {
+            out.push_str(&format!(
+                "\nif [[ -z \"${variable}\" ]]; then\n  echo \"Missing required arg: {}\" >&2\n  exit 1\nfi\n",
+                field.flag()
+            ));
+        }
+        if let Some(choices) = field.choices.as_ref().filter(|choices| !choices.is_empty()) {
+            let patterns: Vec<String> = choices.iter().map(|choice| bash_quote(choice)).collect();
+            out.push_str(&format!(
+                "\ncase \"${variable}\" in\n  \"\"|{}) ;;\n  *)\n    echo \"Invalid {}: ${variable} (expected {})\" >&2\n    exit 1\n    ;;\nesac\n",
+                patterns.join("|"),
+                field.flag(),
+                choices.join(", ")
+            ));
+        }
+    }
+    out
+}
+
+/// PowerShell binds `-Name value`, so every field needs a single-dash `Arg`.
+fn powershell_param(fields: &[&Field]) -> Result<String, String> {
+    let mut params = Vec::new();
+    for field in fields {
+        let flag = field.flag();
+        let name = flag
+            .strip_prefix('-')
+            .filter(|name| {
+                !name.is_empty() && name.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Field `{}` uses `{}`; PowerShell parameters need an Arg like `-{}`",
+                    field.name,
+                    flag,
+                    identifier(&field.name)
+                )
+            })?;
+
+        let mut lines = Vec::new();
+        if field.is_switch() {
+            params.push(format!("  [switch]${}", name));
+            continue;
+        }
+        if required(field) {
+            lines.push("  [Parameter(Mandatory)]".to_string());
+        }
+        let choices = match &field.choices {
+            Some(choices) if !choices.is_empty() => Some(choices.clone()),
+            _ if is_bool(field) => Some(vec!["true".to_string(), "false".to_string()]),
+            _ => None,
+        };
+        if let Some(choices) = choices {
+            let quoted: Vec<String> = choices
+                .iter()
+                .map(|choice| powershell_quote(choice))
+                .collect();
+            lines.push(format!("  [ValidateSet({})]", quoted.join(", ")));
+        }
+        let number = field.kind.eq_ignore_ascii_case("number");
+        let mut declaration = format!("  [{}]${}", if number { "double" } else { "string" }, name);
+        if let Some(default) = default_value(field) {
+            let value = if number && default.parse::<f64>().is_ok() {
+                default
+            } else {
+                powershell_quote(&default)
+            };
+            declaration.push_str(&format!(" = {}", value));
+        }
+        lines.push(declaration);
+        params.push(lines.join("\n"));
+    }
+    if params.is_empty() {
+        return Ok("param()\n".to_string());
+    }
+    Ok(format!("param(\n{}\n)\n", params.join(",\n")))
+}
+
+fn python_parser(fields: &[&Field]) -> String {
+    let mut out = String::from("import argparse\n\nparser = argparse.ArgumentParser()\n");
+    for field in fields {
+        let flag = field.flag();
+        let mut parts = vec![double_quote(&flag)];
+        let dest = identifier(&field.name);
+        if identifier(flag.trim_start_matches('-')) != dest {
+            parts.push(format!("dest={}", double_quote(&dest)));
+        }
+        if field.is_switch() {
+            parts.push("action=\"store_true\"".to_string());
+            if let Some(prompt) = &field.prompt {
+                parts.push(format!("help={}", double_quote(prompt)));
+            }
+            out.push_str(&format!("parser.add_argument({})\n", parts.join(", ")));
+            continue;
+        }
+        if field.kind.eq_ignore_ascii_case("number") {
+            parts.push("type=float".to_string());
+        } else if is_bool(field) {
+            parts.push("type=lambda value: value == \"true\"".to_string());
+        }
+        if required(field) {
+            parts.push("required=True".to_string());
+        }
+        if let Some(choices) = field.choices.as_ref().filter(|choices| !choices.is_empty()) {
+            let quoted: Vec<String> = choices.iter().map(|choice| double_quote(choice)).collect();
+            parts.push(format!("choices=[{}]", quoted.join(", ")));
+        }
+        match default_value(field) {
+            Some(_) if is_bool(field) => {
+                let value = field.default.as_deref().is_some_and(|value| {
+                    matches!(value.to_lowercase().as_str(), "true" | "yes" | "1")
+                });
+                parts.push(format!("default={}", if value { "True" } else { "False" }));
+            }
+            Some(default)
+                if field.kind.eq_ignore_ascii_case("number") && default.parse::<f64>().is_ok() =>
+            {
+                parts.push(format!("default={}", default));
+            }
+            Some(default) => parts.push(format!("default={}", double_quote(&default))),
+            None => {}
+        }
+        if let Some(prompt) = &field.prompt {
+            parts.push(format!("help={}", double_quote(prompt)));
+        }
+        out.push_str(&format!("parser.add_argument({})\n", parts.join(", ")));
+    }
+    out.push_str("args = parser.parse_args()\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{parse_schema_as, FieldLibrary, SchemaFormat};
+
+    fn schema() -> Schema {
+        parse_schema_as(
+            r#"{"Name": "deploy", "Fields": [
+                {"Name": "env", "Type": "string", "Order": 2, "Required": true, "Choices": ["dev", "prod"], "Arg": "-Env"},
+                {"Name": "count", "Type": "number", "Order": 1, "Default": "3", "Arg": "-Count"},
+                {"Name": "dry_run", "Type": "bool", "Order": 3, "Arg": "-DryRun"}
+            ]}"#,
+            SchemaFormat::Json,
+            &FieldLibrary::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bash_block_is_idempotent() {
+        let script = "#!/usr/bin/env bash\n# OMAKURE_SCHEMA_START\n# {}\n# OMAKURE_SCHEMA_END\necho \"$ARG_ENV\"\n";
+        let block = args_block(ScriptKind::Bash, &schema()).unwrap();
+        let once = apply_block(ScriptKind::Bash, script, &block).unwrap();
+        assert!(once.starts_with(
+            "#!/usr/bin/env bash\n# OMAKURE_SCHEMA_START\n# {}\n# OMAKURE_SCHEMA_END\n\n# OMAKURE_ARGS_START"
+        ));
+        assert!(once.contains("ARG_COUNT='3'\nARG_ENV=''\nARG_DRY_RUN='false'\n"));
+        assert!(once.contains("    -Env)\n      [[ $# -ge 2 ]] || { echo \"Missing value for $1\" >&2; exit 1; }\n      ARG_ENV=\"$2\"\n      shift 2\n"));
+        assert!(once.contains("if [[ -z \"$ARG_ENV\" ]]; then"));
+        assert!(once.contains("  \"\"|'dev'|'prod') ;;"));
+        assert!(once.ends_with("# OMAKURE_ARGS_END\n\necho \"$ARG_ENV\"\n"));
+        assert_eq!(apply_block(ScriptKind::Bash, &once, &block).unwrap(), once);
+        assert_eq!(parser_outside_block(ScriptKind::Bash, &once), None);
+
+        let handwritten = format!("{}case \"$1\" in\n  -Env) shift 2 ;;\nesac\n", script);
+        let updated = apply_block(ScriptKind::Bash, &handwritten, &block).unwrap();
+        let line = parser_outside_block(ScriptKind::Bash, &updated).unwrap();
+        assert_eq!(updated.lines().nth(line - 1), Some("  -Env) shift 2 ;;"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_parser_rejects_a_trailing_flag_without_value() {
+        use std::process::Command;
+        use std::time::{Duration, Instant};
+
+        // No `set -e`: the parser itself has to stop.
+        let script = "#!/usr/bin/env bash\n# OMAKURE_SCHEMA_START\n# {}\n# OMAKURE_SCHEMA_END\necho \"env=$ARG_ENV\"\n";
+        let block = args_block(ScriptKind::Bash, &schema()).unwrap();
+        let generated = apply_block(ScriptKind::Bash, script, &block).unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "omakure-codegen-trailing-{}.sh",
+            std::process::id()
+        ));
+        std::fs::write(&path, generated).unwrap();
+
+        let run = |args: &[&str]| {
+            let mut child = Command::new("bash")
+                .arg(&path)
+                .args(args)
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::piped())
+                .spawn()
+                .unwrap();
+            let started = Instant::now();
+            while child.try_wait().unwrap().is_none() {
+                if started.elapsed() > Duration::from_secs(5) {
+                    let _ = child.kill();
+                    panic!("generated parser did not stop for {:?}", args);
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            child.wait_with_output().unwrap()
+        };
+        let output = run(&["-Env"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8_lossy(&output.stderr),
+            "Missing value for -Env\n"
+        );
+        let output = run(&["-Env", "dev"]);
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "env=dev\n");
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_powershell_and_python_blocks() {
+        let block = args_block(ScriptKind::PowerShell, &schema()).unwrap();
+        assert!(block.contains(
+            "param(\n  [double]$Count = 3,\n  [Parameter(Mandatory)]\n  [ValidateSet('dev', 'prod')]\n  [string]$Env,\n  [ValidateSet('true', 'false')]\n  [string]$DryRun = 'false'\n)\n"
+        ));
+
+        let block = args_block(ScriptKind::Python, &schema()).unwrap();
+        assert!(block.contains(
+            "parser.add_argument(\"-Env\", dest=\"env\", required=True, choices=[\"dev\", \"prod\"])\n"
+        ));
+        assert!(block
+            .contains("parser.add_argument(\"-Count\", dest=\"count\", type=float, default=3)\n"));
+
+        let mut unbound = schema();
+        unbound.fields[0].arg = None;
+        assert_eq!(
+            args_block(ScriptKind::PowerShell, &unbound).unwrap_err(),
+            "Field `env` uses `--env`; PowerShell parameters need an Arg like `-env`"
+        );
+    }
+}
@@ -0,0 +1,513 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::adapters::system_checks::find_in_path;
+use crate::domain::Requirement;
+use crate::error::{AppResult, ScriptError};
+use crate::ports::{RunObserver, ScriptRunOutput, ScriptRunner};
+use crate::util::set_executable_permissions;
+
+/// Hidden subcommand recording and replaying shims call back into.
+pub const SHIM_SUBCOMMAND: &str = "__shim";
+/// Calls a replaying shim serves, in recorded order.
+const REPLAY_FILE: &str = "replay.json";
+/// One marker file per replay call already served, named after its index. Markers are made
+/// with `create_new`, so parallel calls to the same tool each claim a different call.
+const REPLAY_USED_DIR: &str = "replay-used";
+/// One file per call made through a recording shim.
+const CALLS_DIR: &str = "calls";
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Canned output an external command answers with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StubResponse {
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+    #[serde(default)]
+    pub exit_code: i32,
+}
+
+/// An external command call captured by a recording shim.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordedCall {
+    pub command: String,
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub stdin: String,
+    #[serde(flatten)]
+    pub response: StubResponse,
+}
+
+/// The external calls of one run, with what is needed to run it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Script path relative to the workspace.
+    pub script: PathBuf,
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin: Option<String>,
+    pub calls: Vec<RecordedCall>,
+}
+
+impl Cassette {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        serde_json::from_slice(&data).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, data)
+    }
+
+    /// Distinct commands called, in first-call order.
+    pub fn commands(&self) -> Vec<String> {
+        let mut commands: Vec<String> = Vec::new();
+        for call in &self.calls {
+            if !commands.contains(&call.command) {
+                commands.push(call.command.clone());
+            }
+        }
+        commands
+    }
+}
+
+/// Temporary directory of executables that shadow real commands once it is put first on `PATH`.
+/// Removed when dropped.
+pub struct ShimDir {
+    dir: PathBuf,
+}
+
+impl ShimDir {
+    pub fn create() -> io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "omakure-shims-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Adds a shim for `command` that prints `response` and exits with its code.
+    pub fn add_stub(&self, command: &str, response: &StubResponse) -> io::Result<()> {
+        let command = shim_name(command)?;
+        let stdout = self.dir.join(format!("{}.stdout", command));
+        let stderr = self.dir.join(format!("{}.stderr", command));
+        fs::write(&stdout, &response.stdout)?;
+        fs::write(&stderr, &response.stderr)?;
+        let script = if cfg!(windows) {
+            format!(
+                "@echo off\r\ntype \"{}\"\r\ntype \"{}\" 1>&2\r\nexit /b {}\r\n",
+                stdout.display(),
+                stderr.display(),
+                response.exit_code
+            )
+        } else {
+            format!(
+                "#!/bin/sh\ncat {}\ncat {} >&2\nexit {}\n",
+                sh_quote(&stdout),
+                sh_quote(&stderr),
+                response.exit_code
+            )
+        };
+        self.write_shim(command, &script)
+    }
+
+    /// Adds a shim that runs the real `command` (as found on the current `PATH`) and records
+    /// the call. The command shares the script's stdin unless `reads_stdin` is set; then its
+    /// input is copied into the recording, and whatever it doesn't read is lost to the script.
+    pub fn add_recorder(&self, command: &str, reads_stdin: bool) -> io::Result<()> {
+        let command = shim_name(command)?;
+        let real = find_in_path(command).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Command to record not found in PATH: {}", command),
+            )
+        })?;
+        fs::write(
+            self.dir.join(format!("{}.real", command)),
+            real.to_string_lossy().as_bytes(),
+        )?;
+        if reads_stdin {
+            fs::write(self.dir.join(format!("{}.stdin", command)), "")?;
+        }
+        self.write_callback_shim(command, "record")
+    }
+
+    /// Adds a shim that answers with the next recorded call with the same arguments.
+    pub fn add_replayer(&self, command: &str, calls: &[RecordedCall]) -> io::Result<()> {
+        let command = shim_name(command)?;
+        let path = self.dir.join(REPLAY_FILE);
+        let mut all: Vec<RecordedCall> = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        all.extend(calls.iter().filter(|call| call.command == command).cloned());
+        fs::write(&path, serde_json::to_vec(&all).map_err(io::Error::other)?)?;
+        self.write_callback_shim(command, "replay")
+    }
+
+    /// Calls made through recording shims since the last take, in the order they started.
+    pub fn take_recorded_calls(&self) -> Vec<RecordedCall> {
+        let mut files: Vec<PathBuf> = fs::read_dir(self.dir.join(CALLS_DIR))
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        files.sort();
+        files
+            .iter()
+            .filter_map(|path| {
+                let call = serde_json::from_slice(&fs::read(path).ok()?).ok();
+                let _ = fs::remove_file(path);
+                call
+            })
+            .collect()
+    }
+
+    /// Shim that hands the call to `omakure __shim <mode> <dir> <command> <args>...`.
+    fn write_callback_shim(&self, command: &str, mode: &str) -> io::Result<()> {
+        let exe = std::env::current_exe()?;
+        let script = if cfg!(windows) {
+            format!(
+                "@echo off\r\n\"{}\" {} {} \"{}\" {} %*\r\nexit /b %ERRORLEVEL%\r\n",
+                exe.display(),
+                SHIM_SUBCOMMAND,
+                mode,
+                self.dir.display(),
+                command
+            )
+        } else {
+            format!(
+                "#!/bin/sh\nexec {} {} {} {} {} \"$@\"\n",
+                sh_quote(&exe),
+                SHIM_SUBCOMMAND,
+                mode,
+                sh_quote(&self.dir),
+                command
+            )
+        };
+        self.write_shim(command, &script)
+    }
+
+    fn write_shim(&self, command: &str, script: &str) -> io::Result<()> {
+        let path = if cfg!(windows) {
+            self.dir.join(format!("{}.cmd", command))
+        } else {
+            self.dir.join(command)
+        };
+        fs::write(&path, script)?;
+        set_executable_permissions(&path).map_err(|err| io::Error::other(err.to_string()))
+    }
+
+    /// `PATH` with the shims in front of the inherited entries.
+    pub fn path_env(&self) -> (String, String) {
+        let mut paths = vec![self.dir.clone()];
+        paths.extend(std::env::split_paths(
+            &std::env::var_os("PATH").unwrap_or_default(),
+        ));
+        let joined = std::env::join_paths(paths).unwrap_or_else(|_| OsString::from(&self.dir));
+        ("PATH".to_string(), joined.to_string_lossy().to_string())
+    }
+}
+
+impl Drop for ShimDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Runs the real command behind a recording shim, passing its streams through, and stores the
+/// call in the shim directory. Returns the command's exit code.
+pub fn record_call(dir: &Path, command: &str, args: &[String]) -> io::Result<i32> {
+    let real = fs::read_to_string(dir.join(format!("{}.real", command)))?;
+    let reads_stdin = dir.join(format!("{}.stdin", command)).exists();
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let mut child = Command::new(real.trim())
+        .args(args)
+        .stdin(if reads_stdin {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Stdin is copied as the tool reads it, which drains it for the script; a script's stdin
+    // that never closes only leaves this thread behind when the process exits.
+    let stdin = Arc::new(Mutex::new(Vec::new()));
+    if let Some(mut pipe) = child.stdin.take() {
+        let captured = Arc::clone(&stdin);
+        thread::spawn(move || {
+            let mut buffer = [0u8; 8192];
+            let mut input = io::stdin().lock();
+            while let Ok(read) = input.read(&mut buffer) {
+                if read == 0 {
+                    break;
+                }
+                captured
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .extend_from_slice(&buffer[..read]);
+                if pipe.write_all(&buffer[..read]).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    let stdout = child.stdout.take().map(|pipe| tee(pipe, io::stdout()));
+    let stderr = child.stderr.take().map(|pipe| tee(pipe, io::stderr()));
+    let status = child.wait()?;
+    let collect = |handle: Option<thread::JoinHandle<Vec<u8>>>| {
+        let bytes = handle
+            .and_then(|handle| handle.join().ok())
+            .unwrap_or_default();
+        String::from_utf8_lossy(&bytes).to_string()
+    };
+    let exit_code = status.code().unwrap_or(1);
+    let call = RecordedCall {
+        command: command.to_string(),
+        args: args.to_vec(),
+        stdin: String::from_utf8_lossy(&stdin.lock().unwrap_or_else(|err| err.into_inner()))
+            .to_string(),
+        response: StubResponse {
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+            exit_code,
+        },
+    };
+
+    let calls = dir.join(CALLS_DIR);
+    fs::create_dir_all(&calls)?;
+    let file = calls.join(format!("{:032}-{}.json", started, std::process::id()));
+    fs::write(file, serde_json::to_vec(&call).map_err(io::Error::other)?)?;
+    Ok(exit_code)
+}
+
+/// Copies a child's output stream to ours while keeping a copy.
+fn tee<R, W>(mut source: R, mut sink: W) -> thread::JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut buffer = [0u8; 8192];
+        while let Ok(read) = source.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let _ = sink.write_all(&buffer[..read]);
+            let _ = sink.flush();
+            kept.extend_from_slice(&buffer[..read]);
+        }
+        kept
+    })
+}
+
+/// Serves the first unused recorded call of `command` with the same arguments. Calls that were
+/// never recorded fail with exit code 127.
+pub fn replay_call(dir: &Path, command: &str, args: &[String]) -> io::Result<i32> {
+    let calls: Vec<RecordedCall> =
+        serde_json::from_slice(&fs::read(dir.join(REPLAY_FILE))?).map_err(io::Error::other)?;
+    let used_dir = dir.join(REPLAY_USED_DIR);
+    fs::create_dir_all(&used_dir)?;
+    let mut claimed = None;
+    for index in (0..calls.len())
+        .filter(|index| calls[*index].command == command && calls[*index].args == args)
+    {
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(used_dir.join(index.to_string()))
+        {
+            Ok(_) => {
+                claimed = Some(index);
+                break;
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+    let Some(index) = claimed else {
+        eprintln!(
+            "omakure replay: no recorded call left for `{} {}`",
+            command,
+            args.join(" ")
+        );
+        return Ok(127);
+    };
+
+    let response = &calls[index].response;
+    io::stdout().write_all(response.stdout.as_bytes())?;
+    io::stderr().write_all(response.stderr.as_bytes())?;
+    Ok(response.exit_code)
+}
+
+/// Runs scripts with a shim directory first on `PATH`. Requirements on shimmed commands are
+/// skipped: a replayed tool doesn't have to be installed.
+pub struct ShimmedRunner {
+    inner: Box<dyn ScriptRunner>,
+    path: (String, String),
+    commands: Vec<String>,
+}
+
+impl ShimmedRunner {
+    pub fn new(inner: Box<dyn ScriptRunner>, shims: &ShimDir, commands: Vec<String>) -> Self {
+        Self {
+            inner,
+            path: shims.path_env(),
+            commands,
+        }
+    }
+}
+
+impl ScriptRunner for ShimmedRunner {
+    fn run(
+        &self,
+        script: &Path,
+        args: &[String],
+        stdin: Option<&str>,
+        prompts: bool,
+        env: &[(String, String)],
+        observer: &mut dyn RunObserver,
+    ) -> AppResult<ScriptRunOutput> {
+        let mut env = env.to_vec();
+        env.push(self.path.clone());
+        self.inner.run(script, args, stdin, prompts, &env, observer)
+    }
+
+    fn check_requirements(&self, requires: &[Requirement]) -> Vec<ScriptError> {
+        let unshimmed: Vec<Requirement> = requires
+            .iter()
+            .filter(|requirement| !self.commands.contains(&requirement.command))
+            .cloned()
+            .collect();
+        self.inner.check_requirements(&unshimmed)
+    }
+}
+
+/// Stubbed commands are bare names (`az`), never paths.
+fn shim_name(command: &str) -> io::Result<&str> {
+    let valid = !command.is_empty()
+        && command
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    if valid {
+        Ok(command)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid command name to stub: `{}`", command),
+        ))
+    }
+}
+
+fn sh_quote(path: &Path) -> String {
+    format!("'{}'", path.display().to_string().replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(args: &[&str], exit_code: i32) -> RecordedCall {
+        RecordedCall {
+            command: "greet".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            stdin: String::new(),
+            response: StubResponse {
+                exit_code,
+                ..StubResponse::default()
+            },
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stub_answers_with_its_response() {
+        let shims = ShimDir::create().unwrap();
+        let response = StubResponse {
+            stdout: "hello\n".to_string(),
+            stderr: "careful\n".to_string(),
+            exit_code: 3,
+        };
+        shims.add_stub("greet", &response).unwrap();
+        assert!(shims.add_stub("../greet", &response).is_err());
+
+        let (key, path) = shims.path_env();
+        let output = Command::new("sh")
+            .args(["-c", "greet --any args"])
+            .env(key, path)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "careful\n");
+        assert_eq!(output.status.code(), Some(3));
+    }
+
+    #[test]
+    fn test_replay_serves_each_recorded_call_once_in_order() {
+        let shims = ShimDir::create().unwrap();
+        let mut other = call(&["a"], 9);
+        other.command = "other".to_string();
+        let calls = vec![call(&["a"], 0), other, call(&["b"], 4), call(&["a"], 2)];
+        shims.add_replayer("greet", &calls).unwrap();
+
+        let replay = |args: &[&str]| {
+            let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+            replay_call(&shims.dir, "greet", &args).unwrap()
+        };
+        assert_eq!(replay(&["a"]), 0);
+        assert_eq!(replay(&["a"]), 2);
+        assert_eq!(replay(&["a"]), 127);
+        assert_eq!(replay(&["b"]), 4);
+        assert_eq!(replay(&["c"]), 127);
+
+        let mut used: Vec<String> = fs::read_dir(shims.dir.join(REPLAY_USED_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        used.sort();
+        assert_eq!(used, vec!["0", "1", "2"]);
+    }
+
+    #[test]
+    fn test_parallel_replays_claim_different_calls() {
+        let shims = ShimDir::create().unwrap();
+        let calls: Vec<RecordedCall> = (0..8).map(|code| call(&["list"], code)).collect();
+        shims.add_replayer("greet", &calls).unwrap();
+
+        let dir = shims.dir.clone();
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let dir = dir.clone();
+                thread::spawn(move || replay_call(&dir, "greet", &["list".to_string()]).unwrap())
+            })
+            .collect();
+        let mut codes: Vec<i32> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        codes.sort();
+        assert_eq!(codes, vec![0, 1, 2, 3, 4, 5, 6, 7, 127, 127]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::error::SchemaError;
+
+/// Key a schema field uses to pull in a shared definition.
+const REF_KEY: &str = "$ref";
+
+/// Shared field definitions that schema fields reuse with
+/// `{"$ref": "azure/subscription_id", "Order": 1}`.
+#[derive(Debug, Clone, Default)]
+pub struct FieldLibrary {
+    fields: BTreeMap<String, Value>,
+}
+
+impl FieldLibrary {
+    /// Adds every entry of a library document (`{"subscription_id": {...}, ...}`) as
+    /// `namespace/<key>`. Entries added later replace earlier ones with the same reference.
+    pub fn add_document(&mut self, namespace: &str, document: Value) -> Result<(), String> {
+        let Value::Object(entries) = document else {
+            return Err("Field library must map field keys to field definitions".to_string());
+        };
+        for (key, field) in entries {
+            if !field.is_object() {
+                return Err(format!("Field `{}` must be an object", key));
+            }
+            self.fields.insert(format!("{}/{}", namespace, key), field);
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, reference: &str) -> Option<&Value> {
+        self.fields.get(reference)
+    }
+
+    /// Replaces every `$ref` entry in the schema's `Fields` with the referenced definition,
+    /// overlaid with the entry's own keys. `Name` defaults to the last segment of the reference.
+    pub fn resolve(&self, schema: &mut Value) -> Result<(), SchemaError> {
+        let Some(fields) = schema.get_mut("Fields").and_then(Value::as_array_mut) else {
+            return Ok(());
+        };
+        for field in fields {
+            let Some(entry) = field.as_object_mut() else {
+                continue;
+            };
+            let Some(reference) = entry.remove(REF_KEY) else {
+                continue;
+            };
+            let reference = reference.as_str().unwrap_or_default().to_string();
+            let Some(Value::Object(shared)) = self.get(&reference) else {
+                return Err(SchemaError::UnknownFieldRef { reference });
+            };
+            let mut merged = shared.clone();
+            merged.remove(REF_KEY);
+            if !merged.contains_key("Name") {
+                let name = reference.rsplit('/').next().unwrap_or(&reference);
+                merged.insert("Name".to_string(), Value::String(name.to_string()));
+            }
+            merged.extend(std::mem::take(entry));
+            *entry = merged;
+        }
+        Ok(())
+    }
+}
+
+/// Whether any schema field uses `$ref`.
+pub fn has_field_refs(schema: &Value) -> bool {
+    schema
+        .get("Fields")
+        .and_then(Value::as_array)
+        .is_some_and(|fields| fields.iter().any(|field| field.get(REF_KEY).is_some()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolve_merges_overrides() {
+        let mut library = FieldLibrary::default();
+        library
+            .add_document(
+                "azure",
+                json!({
+                    "subscription_id": {
+                        "Prompt": "Subscription",
+                        "Type": "string",
+                        "Order": 9,
+                        "Choices": ["dev", "prod"]
+                    }
+                }),
+            )
+            .unwrap();
+        let mut schema = json!({
+            "Name": "rg",
+            "Fields": [
+                {"$ref": "azure/subscription_id", "Order": 1, "Required": true},
+                {"Name": "local", "Type": "string", "Order": 2}
+            ]
+        });
+        library.resolve(&mut schema).unwrap();
+        assert_eq!(
+            schema["Fields"][0],
+            json!({
+                "Name": "subscription_id",
+                "Prompt": "Subscription",
+                "Type": "string",
+                "Order": 1,
+                "Required": true,
+                "Choices": ["dev", "prod"]
+            })
+        );
+        assert_eq!(schema["Fields"][1]["Name"], "local");
+
+        let mut broken = json!({"Fields": [{"$ref": "azure/region"}]});
+        let err = library.resolve(&mut broken).unwrap_err();
+        assert_eq!(err.to_string(), "Unknown field reference `azure/region`");
+        assert!(library.add_document("x", json!(["a"])).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use super::schema::{Field, Schema};
+use super::validation::normalize_input;
+
+/// How serious a lint finding is; errors make `omakure lint` exit non-zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// A problem in a schema that parsed fine but won't behave as its author expects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    pub severity: Severity,
+    pub message: String,
+    /// Quoted name to point at, and which occurrence of it in the source (0 = first).
+    pub anchor: Option<(String, usize)>,
+}
+
+impl SchemaIssue {
+    fn new(severity: Severity, message: String, name: &str, occurrence: usize) -> Self {
+        Self {
+            severity,
+            message,
+            anchor: Some((format!("\"{}\"", name), occurrence)),
+        }
+    }
+}
+
+/// Checks a single schema for duplicate names, flags and orders, bad defaults, and queue
+/// entries that reference fields the schema doesn't declare.
+pub fn schema_issues(schema: &Schema) -> Vec<SchemaIssue> {
+    let mut issues = Vec::new();
+    let mut names: HashMap<String, usize> = HashMap::new();
+    let mut orders: HashMap<u32, &str> = HashMap::new();
+    let mut flags: HashMap<String, &str> = HashMap::new();
+
+    for field in &schema.fields {
+        let seen = names.entry(field.name.to_lowercase()).or_insert(0);
+        if *seen > 0 {
+            issues.push(SchemaIssue::new(
+                Severity::Error,
+                format!("Duplicate field name `{}`", field.name),
+                &field.name,
+                *seen,
+            ));
+        }
+        *seen += 1;
+
+        if let Some(other) = orders.insert(field.order, &field.name) {
+            issues.push(SchemaIssue::new(
+                Severity::Warning,
+                format!(
+                    "Field `{}` reuses Order {} (also used by `{}`)",
+                    field.name, field.order, other
+                ),
+                &field.name,
+                0,
+            ));
+        }
+
+        let flag = field.flag();
+        if let Some(other) = flags.insert(flag.clone(), &field.name) {
+            if !other.eq_ignore_ascii_case(&field.name) {
+                issues.push(SchemaIssue::new(
+                    Severity::Error,
+                    format!(
+                        "Fields `{}` and `{}` both use the flag `{}`",
+                        other, field.name, flag
+                    ),
+                    &field.name,
+                    0,
+                ));
+            }
+        }
+
+        if field.is_switch() && !matches!(field.kind.to_lowercase().as_str(), "bool" | "boolean") {
+            issues.push(SchemaIssue::new(
+                Severity::Warning,
+                format!(
+                    "Field `{}` is a Switch but its Type is `{}`; switches need `bool`",
+                    field.name, field.kind
+                ),
+                &field.name,
+                0,
+            ));
+        }
+        issues.extend(default_issues(field));
+    }
+
+    if schema.stdin.is_some() && schema.prompts == Some(true) {
+        issues.push(SchemaIssue::new(
+            Severity::Warning,
+            "`Prompts` can't be answered in runs that pipe the `Stdin` payload; such runs stop at the first prompt".to_string(),
+            "Prompts",
+            0,
+        ));
+    }
+
+    if let Some(queue) = &schema.queue {
+        let known = |name: &str| {
+            schema
+                .fields
+                .iter()
+                .any(|field| field.name.eq_ignore_ascii_case(name))
+        };
+        for value in queue.matrix.iter().flat_map(|matrix| &matrix.values) {
+            if !known(&value.name) {
+                issues.push(SchemaIssue::new(
+                    Severity::Error,
+                    format!("Queue matrix references unknown field `{}`", value.name),
+                    &value.name,
+                    0,
+                ));
+            }
+        }
+        for (index, case) in queue.cases.iter().flatten().enumerate() {
+            let label = case
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("#{}", index + 1));
+            for value in &case.values {
+                if !known(&value.name) {
+                    issues.push(SchemaIssue::new(
+                        Severity::Error,
+                        format!(
+                            "Queue case `{}` references unknown field `{}`",
+                            label, value.name
+                        ),
+                        &value.name,
+                        0,
+                    ));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+fn default_issues(field: &Field) -> Vec<SchemaIssue> {
+    let Some(default) = &field.default else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    if let Some(choices) = &field.choices {
+        if !choices.contains(default) {
+            issues.push(SchemaIssue::new(
+                Severity::Warning,
+                format!(
+                    "Default `{}` of field `{}` is not one of its Choices ({})",
+                    default,
+                    field.name,
+                    choices.join(", ")
+                ),
+                &field.name,
+                0,
+            ));
+        }
+    }
+    let untyped_choices = Field {
+        choices: None,
+        ..field.clone()
+    };
+    if normalize_input(&untyped_choices, default).is_err() {
+        issues.push(SchemaIssue::new(
+            Severity::Warning,
+            format!(
+                "Default `{}` of field `{}` is not a valid {}",
+                default, field.name, field.kind
+            ),
+            &field.name,
+            0,
+        ));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parsing::parse_schema;
+
+    fn messages(json: &str) -> Vec<String> {
+        schema_issues(&parse_schema(json).unwrap())
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_schema_has_no_issues() {
+        let json = r#"{"Name":"ok","Fields":[
+            {"Name":"a","Type":"number","Order":1,"Default":"3"},
+            {"Name":"b","Type":"string","Order":2,"Choices":["x","y"],"Default":"y"}
+        ],"Queue":{"Matrix":{"Values":[{"Name":"a","Values":["1"]}]}}}"#;
+        assert!(messages(json).is_empty());
+    }
+
+    #[test]
+    fn test_reports_field_issues() {
+        let json = r#"{"Name":"bad","Fields":[
+            {"Name":"target","Type":"string","Order":1,"Arg":"-t"},
+            {"Name":"Target","Type":"string","Order":2},
+            {"Name":"tag","Type":"string","Order":2,"Arg":"-t","Choices":["a"],"Default":"b"},
+            {"Name":"count","Type":"number","Order":3,"Default":"many"}
+        ],"Queue":{"Cases":[{"Name":"one","Values":[{"Name":"nope","Value":"1"}]}]}}"#;
+        let issues = schema_issues(&parse_schema(json).unwrap());
+        let messages: Vec<&str> = issues.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Duplicate field name `Target`",
+                "Field `tag` reuses Order 2 (also used by `Target`)",
+                "Fields `target` and `tag` both use the flag `-t`",
+                "Default `b` of field `tag` is not one of its Choices (a)",
+                "Default `many` of field `count` is not a valid number",
+                "Queue case `one` references unknown field `nope`",
+            ]
+        );
+        assert_eq!(issues[0].anchor, Some(("\"Target\"".to_string(), 1)));
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_warns_about_prompts_with_stdin() {
+        let json = r#"{"Name":"ask","Fields":[{"Name":"ids","Type":"string","Order":1}],
+            "Stdin":{"Field":"ids"},"Prompts":true}"#;
+        assert_eq!(
+            messages(json),
+            vec!["`Prompts` can't be answered in runs that pipe the `Stdin` payload; such runs stop at the first prompt"]
+        );
+    }
+}
@@ -1,9 +1,19 @@
 //! Domain layer - core types and validation logic.
 
+mod field_library;
+mod lint;
 mod parsing;
+mod protocol;
+mod requirements;
 mod schema;
+mod schema_spec;
 mod validation;
 
-pub use parsing::{extract_schema_block, parse_schema};
-pub use schema::{Field, Schema};
+pub use field_library::{has_field_refs, FieldLibrary};
+pub use lint::{schema_issues, SchemaIssue, Severity};
+pub use parsing::{locate_schema_block, parse_document, parse_schema_as, SchemaFormat};
+pub use protocol::{parse_output_line, Annotation, AnnotationLevel, OutputLine, PromptRequest};
+pub use requirements::{extract_version, version_at_least};
+pub use schema::{is_sensitive_key, Field, Requirement, Schema, StdinSource, StdinSpec};
+pub use schema_spec::{schema_json_schema, spec_violations};
 pub use validation::normalize_input;
@@ -1,7 +1,9 @@
 use serde::Deserialize;
+use serde_json::Value;
 
 use crate::error::SchemaError;
 
+use super::field_library::{has_field_refs, FieldLibrary};
 use super::schema::Schema;
 
 /// Parses a schema JSON object from a string.
@@ -17,31 +19,125 @@ pub fn parse_schema(output: &str) -> Result<Schema, SchemaError> {
     Err(SchemaError::JsonNotFound)
 }
 
-/// Extracts the schema block from a script file.
-pub fn extract_schema_block(contents: &str, prefixes: &[&str]) -> Result<String, SchemaError> {
-    let mut in_block = false;
+/// Syntax a schema is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl SchemaFormat {
+    /// Format named by a block marker (`OMAKURE_SCHEMA_START toml`) or a file extension.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a schema written in `format`, expanding `$ref` fields from `library`. JSON may be
+/// surrounded by other text, as in [`parse_schema`]; TOML and YAML must be the whole document.
+pub fn parse_schema_as(
+    text: &str,
+    format: SchemaFormat,
+    library: &FieldLibrary,
+) -> Result<Schema, SchemaError> {
+    if format == SchemaFormat::Json {
+        for (start, _) in text.match_indices('{') {
+            let mut deserializer = serde_json::Deserializer::from_str(&text[start..]);
+            let Ok(mut value) = Value::deserialize(&mut deserializer) else {
+                continue;
+            };
+            if has_field_refs(&value) {
+                library.resolve(&mut value)?;
+                return serde_json::from_value(value)
+                    .map_err(|err| SchemaError::Invalid(err.to_string()));
+            }
+        }
+        return parse_schema(text);
+    }
+
+    let mut value = parse_document(text, format)?;
+    if !has_field_refs(&value) {
+        // Deserializing straight from the text keeps the format's own error messages.
+        return match format {
+            SchemaFormat::Toml => Ok(toml::from_str(text)?),
+            _ => Ok(serde_yaml::from_str(text)?),
+        };
+    }
+    library.resolve(&mut value)?;
+    serde_json::from_value(value).map_err(|err| SchemaError::Invalid(err.to_string()))
+}
+
+/// Reads a JSON, TOML or YAML document into a JSON value.
+pub fn parse_document(text: &str, format: SchemaFormat) -> Result<Value, SchemaError> {
+    match format {
+        SchemaFormat::Json => Ok(serde_json::from_str(text)?),
+        SchemaFormat::Toml => Ok(toml::from_str(text)?),
+        SchemaFormat::Yaml => Ok(serde_yaml::from_str(text)?),
+    }
+}
+
+/// Schema block text, with the file line (1-based) each block line came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaBlock {
+    pub text: String,
+    pub format: SchemaFormat,
+    /// Line of the `OMAKURE_SCHEMA_START` marker.
+    pub start_line: usize,
+    pub lines: Vec<usize>,
+}
+
+/// Extracts the schema block from a script file, keeping track of where each line sits in the
+/// file and which format the start marker names.
+pub fn locate_schema_block(contents: &str, prefixes: &[&str]) -> Result<SchemaBlock, SchemaError> {
+    let mut start_line = None;
+    let mut format = SchemaFormat::Json;
     let mut buffer = String::new();
+    let mut lines = Vec::new();
 
     for (index, line) in contents.lines().enumerate() {
         if let Some(commented) = strip_comment_prefix(line, prefixes) {
             let trimmed = commented.trim();
-            if !in_block && trimmed == "OMAKURE_SCHEMA_START" {
-                in_block = true;
-                continue;
-            }
-            if in_block && trimmed == "OMAKURE_SCHEMA_END" {
-                if buffer.trim().is_empty() {
-                    return Err(SchemaError::EmptyBlock);
+            if start_line.is_none() {
+                let marker = trimmed
+                    .strip_prefix("OMAKURE_SCHEMA_START")
+                    .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace));
+                if let Some(name) = marker.map(str::trim) {
+                    if !name.is_empty() {
+                        format = SchemaFormat::from_name(name).ok_or_else(|| {
+                            SchemaError::UnknownFormat {
+                                name: name.to_string(),
+                            }
+                        })?;
+                    }
+                    start_line = Some(index + 1);
+                    continue;
                 }
-                return Ok(buffer);
             }
-            if in_block {
+            if let Some(start_line) = start_line {
+                if trimmed == "OMAKURE_SCHEMA_END" {
+                    if buffer.trim().is_empty() {
+                        return Err(SchemaError::EmptyBlock);
+                    }
+                    return Ok(SchemaBlock {
+                        text: buffer,
+                        format,
+                        start_line,
+                        lines,
+                    });
+                }
                 if !buffer.is_empty() {
                     buffer.push('\n');
                 }
                 buffer.push_str(commented);
+                lines.push(index + 1);
             }
-        } else if in_block {
+        } else if start_line.is_some() {
             if line.trim().is_empty() {
                 continue;
             }
@@ -55,12 +151,8 @@ pub fn extract_schema_block(contents: &str, prefixes: &[&str]) -> Result<String,
 fn strip_comment_prefix<'a>(line: &'a str, prefixes: &[&str]) -> Option<&'a str> {
     let trimmed = line.trim_start();
     for prefix in prefixes {
-        if trimmed.starts_with(prefix) {
-            let mut remainder = &trimmed[prefix.len()..];
-            if remainder.starts_with(' ') {
-                remainder = &remainder[1..];
-            }
-            return Some(remainder);
+        if let Some(remainder) = trimmed.strip_prefix(prefix) {
+            return Some(remainder.strip_prefix(' ').unwrap_or(remainder));
         }
     }
     None
@@ -70,6 +162,10 @@ fn strip_comment_prefix<'a>(line: &'a str, prefixes: &[&str]) -> Option<&'a str>
 mod tests {
     use super::*;
 
+    fn extract_schema_block(contents: &str, prefixes: &[&str]) -> Result<String, SchemaError> {
+        locate_schema_block(contents, prefixes).map(|block| block.text)
+    }
+
     fn make_schema_json() -> String {
         r#"{
   "Name": "test_script",
@@ -175,4 +271,53 @@ Some output after"#;
         let result = extract_schema_block(contents, &["#"]);
         assert!(matches!(result.unwrap_err(), SchemaError::BlockNotFound));
     }
+
+    #[test]
+    fn test_locate_schema_block_tracks_lines() {
+        let contents = "#!/bin/bash\n# OMAKURE_SCHEMA_START\n# {\n\n#   \"Name\": \"x\"\n# }\n# OMAKURE_SCHEMA_END";
+        let block = locate_schema_block(contents, &["#"]).unwrap();
+        assert_eq!(block.start_line, 2);
+        assert_eq!(block.lines, vec![3, 5, 6]);
+        assert_eq!(block.text.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_toml_schema_block() {
+        let contents = r#"#!/bin/bash
+# OMAKURE_SCHEMA_START toml
+# Name = "deploy"
+# Tags = ["azure"]
+#
+# [[Fields]]
+# Name = "target"
+# Type = "string"
+# Order = 1
+# Choices = ["dev", "prod"]
+# OMAKURE_SCHEMA_END
+"#;
+        let block = locate_schema_block(contents, &["#"]).unwrap();
+        assert_eq!(block.format, SchemaFormat::Toml);
+        let schema = parse_schema_as(&block.text, block.format, &FieldLibrary::default()).unwrap();
+        assert_eq!(schema.name, "deploy");
+        assert_eq!(schema.fields[0].name, "target");
+        assert_eq!(
+            schema.fields[0].choices,
+            Some(vec!["dev".to_string(), "prod".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_yaml_schema_and_unknown_format() {
+        let yaml = "Name: deploy\nFields:\n  - Name: target\n    Type: string\n    Order: 1\n";
+        let schema = parse_schema_as(yaml, SchemaFormat::Yaml, &FieldLibrary::default()).unwrap();
+        assert_eq!(schema.fields[0].name, "target");
+
+        let contents = "# OMAKURE_SCHEMA_START ini\n# Name=x\n# OMAKURE_SCHEMA_END";
+        let err = locate_schema_block(contents, &["#"]).unwrap_err();
+        assert!(matches!(err, SchemaError::UnknownFormat { name } if name == "ini"));
+        assert!(matches!(
+            parse_schema_as("Name = ", SchemaFormat::Toml, &FieldLibrary::default()),
+            Err(SchemaError::InvalidToml(_))
+        ));
+    }
 }
@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use super::schema::Field;
+
+/// Severity of an annotation emitted by a script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationLevel {
+    Warning,
+    Error,
+}
+
+/// Highlighted message emitted with `::warning` or `::error`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub level: AnnotationLevel,
+    pub message: String,
+}
+
+/// Input request emitted with `::prompt`; the answer is written to the script's stdin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptRequest {
+    pub id: String,
+    pub kind: String,
+    pub text: Option<String>,
+    pub default: Option<String>,
+    pub choices: Option<Vec<String>>,
+    pub required: bool,
+}
+
+impl PromptRequest {
+    /// Describes the prompt as a schema field so it shares form rendering and validation.
+    pub fn to_field(&self) -> Field {
+        Field {
+            name: self.id.clone(),
+            prompt: self.text.clone(),
+            kind: self.kind.clone(),
+            order: 0,
+            required: Some(self.required),
+            default: self.default.clone(),
+            choices: self.choices.clone(),
+            arg: None,
+            secret: None,
+            switch: None,
+        }
+    }
+}
+
+/// A stdout line interpreted through the `::directive` protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputLine {
+    Text(String),
+    Progress {
+        percent: u8,
+        message: Option<String>,
+    },
+    Annotation(Annotation),
+    Group(String),
+    EndGroup,
+    Prompt(PromptRequest),
+}
+
+/// Parses one stdout line; unknown or malformed directives stay plain text.
+pub fn parse_output_line(line: &str) -> OutputLine {
+    let Some(directive) = line.trim_end_matches('\r').strip_prefix("::") else {
+        return OutputLine::Text(line.to_string());
+    };
+    let (name, rest) = match directive.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (directive, ""),
+    };
+
+    match name {
+        "progress" => {
+            let (value, message) = match rest.split_once(char::is_whitespace) {
+                Some((value, message)) => (value, Some(message.trim())),
+                None => (rest, None),
+            };
+            match value.trim_end_matches('%').parse::<f64>() {
+                Ok(percent) if percent.is_finite() => OutputLine::Progress {
+                    percent: percent.clamp(0.0, 100.0).round() as u8,
+                    message: message
+                        .filter(|message| !message.is_empty())
+                        .map(str::to_string),
+                },
+                _ => OutputLine::Text(line.to_string()),
+            }
+        }
+        "warning" => OutputLine::Annotation(Annotation {
+            level: AnnotationLevel::Warning,
+            message: rest.to_string(),
+        }),
+        "error" => OutputLine::Annotation(Annotation {
+            level: AnnotationLevel::Error,
+            message: rest.to_string(),
+        }),
+        "group" => OutputLine::Group(rest.to_string()),
+        "endgroup" if rest.is_empty() => OutputLine::EndGroup,
+        "prompt" => match parse_prompt(rest) {
+            Some(prompt) => OutputLine::Prompt(prompt),
+            None => OutputLine::Text(line.to_string()),
+        },
+        _ => OutputLine::Text(line.to_string()),
+    }
+}
+
+fn parse_prompt(attrs: &str) -> Option<PromptRequest> {
+    let mut prompt = PromptRequest {
+        id: String::new(),
+        kind: "string".to_string(),
+        text: None,
+        default: None,
+        choices: None,
+        required: true,
+    };
+    for (key, value) in parse_attributes(attrs)? {
+        match key.as_str() {
+            "id" => prompt.id = value,
+            "type" => prompt.kind = value,
+            "text" => prompt.text = Some(value),
+            "default" => prompt.default = Some(value),
+            "choices" => {
+                prompt.choices = Some(
+                    value
+                        .split(',')
+                        .map(|choice| choice.trim().to_string())
+                        .filter(|choice| !choice.is_empty())
+                        .collect(),
+                )
+            }
+            "required" => prompt.required = !matches!(value.as_str(), "false" | "no" | "0"),
+            _ => {}
+        }
+    }
+    if prompt.id.is_empty() {
+        return None;
+    }
+    Some(prompt)
+}
+
+/// Splits `key=value key="quoted value"` pairs; returns None on unbalanced quotes.
+fn parse_attributes(input: &str) -> Option<Vec<(String, String)>> {
+    let mut attrs = Vec::new();
+    let mut chars = input.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            return Some(attrs);
+        }
+        let mut key = String::new();
+        while let Some(&ch) = chars.peek() {
+            if ch == '=' || ch.is_whitespace() {
+                break;
+            }
+            key.push(ch);
+            chars.next();
+        }
+        if chars.next() != Some('=') {
+            return None;
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut closed = false;
+            while let Some(ch) = chars.next() {
+                match ch {
+                    '"' => {
+                        closed = true;
+                        break;
+                    }
+                    '\\' => value.push(chars.next()?),
+                    _ => value.push(ch),
+                }
+            }
+            if !closed {
+                return None;
+            }
+        } else {
+            while let Some(&ch) = chars.peek() {
+                if ch.is_whitespace() {
+                    break;
+                }
+                value.push(ch);
+                chars.next();
+            }
+        }
+        attrs.push((key.to_ascii_lowercase(), value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progress_with_message() {
+        assert_eq!(
+            parse_output_line("::progress 40 Deleting disks"),
+            OutputLine::Progress {
+                percent: 40,
+                message: Some("Deleting disks".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_progress_clamps_and_accepts_percent_sign() {
+        assert_eq!(
+            parse_output_line("::progress 140%"),
+            OutputLine::Progress {
+                percent: 100,
+                message: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_progress_invalid_number_is_text() {
+        let line = "::progress soon";
+        assert_eq!(parse_output_line(line), OutputLine::Text(line.to_string()));
+    }
+
+    #[test]
+    fn test_parse_annotations() {
+        assert_eq!(
+            parse_output_line("::warning quota almost reached"),
+            OutputLine::Annotation(Annotation {
+                level: AnnotationLevel::Warning,
+                message: "quota almost reached".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_output_line("::error lock held"),
+            OutputLine::Annotation(Annotation {
+                level: AnnotationLevel::Error,
+                message: "lock held".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!(
+            parse_output_line("::group Disks"),
+            OutputLine::Group("Disks".to_string())
+        );
+        assert_eq!(parse_output_line("::endgroup"), OutputLine::EndGroup);
+    }
+
+    #[test]
+    fn test_unknown_directive_passes_through() {
+        let line = "::set-output name=x";
+        assert_eq!(parse_output_line(line), OutputLine::Text(line.to_string()));
+        assert_eq!(
+            parse_output_line("plain text"),
+            OutputLine::Text("plain text".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_prompt_with_quoted_text() {
+        let line = r#"::prompt id=confirm type=bool text="Delete 14 resources?""#;
+        let OutputLine::Prompt(prompt) = parse_output_line(line) else {
+            panic!("expected prompt");
+        };
+        assert_eq!(prompt.id, "confirm");
+        assert_eq!(prompt.kind, "bool");
+        assert_eq!(prompt.text.as_deref(), Some("Delete 14 resources?"));
+        assert!(prompt.required);
+    }
+
+    #[test]
+    fn test_parse_prompt_choices_and_escapes() {
+        let line =
+            r#"::prompt id=region choices="eastus, westus" default=eastus text="Say \"hi\"""#;
+        let OutputLine::Prompt(prompt) = parse_output_line(line) else {
+            panic!("expected prompt");
+        };
+        assert_eq!(
+            prompt.choices,
+            Some(vec!["eastus".to_string(), "westus".to_string()])
+        );
+        assert_eq!(prompt.default.as_deref(), Some("eastus"));
+        assert_eq!(prompt.text.as_deref(), Some("Say \"hi\""));
+        assert_eq!(prompt.to_field().name, "region");
+    }
+
+    #[test]
+    fn test_parse_prompt_without_id_is_text() {
+        let line = r#"::prompt text="unbalanced"#;
+        assert_eq!(parse_output_line(line), OutputLine::Text(line.to_string()));
+        let line = "::prompt type=bool";
+        assert_eq!(parse_output_line(line), OutputLine::Text(line.to_string()));
+    }
+}
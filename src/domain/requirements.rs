@@ -0,0 +1,70 @@
+use std::cmp::Ordering;
+
+/// Finds the first dotted version number (e.g. `2.61.0`) in command output.
+pub fn extract_version(output: &str) -> Option<String> {
+    output
+        .split(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+        .map(|token| token.trim_matches('.'))
+        .find(|token| token.contains('.') && token.split('.').all(|part| !part.is_empty()))
+        .map(str::to_string)
+}
+
+/// Compares dotted versions numerically; missing components count as zero.
+pub fn version_at_least(found: &str, minimum: &str) -> bool {
+    compare_versions(found, minimum) != Ordering::Less
+}
+
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches(['v', 'V'])
+            .split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(char::is_ascii_digit)
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    };
+    let left = parse(left);
+    let right = parse(right);
+    for idx in 0..left.len().max(right.len()) {
+        let a = left.get(idx).copied().unwrap_or(0);
+        let b = right.get(idx).copied().unwrap_or(0);
+        match a.cmp(&b) {
+            Ordering::Equal => continue,
+            other => return other,
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_version_from_tool_output() {
+        assert_eq!(
+            extract_version("azure-cli                         2.61.0 *"),
+            Some("2.61.0".to_string())
+        );
+        assert_eq!(extract_version("jq-1.7.1"), Some("1.7.1".to_string()));
+        assert_eq!(
+            extract_version("Client Version: v1.29.2"),
+            Some("1.29.2".to_string())
+        );
+        assert_eq!(extract_version("no version here"), None);
+    }
+
+    #[test]
+    fn test_version_at_least_compares_numerically() {
+        assert!(version_at_least("2.61.0", "2.50"));
+        assert!(version_at_least("1.10", "1.9"));
+        assert!(version_at_least("2.0", "2.0.0"));
+        assert!(!version_at_least("1.6", "1.7"));
+        assert!(version_at_least("v1.29.2", "1.29"));
+    }
+}
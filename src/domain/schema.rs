@@ -1,7 +1,7 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 /// Schema definition for a script.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Schema {
     pub name: String,
@@ -10,10 +10,15 @@ pub struct Schema {
     pub fields: Vec<Field>,
     pub outputs: Option<Vec<OutputField>>,
     pub queue: Option<QueueSpec>,
+    pub output_format: Option<String>,
+    pub stdin: Option<StdinSpec>,
+    pub requires: Option<Vec<Requirement>>,
+    /// The script asks for input with `::prompt`, so its stdin is kept open for answers.
+    pub prompts: Option<bool>,
 }
 
 /// Script input field definition.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct Field {
     pub name: String,
@@ -25,10 +30,93 @@ pub struct Field {
     pub default: Option<String>,
     pub choices: Option<Vec<String>>,
     pub arg: Option<String>,
+    /// Sensitive value: masked in the form and never saved in presets. Defaults to a guess
+    /// from the name (see [`is_sensitive_key`]).
+    pub secret: Option<bool>,
+    /// Bool passed as a bare flag: the flag alone when true, nothing when false.
+    pub switch: Option<bool>,
+}
+
+impl Field {
+    pub fn is_secret(&self) -> bool {
+        self.secret.unwrap_or_else(|| is_sensitive_key(&self.name))
+    }
+
+    pub fn is_switch(&self) -> bool {
+        self.switch.unwrap_or(false)
+    }
+
+    /// Flag the script receives this field under: `Arg`, or `--<name>` when it has none.
+    pub fn flag(&self) -> String {
+        self.arg
+            .clone()
+            .unwrap_or_else(|| format!("--{}", self.name))
+    }
+
+    /// Arguments passing a normalized value: `--name value`, or just `--name` for a switch
+    /// that is on.
+    pub fn cli_args(&self, value: String) -> Vec<String> {
+        let flag = self.flag();
+        match self.is_switch() {
+            true if value == "true" => vec![flag],
+            true => Vec::new(),
+            false => vec![flag, value],
+        }
+    }
+}
+
+/// Names that usually hold credentials, such as `api_token` or `DB_PASSWORD`.
+pub fn is_sensitive_key(key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    let tokens = [
+        "password", "secret", "token", "key", "api", "private", "cred",
+    ];
+    tokens.iter().any(|token| lower.contains(token))
+}
+
+/// External command a script needs before it can run.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "PascalCase")]
+pub struct Requirement {
+    pub command: String,
+    pub min_version: Option<String>,
+    /// Arguments that print the version; defaults to `--version`.
+    pub version_args: Option<Vec<String>>,
+    pub hint: Option<String>,
+}
+
+impl Requirement {
+    /// Short form such as `az >= 2.50`.
+    pub fn label(&self) -> String {
+        match &self.min_version {
+            Some(version) => format!("{} >= {}", self.command, version),
+            None => self.command.clone(),
+        }
+    }
+}
+
+/// Field whose value is piped into the script's stdin instead of passed as an argument.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct StdinSpec {
+    pub field: String,
+    #[serde(default)]
+    pub source: StdinSource,
+}
+
+/// How a stdin field value is interpreted.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StdinSource {
+    /// The value itself is the payload.
+    #[default]
+    Text,
+    /// The value is a file path, relative to the workspace root.
+    File,
 }
 
 /// Script output field definition.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct OutputField {
     pub name: String,
@@ -37,7 +125,7 @@ pub struct OutputField {
 }
 
 /// Optional queue specification for batch execution.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct QueueSpec {
     pub matrix: Option<MatrixSpec>,
@@ -45,14 +133,14 @@ pub struct QueueSpec {
 }
 
 /// Matrix specification for batch execution.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct MatrixSpec {
     pub values: Vec<MatrixValue>,
 }
 
 /// Matrix value.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct MatrixValue {
     pub name: String,
@@ -60,7 +148,7 @@ pub struct MatrixValue {
 }
 
 /// Queue case entry.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct QueueCase {
     pub name: Option<String>,
@@ -68,7 +156,7 @@ pub struct QueueCase {
 }
 
 /// Queue case value.
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct CaseValue {
     pub name: String,
@@ -0,0 +1,541 @@
+use serde_json::{json, Map, Value};
+
+/// Field `Type` values Omakure understands.
+const FIELD_TYPES: &[&str] = &["string", "number", "bool", "boolean"];
+
+/// Shape of a schema value; drives both the exported JSON Schema and strict validation, so the
+/// two can't drift apart.
+#[derive(Debug, Clone, Copy)]
+enum Shape {
+    String,
+    /// One of a fixed set of strings.
+    Enum(&'static [&'static str]),
+    /// Non-negative integer.
+    Integer,
+    Bool,
+    Array(&'static Shape),
+    Object(&'static Object),
+}
+
+#[derive(Debug)]
+struct Object {
+    name: &'static str,
+    description: &'static str,
+    properties: &'static [Property],
+    required: &'static [&'static str],
+    /// Property that stands in for the required ones (a field pulled in with `$ref`).
+    replaces_required: Option<&'static str>,
+}
+
+#[derive(Debug)]
+struct Property {
+    name: &'static str,
+    shape: Shape,
+    description: &'static str,
+}
+
+const fn prop(name: &'static str, shape: Shape, description: &'static str) -> Property {
+    Property {
+        name,
+        shape,
+        description,
+    }
+}
+
+static STRINGS: Shape = Shape::String;
+
+static SCHEMA: Object = Object {
+    name: "Schema",
+    description: "Omakure script schema.",
+    properties: &[
+        prop(
+            "$schema",
+            Shape::String,
+            "JSON Schema used by editors; ignored by Omakure.",
+        ),
+        prop("Name", Shape::String, "Script name shown in the TUI."),
+        prop("Description", Shape::String, "What the script does."),
+        prop("Tags", Shape::Array(&STRINGS), "Tags used by search."),
+        prop(
+            "Fields",
+            Shape::Array(&FIELD_SHAPE),
+            "Inputs passed to the script.",
+        ),
+        prop(
+            "Outputs",
+            Shape::Array(&OUTPUT_SHAPE),
+            "Values the script reports back.",
+        ),
+        prop(
+            "Queue",
+            Shape::Object(&QUEUE),
+            "Batch runs over several inputs.",
+        ),
+        prop(
+            "OutputFormat",
+            Shape::String,
+            "Set to \"json\" to open results in the JSON view.",
+        ),
+        prop(
+            "Stdin",
+            Shape::Object(&STDIN),
+            "Field piped into the script's stdin.",
+        ),
+        prop(
+            "Requires",
+            Shape::Array(&REQUIREMENT_SHAPE),
+            "Commands checked before the script runs.",
+        ),
+        prop(
+            "Prompts",
+            Shape::Bool,
+            "The script asks for input with ::prompt; keeps its stdin open for answers.",
+        ),
+    ],
+    required: &["Name", "Fields"],
+    replaces_required: None,
+};
+
+static FIELD_SHAPE: Shape = Shape::Object(&FIELD);
+static FIELD: Object = Object {
+    name: "Field",
+    description: "Script input.",
+    properties: &[
+        prop(
+            "$ref",
+            Shape::String,
+            "Shared field to start from, e.g. \"azure/subscription_id\".",
+        ),
+        prop("Name", Shape::String, "Internal field name."),
+        prop("Prompt", Shape::String, "Text shown to the user."),
+        prop("Type", Shape::Enum(FIELD_TYPES), "Value type."),
+        prop("Order", Shape::Integer, "Position in the form."),
+        prop("Required", Shape::Bool, "Whether a value must be given."),
+        prop(
+            "Default",
+            Shape::String,
+            "Value used when the input is left empty.",
+        ),
+        prop("Choices", Shape::Array(&STRINGS), "Allowed values."),
+        prop(
+            "Arg",
+            Shape::String,
+            "Flag passed to the script; defaults to --<Name>.",
+        ),
+        prop(
+            "Secret",
+            Shape::Bool,
+            "Masks the value in the form and keeps it out of presets.",
+        ),
+        prop(
+            "Switch",
+            Shape::Bool,
+            "Bool passed as a bare flag: the flag alone when true, nothing when false.",
+        ),
+    ],
+    required: &["Name", "Type", "Order"],
+    replaces_required: Some("$ref"),
+};
+
+static OUTPUT_SHAPE: Shape = Shape::Object(&OUTPUT);
+static OUTPUT: Object = Object {
+    name: "Output",
+    description: "Value the script reports back.",
+    properties: &[
+        prop("Name", Shape::String, "Output name."),
+        prop("Type", Shape::String, "Output type."),
+    ],
+    required: &["Name", "Type"],
+    replaces_required: None,
+};
+
+static QUEUE: Object = Object {
+    name: "Queue",
+    description: "Batch runs over several inputs.",
+    properties: &[
+        prop(
+            "Matrix",
+            Shape::Object(&MATRIX),
+            "Runs every combination of values.",
+        ),
+        prop("Cases", Shape::Array(&CASE_SHAPE), "Runs each listed case."),
+    ],
+    required: &[],
+    replaces_required: None,
+};
+
+static MATRIX: Object = Object {
+    name: "Matrix",
+    description: "Values combined into runs.",
+    properties: &[prop(
+        "Values",
+        Shape::Array(&MATRIX_VALUE_SHAPE),
+        "Field values to combine.",
+    )],
+    required: &["Values"],
+    replaces_required: None,
+};
+
+static MATRIX_VALUE_SHAPE: Shape = Shape::Object(&MATRIX_VALUE);
+static MATRIX_VALUE: Object = Object {
+    name: "MatrixValue",
+    description: "Values for one field.",
+    properties: &[
+        prop("Name", Shape::String, "Field name."),
+        prop("Values", Shape::Array(&STRINGS), "Values to run with."),
+    ],
+    required: &["Name", "Values"],
+    replaces_required: None,
+};
+
+static CASE_SHAPE: Shape = Shape::Object(&CASE);
+static CASE: Object = Object {
+    name: "Case",
+    description: "One queued run.",
+    properties: &[
+        prop("Name", Shape::String, "Case label."),
+        prop(
+            "Values",
+            Shape::Array(&CASE_VALUE_SHAPE),
+            "Field values for this run.",
+        ),
+    ],
+    required: &["Values"],
+    replaces_required: None,
+};
+
+static CASE_VALUE_SHAPE: Shape = Shape::Object(&CASE_VALUE);
+static CASE_VALUE: Object = Object {
+    name: "CaseValue",
+    description: "Value for one field.",
+    properties: &[
+        prop("Name", Shape::String, "Field name."),
+        prop("Value", Shape::String, "Field value."),
+    ],
+    required: &["Name", "Value"],
+    replaces_required: None,
+};
+
+static STDIN: Object = Object {
+    name: "Stdin",
+    description: "Field piped into the script's stdin.",
+    properties: &[
+        prop("Field", Shape::String, "Field whose value is piped."),
+        prop(
+            "Source",
+            Shape::Enum(&["text", "file"]),
+            "Pipe the value itself, or the file it names.",
+        ),
+    ],
+    required: &["Field"],
+    replaces_required: None,
+};
+
+static REQUIREMENT_SHAPE: Shape = Shape::Object(&REQUIREMENT);
+static REQUIREMENT: Object = Object {
+    name: "Requirement",
+    description: "Command the script needs.",
+    properties: &[
+        prop("Command", Shape::String, "Executable looked up in PATH."),
+        prop("MinVersion", Shape::String, "Lowest accepted version."),
+        prop(
+            "VersionArgs",
+            Shape::Array(&STRINGS),
+            "Arguments that print the version; defaults to --version.",
+        ),
+        prop("Hint", Shape::String, "How to install the command."),
+    ],
+    required: &["Command"],
+    replaces_required: None,
+};
+
+/// A value in a schema document that doesn't match the schema format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecViolation {
+    /// JSON pointer to the offending value, e.g. `/Fields/0/Requried`.
+    pub pointer: String,
+    pub message: String,
+}
+
+/// JSON Schema (draft 2020-12) describing script schemas, for editor completion.
+pub fn schema_json_schema() -> Value {
+    let mut defs = Map::new();
+    collect_defs(&SCHEMA, &mut defs);
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Omakure script schema",
+        "$ref": "#/$defs/Schema",
+        "$defs": defs,
+    })
+}
+
+/// Checks a schema document as written (before `$ref` fields are expanded) for unknown
+/// properties, wrong value types, missing required properties and invalid enum values.
+pub fn spec_violations(document: &Value) -> Vec<SpecViolation> {
+    let mut violations = Vec::new();
+    check(
+        &Shape::Object(&SCHEMA),
+        document,
+        String::new(),
+        &mut violations,
+    );
+    violations
+}
+
+fn collect_defs(object: &'static Object, defs: &mut Map<String, Value>) {
+    if defs.contains_key(object.name) {
+        return;
+    }
+    defs.insert(object.name.to_string(), Value::Null);
+
+    let mut properties = Map::new();
+    for property in object.properties {
+        let mut schema = shape_schema(&property.shape, defs);
+        schema["description"] = Value::String(property.description.to_string());
+        properties.insert(property.name.to_string(), schema);
+    }
+    let mut definition = json!({
+        "type": "object",
+        "description": object.description,
+        "properties": properties,
+        "additionalProperties": false,
+    });
+    match object.replaces_required {
+        Some(key) => {
+            definition["anyOf"] = json!([{ "required": [key] }, { "required": object.required }]);
+        }
+        None if !object.required.is_empty() => definition["required"] = json!(object.required),
+        None => {}
+    }
+    defs.insert(object.name.to_string(), definition);
+}
+
+fn shape_schema(shape: &Shape, defs: &mut Map<String, Value>) -> Value {
+    match shape {
+        Shape::String => json!({ "type": "string" }),
+        Shape::Enum(values) => json!({ "type": "string", "enum": values }),
+        Shape::Integer => json!({ "type": "integer", "minimum": 0 }),
+        Shape::Bool => json!({ "type": "boolean" }),
+        Shape::Array(item) => json!({ "type": "array", "items": shape_schema(item, defs) }),
+        Shape::Object(object) => {
+            collect_defs(object, defs);
+            json!({ "$ref": format!("#/$defs/{}", object.name) })
+        }
+    }
+}
+
+fn check(shape: &Shape, value: &Value, pointer: String, out: &mut Vec<SpecViolation>) {
+    let mut violation = |message: String| {
+        out.push(SpecViolation {
+            pointer: pointer.clone(),
+            message,
+        })
+    };
+    match shape {
+        Shape::String if !value.is_string() => violation(expected("a string", value)),
+        Shape::Bool if !value.is_boolean() => violation(expected("true or false", value)),
+        Shape::Integer
+            if value
+                .as_u64()
+                .is_none_or(|number| number > u64::from(u32::MAX)) =>
+        {
+            violation(expected("a non-negative integer", value))
+        }
+        Shape::Enum(allowed) => match value.as_str() {
+            Some(text) if allowed.contains(&text) => {}
+            Some(text) => violation(format!("`{}` is not one of: {}", text, allowed.join(", "))),
+            None => violation(expected("a string", value)),
+        },
+        Shape::Array(item) => match value.as_array() {
+            Some(items) => {
+                for (index, element) in items.iter().enumerate() {
+                    check(item, element, format!("{}/{}", pointer, index), out);
+                }
+            }
+            None => violation(expected("an array", value)),
+        },
+        Shape::Object(object) => match value.as_object() {
+            Some(entries) => check_object(object, entries, &pointer, out),
+            None => violation(expected("an object", value)),
+        },
+        _ => {}
+    }
+}
+
+fn check_object(
+    object: &Object,
+    entries: &Map<String, Value>,
+    pointer: &str,
+    out: &mut Vec<SpecViolation>,
+) {
+    for (key, value) in entries {
+        let child = format!("{}/{}", pointer, escape_pointer(key));
+        match object
+            .properties
+            .iter()
+            .find(|property| property.name == key)
+        {
+            Some(property) => check(&property.shape, value, child, out),
+            None => {
+                let hint = closest_property(object, key)
+                    .map(|name| format!("; did you mean `{}`?", name))
+                    .unwrap_or_default();
+                out.push(SpecViolation {
+                    pointer: child,
+                    message: format!("Unknown property `{}` in {}{}", key, object.name, hint),
+                });
+            }
+        }
+    }
+    let replaced = object
+        .replaces_required
+        .is_some_and(|key| entries.contains_key(key));
+    if replaced {
+        return;
+    }
+    for required in object.required {
+        if !entries.contains_key(*required) {
+            out.push(SpecViolation {
+                pointer: pointer.to_string(),
+                message: format!(
+                    "Missing required property `{}` in {}",
+                    required, object.name
+                ),
+            });
+        }
+    }
+}
+
+fn expected(what: &str, value: &Value) -> String {
+    let found = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    };
+    format!("Expected {}, found {}", what, found)
+}
+
+/// Known property within two edits of `key`, ignoring case.
+fn closest_property(object: &Object, key: &str) -> Option<&'static str> {
+    let key = key.to_lowercase();
+    object
+        .properties
+        .iter()
+        .map(|property| {
+            (
+                edit_distance(&key, &property.name.to_lowercase()),
+                property.name,
+            )
+        })
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::schema::{Field, Requirement, Schema, StdinSource, StdinSpec};
+
+    #[test]
+    fn test_spec_violations_point_at_problems() {
+        let document = json!({
+            "Name": "deploy",
+            "Fields": [
+                {"Name": "env", "Type": "strng", "Order": 1, "Requried": true},
+                {"$ref": "azure/region", "Order": "2"},
+                {"Prompt": "x"}
+            ],
+            "Stdin": {"Field": "env", "Source": "pipe"}
+        });
+        let violations: Vec<String> = spec_violations(&document)
+            .into_iter()
+            .map(|violation| format!("{} {}", violation.pointer, violation.message))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                "/Fields/0/Requried Unknown property `Requried` in Field; did you mean `Required`?",
+                "/Fields/0/Type `strng` is not one of: string, number, bool, boolean",
+                "/Fields/1/Order Expected a non-negative integer, found a string",
+                "/Fields/2 Missing required property `Name` in Field",
+                "/Fields/2 Missing required property `Type` in Field",
+                "/Fields/2 Missing required property `Order` in Field",
+                "/Stdin/Source `pipe` is not one of: text, file",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spec_covers_every_schema_key() {
+        let schema = Schema {
+            name: "all".to_string(),
+            description: Some(String::new()),
+            tags: Some(Vec::new()),
+            fields: vec![Field {
+                name: "x".to_string(),
+                prompt: Some(String::new()),
+                kind: "string".to_string(),
+                order: 1,
+                required: Some(true),
+                default: Some(String::new()),
+                choices: Some(Vec::new()),
+                arg: Some("--x".to_string()),
+                secret: Some(false),
+                switch: Some(false),
+            }],
+            outputs: Some(Vec::new()),
+            queue: None,
+            output_format: Some("json".to_string()),
+            stdin: Some(StdinSpec {
+                field: "x".to_string(),
+                source: StdinSource::File,
+            }),
+            requires: Some(vec![Requirement {
+                command: "az".to_string(),
+                min_version: Some("2.0".to_string()),
+                version_args: Some(Vec::new()),
+                hint: Some(String::new()),
+            }]),
+            prompts: Some(true),
+        };
+        let mut document = serde_json::to_value(&schema).unwrap();
+        document["Queue"] = json!({
+            "Matrix": {"Values": [{"Name": "x", "Values": ["a"]}]},
+            "Cases": [{"Name": "one", "Values": [{"Name": "x", "Value": "a"}]}]
+        });
+        document["Outputs"] = json!([{"Name": "id", "Type": "string"}]);
+        assert_eq!(spec_violations(&document), Vec::new());
+
+        let exported = schema_json_schema();
+        assert_eq!(
+            exported["$defs"]["Field"]["properties"]["Type"]["enum"],
+            json!(FIELD_TYPES)
+        );
+        assert_eq!(
+            exported["$defs"]["Schema"]["required"],
+            json!(["Name", "Fields"])
+        );
+    }
+}
@@ -67,6 +67,8 @@ mod tests {
             default: None,
             choices: None,
             arg: None,
+            secret: None,
+            switch: None,
         }
     }
 
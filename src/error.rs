@@ -35,6 +35,24 @@ pub enum SchemaError {
     #[error("Invalid JSON in schema: {0}")]
     InvalidJson(#[from] serde_json::Error),
 
+    #[error("Invalid TOML in schema: {0}")]
+    InvalidToml(#[from] toml::de::Error),
+
+    #[error("Invalid YAML in schema: {0}")]
+    InvalidYaml(#[from] serde_yaml::Error),
+
+    #[error("Invalid schema: {0}")]
+    Invalid(String),
+
+    #[error("Strict schema check failed at {0}")]
+    Strict(String),
+
+    #[error("Unknown field reference `{reference}`")]
+    UnknownFieldRef { reference: String },
+
+    #[error("Unknown schema format `{name}` (expected json, toml or yaml)")]
+    UnknownFormat { name: String },
+
     #[error("Schema JSON object not found in output")]
     JsonNotFound,
 
@@ -62,6 +80,37 @@ pub enum ScriptError {
 
     #[error("{name} found, but check failed: {message}")]
     DependencyCheckFailed { name: String, message: String },
+
+    #[error("{name} {found} is older than the required {required}. {hint}")]
+    DependencyOutdated {
+        name: String,
+        found: String,
+        required: String,
+        hint: String,
+    },
+
+    #[error("Missing prerequisites: {details}")]
+    RequirementsUnmet { details: String },
+
+    #[error("Script requested input `{id}` but no answer was provided")]
+    PromptUnanswered { id: String },
+
+    #[error("Script requested input `{id}` but its schema doesn't declare `\"Prompts\": true`")]
+    PromptsUndeclared { id: String },
+
+    #[error(
+        "Script requested input `{id}` while its stdin carries the `Stdin` payload; prompts can't be answered in runs that pipe a payload"
+    )]
+    PromptWithStdin { id: String },
+
+    #[error("Run blocked by hook: {reason}")]
+    BlockedByHook { reason: String },
+
+    #[error("Hook failed: {message}")]
+    HookFailed { message: String },
+
+    #[error("Run cancelled: {message}")]
+    NotConfirmed { message: String },
 }
 
 /// Errors related to environment configuration.
@@ -1,3 +1,5 @@
+use crate::command_stub::Cassette;
+use crate::domain::Annotation;
 use crate::ports::ScriptRunOutput;
 use crate::workspace::Workspace;
 use serde::{Deserialize, Serialize};
@@ -6,6 +8,9 @@ use std::io;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+/// Default cap for stdin stored in history, in bytes.
+const DEFAULT_STDIN_LIMIT: usize = 64 * 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub timestamp: i64,
@@ -16,6 +21,18 @@ pub struct HistoryEntry {
     pub stdout: String,
     pub stderr: String,
     pub error: Option<String>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub stdin: Option<String>,
+    #[serde(default)]
+    pub stdin_truncated: bool,
+    /// Notes added by `after_run` hooks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Cassette of the external calls recorded with `run --record`, relative to the workspace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cassette: Option<PathBuf>,
 }
 
 pub fn success_entry(
@@ -33,6 +50,11 @@ pub fn success_entry(
         stdout: output.stdout,
         stderr: output.stderr,
         error: None,
+        annotations: output.annotations,
+        stdin: None,
+        stdin_truncated: false,
+        notes: Vec::new(),
+        cassette: None,
     }
 }
 
@@ -51,12 +73,76 @@ pub fn error_entry(
         stdout: String::new(),
         stderr: String::new(),
         error: Some(message),
+        annotations: Vec::new(),
+        stdin: None,
+        stdin_truncated: false,
+        notes: Vec::new(),
+        cassette: None,
+    }
+}
+
+/// Stores the stdin payload on an entry, capped by `[history] stdin_limit` in omakure.toml.
+pub fn attach_stdin(workspace: &Workspace, entry: &mut HistoryEntry, stdin: Option<&str>) {
+    let Some(stdin) = stdin else {
+        return;
+    };
+    let limit = workspace
+        .load_config()
+        .history
+        .stdin_limit
+        .unwrap_or(DEFAULT_STDIN_LIMIT);
+    let (stored, truncated) = truncate_stdin(stdin, limit);
+    entry.stdin = Some(stored);
+    entry.stdin_truncated = truncated;
+}
+
+fn truncate_stdin(stdin: &str, limit: usize) -> (String, bool) {
+    if stdin.len() <= limit {
+        return (stdin.to_string(), false);
+    }
+    let mut end = limit;
+    while !stdin.is_char_boundary(end) {
+        end -= 1;
+    }
+    (stdin[..end].to_string(), true)
+}
+
+/// Saves a cassette under `.history/cassettes`, named after the entry, and links it.
+pub fn attach_cassette(
+    workspace: &Workspace,
+    entry: &mut HistoryEntry,
+    cassette: &Cassette,
+) -> io::Result<()> {
+    let path = workspace
+        .history_dir()
+        .join("cassettes")
+        .join(history_file_name(entry));
+    cassette.save(&path)?;
+    entry.cassette = Some(
+        path.strip_prefix(workspace.root())
+            .unwrap_or(&path)
+            .to_path_buf(),
+    );
+    Ok(())
+}
+
+/// Loads a cassette file, or the cassette linked from a history entry file.
+pub fn load_cassette(workspace: &Workspace, path: &Path) -> Result<Cassette, String> {
+    let data = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    if let Ok(entry) = serde_json::from_slice::<HistoryEntry>(&data) {
+        let cassette = entry.cassette.ok_or_else(|| {
+            format!(
+                "{}: history entry has no cassette; run it with --record first",
+                path.display()
+            )
+        })?;
+        return Cassette::load(&workspace.root().join(cassette));
     }
+    Cassette::load(path)
 }
 
 pub fn record_entry(workspace: &Workspace, entry: &HistoryEntry) -> io::Result<PathBuf> {
-    let data = serde_json::to_vec_pretty(entry)
-        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+    let data = serde_json::to_vec_pretty(entry).map_err(io::Error::other)?;
     let file_name = history_file_name(entry);
     let path = workspace.history_dir().join(file_name);
     fs::write(&path, data)?;
@@ -95,7 +181,7 @@ pub fn load_entries(workspace: &Workspace) -> io::Result<Vec<HistoryEntry>> {
         entries.push(parsed);
     }
 
-    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
     Ok(entries)
 }
 
@@ -253,6 +339,11 @@ mod tests {
             stdout: "output here\n".to_string(),
             stderr: "".to_string(),
             error: None,
+            annotations: Vec::new(),
+            stdin: None,
+            stdin_truncated: false,
+            notes: Vec::new(),
+            cassette: None,
         };
         let output = format_output(&entry);
         assert!(output.contains("STDOUT:"));
@@ -270,8 +361,57 @@ mod tests {
             stdout: "".to_string(),
             stderr: "".to_string(),
             error: Some("Script failed to run".to_string()),
+            annotations: Vec::new(),
+            stdin: None,
+            stdin_truncated: false,
+            notes: Vec::new(),
+            cassette: None,
         };
         let output = format_output(&entry);
         assert_eq!(output, "Script failed to run");
     }
+
+    #[test]
+    fn test_load_cassette_from_history_entry() {
+        use crate::command_stub::{RecordedCall, StubResponse};
+
+        let root = std::env::temp_dir().join(format!("omakure-history-{}", std::process::id()));
+        let workspace = Workspace::new(root.clone());
+        fs::create_dir_all(workspace.history_dir()).unwrap();
+        let cassette = Cassette {
+            script: PathBuf::from("demo.bash"),
+            args: vec!["--who".to_string(), "bob".to_string()],
+            stdin: None,
+            calls: vec![RecordedCall {
+                command: "az".to_string(),
+                args: vec!["account".to_string(), "show".to_string()],
+                stdin: String::new(),
+                response: StubResponse {
+                    stdout: "{}".to_string(),
+                    stderr: String::new(),
+                    exit_code: 0,
+                },
+            }],
+        };
+        let mut entry = error_entry(&workspace, &root.join("demo.bash"), &[], "x".to_string());
+        attach_cassette(&workspace, &mut entry, &cassette).unwrap();
+        let entry_path = record_entry(&workspace, &entry).unwrap();
+
+        let loaded = load_cassette(&workspace, &entry_path).unwrap();
+        assert_eq!(loaded.args, cassette.args);
+        assert_eq!(loaded.calls, cassette.calls);
+        assert_eq!(loaded.commands(), vec!["az".to_string()]);
+        let direct = load_cassette(&workspace, &root.join(entry.cassette.unwrap())).unwrap();
+        assert_eq!(direct.calls, cassette.calls);
+        assert_eq!(load_entries(&workspace).unwrap().len(), 1);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_truncate_stdin_respects_char_boundaries() {
+        assert_eq!(truncate_stdin("abc", 10), ("abc".to_string(), false));
+        assert_eq!(truncate_stdin("abc", 2), ("ab".to_string(), true));
+        assert_eq!(truncate_stdin("aé", 2), ("a".to_string(), true));
+    }
 }
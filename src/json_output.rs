@@ -0,0 +1,286 @@
+use crate::domain::{parse_output_line, OutputLine};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Tabular view of a JSON array of objects.
+#[derive(Debug, Clone)]
+pub struct JsonTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// One visible line of a collapsible JSON tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeRow {
+    pub path: String,
+    pub depth: usize,
+    pub label: String,
+    pub summary: String,
+    pub expandable: bool,
+    pub expanded: bool,
+}
+
+/// Parses script stdout as JSON, accepting only objects and arrays. `::` directive lines
+/// (progress, annotations, groups, prompts) are skipped first.
+pub fn parse_json_output(stdout: &str) -> Option<Value> {
+    let text: Vec<&str> = stdout
+        .lines()
+        .filter(|line| matches!(parse_output_line(line), OutputLine::Text(_)))
+        .collect();
+    let text = text.join("\n");
+    let trimmed = text.trim();
+    if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+        return None;
+    }
+    match serde_json::from_str::<Value>(trimmed) {
+        Ok(value @ (Value::Object(_) | Value::Array(_))) => Some(value),
+        _ => None,
+    }
+}
+
+/// Builds a table when the value is a non-empty array of objects.
+pub fn table_from_value(value: &Value) -> Option<JsonTable> {
+    let items = value.as_array()?;
+    if items.is_empty() || !items.iter().all(Value::is_object) {
+        return None;
+    }
+
+    let mut columns: Vec<String> = Vec::new();
+    for item in items {
+        if let Some(object) = item.as_object() {
+            for key in object.keys() {
+                if !columns.iter().any(|column| column == key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+
+    let rows = items
+        .iter()
+        .map(|item| {
+            columns
+                .iter()
+                .map(|column| item.get(column).map(cell_text).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    Some(JsonTable { columns, rows })
+}
+
+impl JsonTable {
+    /// Sorts rows by a column, comparing numerically when both cells are numbers.
+    pub fn sort_by(&mut self, column: usize, descending: bool) {
+        if column >= self.columns.len() {
+            return;
+        }
+        self.rows.sort_by(|a, b| {
+            let ordering = compare_cells(&a[column], &b[column]);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    /// Renders the visible columns as CSV, header row included.
+    pub fn to_csv(&self, visible: &[usize]) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = visible
+            .iter()
+            .filter_map(|idx| self.columns.get(*idx))
+            .map(|column| csv_escape(column))
+            .collect();
+        out.push_str(&header.join(","));
+        out.push('\n');
+        for row in &self.rows {
+            let cells: Vec<String> = visible
+                .iter()
+                .filter_map(|idx| row.get(*idx))
+                .map(|cell| csv_escape(cell))
+                .collect();
+            out.push_str(&cells.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders one row as tab-separated `column=value` pairs.
+    pub fn row_text(&self, row: usize, visible: &[usize]) -> Option<String> {
+        let row = self.rows.get(row)?;
+        let parts: Vec<String> = visible
+            .iter()
+            .filter_map(|idx| Some(format!("{}={}", self.columns.get(*idx)?, row.get(*idx)?)))
+            .collect();
+        Some(parts.join("\t"))
+    }
+}
+
+/// Flattens a JSON value into tree rows, descending only into expanded paths.
+pub fn flatten_tree(value: &Value, expanded: &HashSet<String>) -> Vec<TreeRow> {
+    let mut rows = Vec::new();
+    push_tree_rows(value, "", "$", 0, expanded, &mut rows);
+    rows
+}
+
+/// Resolves a tree row path back to its JSON value.
+pub fn value_at<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    value.pointer(path)
+}
+
+/// Text used when copying a value: strings raw, everything else as JSON.
+pub fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn push_tree_rows(
+    value: &Value,
+    path: &str,
+    label: &str,
+    depth: usize,
+    expanded: &HashSet<String>,
+    rows: &mut Vec<TreeRow>,
+) {
+    let expandable = match value {
+        Value::Object(map) => !map.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        _ => false,
+    };
+    let is_expanded = expandable && (path.is_empty() || expanded.contains(path));
+    rows.push(TreeRow {
+        path: path.to_string(),
+        depth,
+        label: label.to_string(),
+        summary: summarize(value),
+        expandable,
+        expanded: is_expanded,
+    });
+    if !is_expanded {
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = format!("{}/{}", path, escape_pointer(key));
+                push_tree_rows(child, &child_path, key, depth + 1, expanded, rows);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, child) in items.iter().enumerate() {
+                let child_path = format!("{}/{}", path, idx);
+                let child_label = format!("[{}]", idx);
+                push_tree_rows(child, &child_path, &child_label, depth + 1, expanded, rows);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn summarize(value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!("{{{} keys}}", map.len()),
+        Value::Array(items) => format!("[{} items]", items.len()),
+        Value::String(text) => format!("\"{}\"", text),
+        other => other.to_string(),
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(left), Ok(right)) => left.partial_cmp(&right).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_output_rejects_scalars_and_text() {
+        assert!(parse_json_output("42").is_none());
+        assert!(parse_json_output("hello").is_none());
+        assert!(parse_json_output("{ broken").is_none());
+        assert!(parse_json_output("  [1, 2]\n").is_some());
+    }
+
+    #[test]
+    fn test_parse_json_output_skips_directives() {
+        let stdout = "::group Listing\n[{\"name\": \"a\"}]\n::warning slow region\n::endgroup\n";
+        let value = parse_json_output(stdout).unwrap();
+        assert_eq!(value[0]["name"], "a");
+    }
+
+    #[test]
+    fn test_table_from_value_unions_columns() {
+        let value: Value =
+            serde_json::from_str(r#"[{"name": "a", "size": 2}, {"name": "b", "region": "eu"}]"#)
+                .unwrap();
+        let table = table_from_value(&value).unwrap();
+        assert_eq!(table.columns, vec!["name", "size", "region"]);
+        assert_eq!(table.rows[1], vec!["b", "", "eu"]);
+    }
+
+    #[test]
+    fn test_table_from_value_requires_objects() {
+        let value: Value = serde_json::from_str(r#"[1, {"a": 1}]"#).unwrap();
+        assert!(table_from_value(&value).is_none());
+    }
+
+    #[test]
+    fn test_sort_by_numeric_column() {
+        let value: Value = serde_json::from_str(r#"[{"n": 10}, {"n": 9}, {"n": 100}]"#).unwrap();
+        let mut table = table_from_value(&value).unwrap();
+        table.sort_by(0, false);
+        assert_eq!(table.rows, vec![vec!["9"], vec!["10"], vec!["100"]]);
+        table.sort_by(0, true);
+        assert_eq!(table.rows[0], vec!["100"]);
+    }
+
+    #[test]
+    fn test_to_csv_quotes_special_values() {
+        let value: Value =
+            serde_json::from_str(r#"[{"name": "a,b", "note": "say \"hi\""}]"#).unwrap();
+        let table = table_from_value(&value).unwrap();
+        let csv = table.to_csv(&[0, 1]);
+        assert_eq!(csv, "name,note\n\"a,b\",\"say \"\"hi\"\"\"\n");
+    }
+
+    #[test]
+    fn test_flatten_tree_respects_expanded_paths() {
+        let value: Value = serde_json::from_str(r#"{"a": {"b": 1}, "c": [true]}"#).unwrap();
+        let rows = flatten_tree(&value, &HashSet::new());
+        let labels: Vec<&str> = rows.iter().map(|row| row.label.as_str()).collect();
+        assert_eq!(labels, vec!["$", "a", "c"]);
+
+        let mut expanded = HashSet::new();
+        expanded.insert("/a".to_string());
+        let rows = flatten_tree(&value, &expanded);
+        let paths: Vec<&str> = rows.iter().map(|row| row.path.as_str()).collect();
+        assert_eq!(paths, vec!["", "/a", "/a/b", "/c"]);
+        assert_eq!(value_at(&value, "/a/b"), Some(&Value::from(1)));
+    }
+}
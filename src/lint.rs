@@ -0,0 +1,592 @@
+use crate::adapters::workspace_repository::{
+    format_of, load_field_library, sidecar_owner, sidecar_schema_paths, stray_sidecars,
+    FsWorkspaceRepository,
+};
+use crate::domain::{
+    has_field_refs, locate_schema_block, schema_issues, spec_violations, FieldLibrary, Schema,
+    SchemaFormat, SchemaIssue, Severity,
+};
+use crate::error::SchemaError;
+use crate::ports::ScriptRepository;
+use crate::runtime::{schema_comment_prefixes, script_kind, ScriptKind};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One lint finding, located at `script:line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// Script path relative to the workspace root.
+    pub script: PathBuf,
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.script.display(), self.line)
+    }
+}
+
+/// Lints every script in the workspace, errors first, then by location.
+pub fn lint_workspace(root: &Path) -> io::Result<Vec<Problem>> {
+    let repo = FsWorkspaceRepository::new(root.to_path_buf());
+    let mut scripts = repo.list_scripts_recursive()?;
+    scripts.sort();
+
+    let (library, library_errors) = load_field_library(root);
+    let mut problems: Vec<Problem> = library_errors
+        .into_iter()
+        .map(|(path, message)| Problem {
+            script: path.strip_prefix(root).unwrap_or(&path).to_path_buf(),
+            line: 1,
+            severity: Severity::Error,
+            message: format!("Invalid field library: {}", message),
+        })
+        .collect();
+    for sidecar in stray_sidecars(root)? {
+        let owner = sidecar_owner(&sidecar).unwrap_or_default();
+        let owner_name = owner.file_name().unwrap_or_default().to_string_lossy();
+        let message = if owner.is_file() {
+            format!(
+                "Sidecar schema is ignored: {} is neither a script nor an executable file, so it is never listed or run",
+                owner_name
+            )
+        } else {
+            format!(
+                "Sidecar schema is ignored: there is no {} next to it",
+                owner_name
+            )
+        };
+        problems.push(Problem {
+            script: sidecar.strip_prefix(root).unwrap_or(&sidecar).to_path_buf(),
+            line: 1,
+            severity: Severity::Warning,
+            message,
+        });
+    }
+    let mut names: BTreeMap<String, Vec<(PathBuf, usize)>> = BTreeMap::new();
+    for script in &scripts {
+        let sidecars = sidecar_schema_paths(script);
+        let source = sidecars.first().unwrap_or(script);
+        let relative = source.strip_prefix(root).unwrap_or(source).to_path_buf();
+        let problem = |line: usize, severity: Severity, message: String| Problem {
+            script: relative.clone(),
+            line,
+            severity,
+            message,
+        };
+
+        if let Some(sidecar) = sidecars.first() {
+            problems.extend(sidecar_conflicts(root, script, sidecar, &sidecars[1..]));
+        }
+        let contents = match fs::read_to_string(source) {
+            Ok(contents) => contents,
+            Err(err) => {
+                problems.push(problem(1, Severity::Error, err.to_string()));
+                continue;
+            }
+        };
+        let parsed = if sidecars.is_empty() {
+            read_schema(&repo, &library, script, &contents)
+        } else {
+            let lines: Vec<usize> = (1..=contents.lines().count()).collect();
+            parse_located(&contents, format_of(source), &library, &lines, 1)
+                .map(|schema| (schema, 1))
+        };
+        let (schema, search_from) = match parsed {
+            Ok(found) => found,
+            Err((line, message)) => {
+                problems.push(problem(line, Severity::Error, message));
+                continue;
+            }
+        };
+
+        for issue in schema_issues(&schema) {
+            let line = locate(&contents, search_from, &issue);
+            problems.push(problem(line, issue.severity, issue.message));
+        }
+        let name_line = find_line(&contents, search_from, &format!("\"{}\"", schema.name), 0)
+            .unwrap_or(search_from.max(1));
+        names
+            .entry(schema.name.to_lowercase())
+            .or_default()
+            .push((relative, name_line));
+    }
+
+    for scripts in names.values().filter(|scripts| scripts.len() > 1) {
+        for (script, line) in scripts {
+            let others: Vec<String> = scripts
+                .iter()
+                .filter(|(other, _)| other != script)
+                .map(|(other, _)| other.display().to_string())
+                .collect();
+            problems.push(Problem {
+                script: script.clone(),
+                line: *line,
+                severity: Severity::Warning,
+                message: format!("Schema name is also used by {}", others.join(", ")),
+            });
+        }
+    }
+
+    problems.sort_by(|a, b| (a.severity, &a.script, a.line).cmp(&(b.severity, &b.script, b.line)));
+    Ok(problems)
+}
+
+/// Checks a script's schema document, as written, against the schema format: unknown
+/// properties, wrong value types, missing required properties and invalid `Type` values.
+/// Messages start with the JSON pointer of the offending value.
+pub fn check_schema_strict(root: &Path, script: &Path) -> Vec<Problem> {
+    let repo = FsWorkspaceRepository::new(root.to_path_buf());
+    let sidecar = sidecar_schema_paths(script).into_iter().next();
+    let source = sidecar.as_deref().unwrap_or(script);
+    let contents = fs::read_to_string(source).unwrap_or_default();
+    let start = match (&sidecar, script_kind(script)) {
+        (None, Some(kind)) if kind != ScriptKind::Lua => {
+            locate_schema_block(&contents, schema_comment_prefixes(kind))
+                .map(|block| block.start_line)
+                .unwrap_or(1)
+        }
+        _ => 1,
+    };
+    let problem = |line: usize, message: String| Problem {
+        script: source.strip_prefix(root).unwrap_or(source).to_path_buf(),
+        line,
+        severity: Severity::Error,
+        message,
+    };
+
+    match repo.schema_document(script) {
+        Ok(document) => spec_violations(&document)
+            .into_iter()
+            .map(|violation| {
+                let line = pointer_line(&contents, start, &violation.pointer);
+                let pointer = if violation.pointer.is_empty() {
+                    "/"
+                } else {
+                    &violation.pointer
+                };
+                problem(line, format!("{}: {}", pointer, violation.message))
+            })
+            .collect(),
+        Err(err) => vec![problem(start, err.to_string())],
+    }
+}
+
+/// Best guess at the line holding the value a JSON pointer names: the first line from `from`
+/// mentioning its last property name, quoted (JSON, TOML values) or as a key (YAML, TOML).
+fn pointer_line(contents: &str, from: usize, pointer: &str) -> usize {
+    let Some(key) = pointer
+        .rsplit('/')
+        .find(|segment| !segment.is_empty() && !segment.chars().all(|ch| ch.is_ascii_digit()))
+    else {
+        return from.max(1);
+    };
+    let key = key.replace("~1", "/").replace("~0", "~");
+    [
+        format!("\"{}\"", key),
+        format!("{}:", key),
+        format!("{} =", key),
+    ]
+    .iter()
+    .find_map(|needle| find_line(contents, from, needle, 0))
+    .unwrap_or(from.max(1))
+}
+
+/// Parses the schema, returning it with the line where its source starts, or the line
+/// and message of the parse failure.
+fn read_schema(
+    repo: &FsWorkspaceRepository,
+    library: &FieldLibrary,
+    script: &Path,
+    contents: &str,
+) -> Result<(Schema, usize), (usize, String)> {
+    let kind = script_kind(script).ok_or((1, "Unsupported script type".to_string()))?;
+    if kind == ScriptKind::Lua {
+        return repo
+            .read_schema(script)
+            .map(|schema| (schema, 1))
+            .map_err(|err| {
+                let message = err.to_string();
+                (lua_error_line(&message).unwrap_or(1), message)
+            });
+    }
+
+    let block = locate_schema_block(contents, schema_comment_prefixes(kind)).map_err(|err| {
+        let line = match &err {
+            SchemaError::MissingCommentPrefix { line } => *line,
+            _ => find_line(contents, 1, "OMAKURE_SCHEMA_START", 0).unwrap_or(1),
+        };
+        (line, err.to_string())
+    })?;
+    parse_located(
+        &block.text,
+        block.format,
+        library,
+        &block.lines,
+        block.start_line,
+    )
+    .map(|schema| (schema, block.start_line))
+}
+
+/// Parses schema `text`, expanding `$ref` fields; `lines` holds the file line of each text
+/// line, so an error points at the right place. Failures without a position land on `fallback`.
+fn parse_located(
+    text: &str,
+    format: SchemaFormat,
+    library: &FieldLibrary,
+    lines: &[usize],
+    fallback: usize,
+) -> Result<Schema, (usize, String)> {
+    let mut document: Value = deserialize_located(text, format, lines, fallback)?;
+    if !has_field_refs(&document) {
+        return deserialize_located(text, format, lines, fallback);
+    }
+    library.resolve(&mut document).map_err(|err| {
+        let line = match &err {
+            SchemaError::UnknownFieldRef { reference } => text
+                .lines()
+                .position(|line| line.contains(reference.as_str()))
+                .and_then(|index| lines.get(index).copied()),
+            _ => None,
+        };
+        (line.unwrap_or(fallback), err.to_string())
+    })?;
+    serde_json::from_value(document)
+        .map_err(|err| (fallback, SchemaError::Invalid(err.to_string()).to_string()))
+}
+
+fn deserialize_located<T: DeserializeOwned>(
+    text: &str,
+    format: SchemaFormat,
+    lines: &[usize],
+    fallback: usize,
+) -> Result<T, (usize, String)> {
+    let file_line = |index: Option<usize>| {
+        index
+            .and_then(|index| lines.get(index).copied())
+            .unwrap_or(fallback)
+    };
+    match format {
+        SchemaFormat::Json => {
+            let Some(start) = text.find('{') else {
+                return Err((fallback, SchemaError::JsonNotFound.to_string()));
+            };
+            let mut deserializer = serde_json::Deserializer::from_str(&text[start..]);
+            T::deserialize(&mut deserializer).map_err(|err| {
+                let offset = text[..start].matches('\n').count();
+                let line = file_line(Some(offset + err.line().saturating_sub(1)));
+                // serde_json appends a position relative to the block; the file line replaces it.
+                let message = err.to_string();
+                let message = message.split(" at line ").next().unwrap_or(&message);
+                (line, format!("Invalid JSON in schema: {}", message))
+            })
+        }
+        SchemaFormat::Toml => toml::from_str(text).map_err(|err| {
+            let index = err
+                .span()
+                .map(|span| text[..span.start].matches('\n').count());
+            (
+                file_line(index),
+                format!("Invalid TOML in schema: {}", err.message()),
+            )
+        }),
+        SchemaFormat::Yaml => serde_yaml::from_str(text).map_err(|err| {
+            let index = err
+                .location()
+                .map(|location| location.line().saturating_sub(1));
+            let message = err.to_string();
+            let message = message.split(" at line ").next().unwrap_or(&message);
+            (
+                file_line(index),
+                format!("Invalid YAML in schema: {}", message),
+            )
+        }),
+    }
+}
+
+/// Warnings for schema sources that lose to `sidecar`: an embedded block, and other sidecars.
+fn sidecar_conflicts(
+    root: &Path,
+    script: &Path,
+    sidecar: &Path,
+    ignored: &[PathBuf],
+) -> Vec<Problem> {
+    let relative = |path: &Path| path.strip_prefix(root).unwrap_or(path).to_path_buf();
+    let winner = sidecar
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut problems: Vec<Problem> = ignored
+        .iter()
+        .map(|path| Problem {
+            script: relative(path),
+            line: 1,
+            severity: Severity::Warning,
+            message: format!("Sidecar schema is ignored; {} takes precedence", winner),
+        })
+        .collect();
+
+    let embedded = script_kind(script)
+        .filter(|kind| !matches!(kind, ScriptKind::Lua | ScriptKind::Executable))
+        .and_then(|kind| {
+            let contents = fs::read_to_string(script).ok()?;
+            match locate_schema_block(&contents, schema_comment_prefixes(kind)) {
+                Err(SchemaError::BlockNotFound) => None,
+                Ok(block) => Some(block.start_line),
+                Err(_) => find_line(&contents, 1, "OMAKURE_SCHEMA_START", 0),
+            }
+        });
+    if let Some(line) = embedded {
+        problems.push(Problem {
+            script: relative(script),
+            line,
+            severity: Severity::Warning,
+            message: format!(
+                "Schema block is ignored; sidecar {} takes precedence",
+                winner
+            ),
+        });
+    }
+    problems
+}
+
+fn locate(contents: &str, from: usize, issue: &SchemaIssue) -> usize {
+    issue
+        .anchor
+        .as_ref()
+        .and_then(|(text, occurrence)| {
+            let name = text.trim_matches('"');
+            find_line(contents, from, text, *occurrence)
+                .or_else(|| {
+                    // YAML usually leaves names unquoted: `- Name: x`.
+                    let bare = format!(": {}", name);
+                    contents
+                        .lines()
+                        .enumerate()
+                        .skip(from.saturating_sub(1))
+                        .filter(|(_, line)| line.trim_end().ends_with(&bare))
+                        .nth(*occurrence)
+                        .map(|(index, _)| index + 1)
+                })
+                // A field pulled in with `$ref` is named after the reference's last segment.
+                .or_else(|| find_line(contents, from, &format!("/{}\"", name), *occurrence))
+                // Fewer matches than expected (e.g. names differing in case): the first one.
+                .or_else(|| find_line(contents, from, text, 0))
+        })
+        .unwrap_or(from.max(1))
+}
+
+/// 1-based line of the `occurrence`-th match of `needle`, starting at line `from`. A line
+/// holding several matches (a schema written on one line) counts each of them.
+fn find_line(contents: &str, from: usize, needle: &str, occurrence: usize) -> Option<usize> {
+    contents
+        .lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .flat_map(|(index, line)| std::iter::repeat_n(index + 1, line.matches(needle).count()))
+        .nth(occurrence)
+}
+
+/// Pulls the line out of a Lua error such as `[string "x.lua"]:12: unexpected symbol`.
+fn lua_error_line(message: &str) -> Option<usize> {
+    let (_, rest) = message.split_once("\"]:")?;
+    let digits: String = rest.chars().take_while(|ch| ch.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_workspace_locates_problems() {
+        let root = std::env::temp_dir().join(format!("omakure-lint-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("a.bash"),
+            "#!/bin/bash\n# OMAKURE_SCHEMA_START\n# {\n#   \"Name\": \"same\",\n#   \"Fields\": [\n#     {\"Name\": \"x\", \"Type\": \"string\", \"Order\": 1},\n#     {\"Name\": \"x\", \"Type\": \"string\", \"Order\": 2}\n#   ]\n# }\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("b.sh"),
+            "# OMAKURE_SCHEMA_START\n# {\"Name\": \"same\", \"Fields\": []}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("c.py"),
+            "# OMAKURE_SCHEMA_START\n# {\n#   \"Name\": \"broken\",\n#   \"Fields\": [,]\n# }\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("d.bash"),
+            "# OMAKURE_SCHEMA_START\n{\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(root.join("e.bash"), "echo no schema\n").unwrap();
+        fs::write(
+            root.join("f.sh"),
+            "#!/bin/bash\n# OMAKURE_SCHEMA_START\n# {\"Name\": \"f\", \"Fields\": [{\"Name\": \"a\", \"Type\": \"string\", \"Order\": 1}, {\"Name\": \"a\", \"Type\": \"string\", \"Order\": 2}]}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+
+        let problems = lint_workspace(&root).unwrap();
+        let summary: Vec<String> = problems
+            .iter()
+            .map(|problem| {
+                format!(
+                    "{} {} {}",
+                    problem.location(),
+                    problem.severity.label(),
+                    problem.message
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "a.bash:7 error Duplicate field name `x`",
+                "c.py:4 error Invalid JSON in schema: expected value",
+                "d.bash:2 error Schema block line missing comment prefix at line 2",
+                "e.bash:1 error Schema block not found in script",
+                "f.sh:3 error Duplicate field name `a`",
+                "a.bash:4 warning Schema name is also used by b.sh",
+                "b.sh:2 warning Schema name is also used by a.bash",
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_lint_sidecars_and_toml_blocks() {
+        let root =
+            std::env::temp_dir().join(format!("omakure-lint-sidecar-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("a.bash"),
+            "#!/bin/bash\n# OMAKURE_SCHEMA_START\n# {\"Name\": \"a\", \"Fields\": []}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("a.bash.omakure.yaml"),
+            "Name: a\nFields:\n  - Name: x\n    Type: string\n    Order: 1\n  - Name: y\n    Type: string\n    Order: 1\n",
+        )
+        .unwrap();
+        fs::write(root.join("a.bash.omakure.yml"), "Name: other\n").unwrap();
+        fs::write(root.join("tool.omakure.json"), "{\"Name\": \"tool\"}").unwrap();
+        fs::write(
+            root.join("b.py"),
+            "# OMAKURE_SCHEMA_START toml\n# Name = \"b\"\n# Fields = [\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+
+        let summary: Vec<String> = lint_workspace(&root)
+            .unwrap()
+            .iter()
+            .map(|problem| format!("{} {}", problem.location(), problem.severity.label()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "b.py:3 error",
+                "a.bash:2 warning",
+                "a.bash.omakure.yaml:6 warning",
+                "a.bash.omakure.yml:1 warning",
+                "tool.omakure.json:1 warning",
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_lint_resolves_field_refs() {
+        let root = std::env::temp_dir().join(format!("omakure-lint-refs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join(".omaken/azure")).unwrap();
+        fs::create_dir_all(root.join("fields")).unwrap();
+        fs::write(
+            root.join(".omaken/azure/fields.json"),
+            r#"{"region": {"Type": "string", "Order": 1, "Choices": ["eu"], "Default": "us"}}"#,
+        )
+        .unwrap();
+        fs::write(root.join("fields/broken.yaml"), "- not a map\n").unwrap();
+        fs::write(
+            root.join("a.bash"),
+            "# OMAKURE_SCHEMA_START\n# {\"Name\": \"a\", \"Fields\": [\n#   {\"$ref\": \"azure/region\", \"Default\": \"eu\"},\n#   {\"$ref\": \"azure/zone\"}\n# ]}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("b.bash"),
+            "# OMAKURE_SCHEMA_START\n# {\"Name\": \"b\", \"Fields\": [\n#   {\"$ref\": \"azure/region\"}\n# ]}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+
+        let summary: Vec<String> = lint_workspace(&root)
+            .unwrap()
+            .iter()
+            .map(|problem| format!("{} {}", problem.location(), problem.message))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "a.bash:4 Unknown field reference `azure/zone`",
+                "fields/broken.yaml:1 Invalid field library: Field library must map field keys to field definitions",
+                "b.bash:3 Default `us` of field `region` is not one of its Choices (eu)",
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_check_schema_strict_points_at_violations() {
+        let root = std::env::temp_dir().join(format!("omakure-lint-strict-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("a.bash"),
+            "#!/bin/bash\n# OMAKURE_SCHEMA_START\n# {\n#   \"Name\": \"a\",\n#   \"Fields\": [\n#     {\"Name\": \"x\", \"Type\": \"text\", \"Order\": 1,\n#      \"Requried\": true}\n#   ]\n# }\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(root.join("b.py"), "print(1)\n").unwrap();
+        fs::write(
+            root.join("b.py.omakure.yaml"),
+            "Name: b\nTags: deploy\nFields: []\n",
+        )
+        .unwrap();
+
+        let summary = |script: &str| -> Vec<String> {
+            check_schema_strict(&root, &root.join(script))
+                .iter()
+                .map(|problem| format!("{} {}", problem.location(), problem.message))
+                .collect()
+        };
+        assert_eq!(
+            summary("a.bash"),
+            vec![
+                "a.bash:7 /Fields/0/Requried: Unknown property `Requried` in Field; did you mean `Required`?",
+                "a.bash:6 /Fields/0/Type: `text` is not one of: string, number, bool, boolean",
+            ]
+        );
+        assert_eq!(
+            summary("b.py"),
+            vec!["b.py.omakure.yaml:2 /Tags: Expected an array, found a string"]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_lua_error_line() {
+        assert_eq!(
+            lua_error_line("Lua error: syntax error: [string \"/ws/x.lua\"]:12: unexpected"),
+            Some(12)
+        );
+        assert_eq!(lua_error_line("Lua error: out of memory"), None);
+    }
+}
@@ -0,0 +1,559 @@
+use crate::history;
+use crate::workspace::Workspace;
+use mlua::{HookTriggers, Lua, LuaOptions, StdLib, Table, Value};
+use serde_json::{Map, Number};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_EXEC_TIMEOUT_SECS: f64 = 10.0;
+const DEFAULT_HISTORY_LIMIT: usize = 10;
+const DEFAULT_MEMORY_LIMIT_MB: usize = 32;
+const DEFAULT_TIMEOUT_SECS: f64 = 5.0;
+const DEFAULT_SCRIPT_TIMEOUT_SECS: f64 = 600.0;
+const HOOK_INSTRUCTION_INTERVAL: u32 = 1_000;
+const SAFE_OS_FUNCTIONS: [&str; 4] = ["clock", "date", "difftime", "time"];
+
+/// Workspace state exposed to Lua through the `omakure` module.
+#[derive(Debug, Clone)]
+pub struct LuaContext {
+    pub workspace_root: PathBuf,
+    pub env: HashMap<String, String>,
+    pub sandbox: SandboxPolicy,
+}
+
+/// Limits applied to a Lua state before any user code runs.
+#[derive(Debug, Clone)]
+pub struct SandboxPolicy {
+    pub memory_limit: usize,
+    pub timeout: Duration,
+    /// Set when the code comes from a flavor that is not trusted to run commands or read
+    /// workspace files and history.
+    pub untrusted_flavor: Option<String>,
+}
+
+impl SandboxPolicy {
+    /// Reads limits and `trusted_flavors` from omakure.toml for code living in `dir`.
+    pub fn for_dir(workspace: &Workspace, dir: &Path) -> Self {
+        let config = workspace.load_config().lua;
+        let untrusted_flavor = workspace
+            .flavor_of(dir)
+            .filter(|flavor| !config.trusted_flavors.contains(flavor));
+        Self {
+            memory_limit: config.memory_limit_mb.unwrap_or(DEFAULT_MEMORY_LIMIT_MB) * 1024 * 1024,
+            timeout: Duration::from_secs_f64(
+                config.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS).max(0.1),
+            ),
+            untrusted_flavor,
+        }
+    }
+
+    /// Like [`SandboxPolicy::for_dir`], but with the longer `script_timeout_secs` limit.
+    pub fn for_script(workspace: &Workspace, dir: &Path) -> Self {
+        let timeout = workspace
+            .load_config()
+            .lua
+            .script_timeout_secs
+            .unwrap_or(DEFAULT_SCRIPT_TIMEOUT_SECS)
+            .max(0.1);
+        Self {
+            timeout: Duration::from_secs_f64(timeout),
+            ..Self::for_dir(workspace, dir)
+        }
+    }
+}
+
+/// Creates a Lua state with only safe libraries, memory and time limits, and the `omakure` module.
+pub fn sandboxed_lua(context: &LuaContext) -> mlua::Result<Lua> {
+    let lua = Lua::new_with(
+        StdLib::COROUTINE
+            | StdLib::TABLE
+            | StdLib::STRING
+            | StdLib::UTF8
+            | StdLib::MATH
+            | StdLib::OS,
+        LuaOptions::default(),
+    )?;
+    restrict_globals(&lua)?;
+    lua.set_memory_limit(context.sandbox.memory_limit)?;
+    let timeout = context.sandbox.timeout;
+    let deadline = Instant::now() + timeout;
+    lua.set_hook(
+        HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTION_INTERVAL),
+        move |_, _| {
+            if Instant::now() > deadline {
+                return Err(mlua::Error::RuntimeError(format!(
+                    "timed out after {:.1}s",
+                    timeout.as_secs_f64()
+                )));
+            }
+            Ok(())
+        },
+    );
+
+    install_omakure_module(&lua, context, deadline)?;
+    Ok(lua)
+}
+
+/// Prepares a state for reading a script's `schema`: top-level code still runs, but
+/// `omakure.exec` raises an error and `print` is silent, so indexing, previews and lint
+/// never run commands or write to the terminal.
+pub fn without_side_effects(lua: &Lua) -> mlua::Result<()> {
+    let globals = lua.globals();
+    let module: Table = globals.get("omakure")?;
+    module.set(
+        "exec",
+        lua.create_function(|_, _: mlua::MultiValue| -> mlua::Result<()> {
+            Err(mlua::Error::RuntimeError(
+                "omakure.exec can only be called from main(), not while the schema is read"
+                    .to_string(),
+            ))
+        })?,
+    )?;
+    globals.set(
+        "print",
+        lua.create_function(|_, _: mlua::MultiValue| Ok(()))?,
+    )
+}
+
+/// Keeps only the harmless parts of `os` and replaces loaders and I/O with sandbox errors.
+fn restrict_globals(lua: &Lua) -> mlua::Result<()> {
+    let globals = lua.globals();
+    let full_os: Table = globals.get("os")?;
+    let os = blocked_library(lua, "os")?;
+    for name in SAFE_OS_FUNCTIONS {
+        os.raw_set(name, full_os.get::<_, Value>(name)?)?;
+    }
+    globals.set("os", os)?;
+    for name in ["io", "package", "debug"] {
+        globals.set(name, blocked_library(lua, name)?)?;
+    }
+    for name in ["require", "dofile", "loadfile", "load"] {
+        globals.set(name, blocked_function(lua, name)?)?;
+    }
+    Ok(())
+}
+
+/// Turns Lua failures into messages that name the sandbox limit that was hit.
+pub fn describe_error(err: &mlua::Error, sandbox: &SandboxPolicy) -> String {
+    match err {
+        mlua::Error::MemoryError(_) => format!(
+            "Lua error: memory limit of {} MiB exceeded",
+            sandbox.memory_limit / (1024 * 1024)
+        ),
+        other => format!("Lua error: {}", other),
+    }
+}
+
+/// A table whose every missing key raises a sandbox error instead of returning nil.
+fn blocked_library<'lua>(lua: &'lua Lua, name: &'static str) -> mlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    let metatable = lua.create_table()?;
+    metatable.set(
+        "__index",
+        lua.create_function(move |_, (_, key): (Value, String)| -> mlua::Result<()> {
+            Err(mlua::Error::RuntimeError(format!(
+                "{}.{} is not available in the Lua sandbox",
+                name, key
+            )))
+        })?,
+    )?;
+    table.set_metatable(Some(metatable));
+    Ok(table)
+}
+
+fn blocked_function<'lua>(
+    lua: &'lua Lua,
+    name: &'static str,
+) -> mlua::Result<mlua::Function<'lua>> {
+    lua.create_function(move |_, _: mlua::MultiValue| -> mlua::Result<()> {
+        Err(mlua::Error::RuntimeError(format!(
+            "{} is not available in the Lua sandbox",
+            name
+        )))
+    })
+}
+
+/// Registers the `omakure` global table.
+fn install_omakure_module(lua: &Lua, context: &LuaContext, deadline: Instant) -> mlua::Result<()> {
+    let module = lua.create_table()?;
+
+    let untrusted_flavor = context.sandbox.untrusted_flavor.clone();
+    module.set(
+        "exec",
+        lua.create_function(
+            move |_, (cmd, args, options): (String, Option<Vec<String>>, Option<Table>)| {
+                require_trusted(&untrusted_flavor, "exec")?;
+                let timeout = options
+                    .map(|options| options.get::<_, Option<f64>>("timeout"))
+                    .transpose()?
+                    .flatten()
+                    .unwrap_or(DEFAULT_EXEC_TIMEOUT_SECS)
+                    .min(
+                        deadline
+                            .saturating_duration_since(Instant::now())
+                            .as_secs_f64(),
+                    );
+                exec(&cmd, &args.unwrap_or_default(), timeout).map_err(mlua::Error::external)
+            },
+        )?,
+    )?;
+
+    let json = lua.create_table()?;
+    json.set(
+        "decode",
+        lua.create_function(|lua, text: String| {
+            let value: serde_json::Value =
+                serde_json::from_str(&text).map_err(mlua::Error::external)?;
+            json_to_lua(lua, &value)
+        })?,
+    )?;
+    json.set(
+        "encode",
+        lua.create_function(|_, value: Value| {
+            let json = lua_to_json(&value, 0)?;
+            serde_json::to_string(&json).map_err(mlua::Error::external)
+        })?,
+    )?;
+    module.set("json", json)?;
+
+    let env = context.env.clone();
+    module.set(
+        "env",
+        lua.create_function(move |lua, ()| lua.create_table_from(env.clone()))?,
+    )?;
+
+    let root = context.workspace_root.clone();
+    let untrusted_flavor = context.sandbox.untrusted_flavor.clone();
+    module.set(
+        "read_file",
+        lua.create_function(move |_, path: String| {
+            require_trusted(&untrusted_flavor, "read_file")?;
+            let resolved = resolve_in_workspace(&root, &path).map_err(mlua::Error::external)?;
+            fs::read_to_string(&resolved)
+                .map_err(|err| mlua::Error::external(format!("Failed to read {}: {}", path, err)))
+        })?,
+    )?;
+
+    let root = context.workspace_root.clone();
+    let untrusted_flavor = context.sandbox.untrusted_flavor.clone();
+    module.set(
+        "history",
+        lua.create_function(
+            move |lua, (script, limit): (Option<String>, Option<usize>)| {
+                require_trusted(&untrusted_flavor, "history")?;
+                history_table(lua, &root, script.as_deref(), limit)
+            },
+        )?,
+    )?;
+
+    lua.globals().set("omakure", module)
+}
+
+/// Fails when the calling code comes from a flavor that isn't in `[lua] trusted_flavors`.
+fn require_trusted(untrusted_flavor: &Option<String>, function: &str) -> mlua::Result<()> {
+    match untrusted_flavor {
+        Some(flavor) => Err(mlua::Error::RuntimeError(format!(
+            "omakure.{} is not allowed for flavor '{}'; add it to [lua] trusted_flavors in omakure.toml",
+            function, flavor
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Runs a command, returning `stdout, stderr, exit_code`; kills it after `timeout` seconds.
+fn exec(cmd: &str, args: &[String], timeout: f64) -> Result<(String, String, Option<i32>), String> {
+    let mut child = Command::new(cmd)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run {}: {}", cmd, err))?;
+
+    let stdout = child.stdout.take().map(spawn_reader);
+    let stderr = child.stderr.take().map(spawn_reader);
+    let deadline = Instant::now() + Duration::from_secs_f64(timeout.max(0.0));
+    let status = loop {
+        match child.try_wait().map_err(|err| err.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out after {}s", cmd, timeout));
+            }
+            None => thread::sleep(Duration::from_millis(20)),
+        }
+    };
+
+    let collect = |handle: Option<thread::JoinHandle<Vec<u8>>>| {
+        handle
+            .and_then(|handle| handle.join().ok())
+            .map(|bytes| String::from_utf8_lossy(&bytes).to_string())
+            .unwrap_or_default()
+    };
+    Ok((collect(stdout), collect(stderr), status.code()))
+}
+
+fn spawn_reader<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.read_to_end(&mut buffer);
+        buffer
+    })
+}
+
+/// Resolves `path` against the workspace root and rejects anything outside it.
+fn resolve_in_workspace(root: &Path, path: &str) -> Result<PathBuf, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|err| format!("Workspace not accessible: {}", err))?;
+    let candidate = root
+        .join(path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to read {}: {}", path, err))?;
+    if !candidate.starts_with(&root) {
+        return Err(format!("{} is outside the workspace", path));
+    }
+    Ok(candidate)
+}
+
+fn history_table<'lua>(
+    lua: &'lua Lua,
+    root: &Path,
+    script: Option<&str>,
+    limit: Option<usize>,
+) -> mlua::Result<Table<'lua>> {
+    let workspace = Workspace::new(root.to_path_buf());
+    let entries = history::load_entries(&workspace).map_err(mlua::Error::external)?;
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let table = lua.create_table()?;
+    let matching = entries
+        .iter()
+        .filter(|entry| script.is_none_or(|script| history_matches(entry, script)))
+        .take(limit);
+    for (idx, entry) in matching.enumerate() {
+        let item = lua.create_table()?;
+        item.set("script", entry.script.to_string_lossy().to_string())?;
+        item.set("timestamp", entry.timestamp)?;
+        item.set("time", history::format_timestamp(entry.timestamp))?;
+        item.set("success", entry.success)?;
+        item.set("exit_code", entry.exit_code)?;
+        item.set("args", entry.args.clone())?;
+        item.set("error", entry.error.clone())?;
+        item.set("notes", entry.notes.clone())?;
+        table.set(idx + 1, item)?;
+    }
+    Ok(table)
+}
+
+/// Matches a history entry by relative path, with or without the extension.
+fn history_matches(entry: &history::HistoryEntry, script: &str) -> bool {
+    let path = entry.script.to_string_lossy().replace('\\', "/");
+    let script = script.replace('\\', "/");
+    path == script
+        || entry
+            .script
+            .with_extension("")
+            .to_string_lossy()
+            .replace('\\', "/")
+            == script
+}
+
+fn json_to_lua<'lua>(lua: &'lua Lua, value: &serde_json::Value) -> mlua::Result<Value<'lua>> {
+    Ok(match value {
+        serde_json::Value::Null => Value::Nil,
+        serde_json::Value::Bool(flag) => Value::Boolean(*flag),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(int) => Value::Integer(int),
+            None => Value::Number(number.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(text) => Value::String(lua.create_string(text)?),
+        serde_json::Value::Array(items) => {
+            let table = lua.create_table()?;
+            for (idx, item) in items.iter().enumerate() {
+                table.set(idx + 1, json_to_lua(lua, item)?)?;
+            }
+            Value::Table(table)
+        }
+        serde_json::Value::Object(map) => {
+            let table = lua.create_table()?;
+            for (key, item) in map {
+                table.set(key.as_str(), json_to_lua(lua, item)?)?;
+            }
+            Value::Table(table)
+        }
+    })
+}
+
+/// Tables with a non-empty sequence part encode as arrays, everything else as objects.
+pub fn lua_to_json(value: &Value, depth: usize) -> mlua::Result<serde_json::Value> {
+    if depth > 64 {
+        return Err(mlua::Error::external("json.encode: nesting too deep"));
+    }
+    Ok(match value {
+        Value::Nil => serde_json::Value::Null,
+        Value::Boolean(flag) => serde_json::Value::Bool(*flag),
+        Value::Integer(int) => serde_json::Value::from(*int),
+        Value::Number(number) => Number::from_f64(*number)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Value::String(text) => serde_json::Value::String(text.to_str()?.to_string()),
+        Value::Table(table) if table.raw_len() > 0 => {
+            let mut items = Vec::new();
+            for item in table.clone().sequence_values::<Value>() {
+                items.push(lua_to_json(&item?, depth + 1)?);
+            }
+            serde_json::Value::Array(items)
+        }
+        Value::Table(table) => {
+            let mut map = Map::new();
+            for pair in table.clone().pairs::<Value, Value>() {
+                let (key, item) = pair?;
+                let key = match key {
+                    Value::String(text) => text.to_str()?.to_string(),
+                    Value::Integer(int) => int.to_string(),
+                    other => {
+                        return Err(mlua::Error::external(format!(
+                            "json.encode: unsupported key type {}",
+                            other.type_name()
+                        )))
+                    }
+                };
+                map.insert(key, lua_to_json(&item, depth + 1)?);
+            }
+            serde_json::Value::Object(map)
+        }
+        other => {
+            return Err(mlua::Error::external(format!(
+                "json.encode: unsupported value type {}",
+                other.type_name()
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(root: &Path) -> LuaContext {
+        LuaContext {
+            workspace_root: root.to_path_buf(),
+            env: HashMap::from([("subscription".to_string(), "dev".to_string())]),
+            sandbox: SandboxPolicy {
+                memory_limit: 8 * 1024 * 1024,
+                timeout: Duration::from_millis(300),
+                untrusted_flavor: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_sandbox_blocks_unsafe_libraries() {
+        let lua = sandboxed_lua(&context(&std::env::temp_dir())).unwrap();
+        let err = lua.load(r#"os.execute("true")"#).exec().unwrap_err();
+        assert!(err.to_string().contains("os.execute is not available"));
+        let err = lua.load(r#"require("socket")"#).exec().unwrap_err();
+        assert!(err.to_string().contains("require is not available"));
+        let time: i64 = lua.load("return os.time()").eval().unwrap();
+        assert!(time > 0);
+    }
+
+    #[test]
+    fn test_sandbox_enforces_limits() {
+        let sandbox_context = context(&std::env::temp_dir());
+        let lua = sandboxed_lua(&sandbox_context).unwrap();
+        let err = lua.load("while true do end").exec().unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+
+        let lua = sandboxed_lua(&sandbox_context).unwrap();
+        let err = lua
+            .load("local t = {} for i = 1, 1e8 do t[i] = string.rep('x', 64) .. i end")
+            .exec()
+            .unwrap_err();
+        assert!(describe_error(&err, &sandbox_context.sandbox).contains("memory limit"));
+    }
+
+    #[test]
+    fn test_untrusted_flavor_cannot_exec_or_read() {
+        let mut untrusted = context(&std::env::temp_dir());
+        untrusted.sandbox.untrusted_flavor = Some("azure".to_string());
+        let lua = sandboxed_lua(&untrusted).unwrap();
+        for call in [
+            r#"omakure.exec("echo", {"hi"})"#,
+            r#"omakure.read_file("omakure.toml")"#,
+            "omakure.history()",
+        ] {
+            let err = lua.load(call).exec().unwrap_err();
+            assert!(err.to_string().contains("trusted_flavors"), "{}", call);
+        }
+        let env: String = lua
+            .load("return omakure.env().subscription")
+            .eval()
+            .unwrap();
+        assert_eq!(env, "dev");
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let lua = sandboxed_lua(&context(&std::env::temp_dir())).unwrap();
+        let encoded: String = lua
+            .load(r#"return omakure.json.encode(omakure.json.decode('{"a":[1,2],"b":"x"}'))"#)
+            .eval()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&encoded).unwrap();
+        assert_eq!(value, serde_json::json!({"a": [1, 2], "b": "x"}));
+    }
+
+    #[test]
+    fn test_env_returns_active_values() {
+        let lua = sandboxed_lua(&context(&std::env::temp_dir())).unwrap();
+        let value: String = lua
+            .load("return omakure.env().subscription")
+            .eval()
+            .unwrap();
+        assert_eq!(value, "dev");
+    }
+
+    #[test]
+    fn test_read_file_rejects_paths_outside_workspace() {
+        let root = std::env::temp_dir().join(format!("omakure-lua-api-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("note.txt"), "hello").unwrap();
+        let lua = sandboxed_lua(&context(&root)).unwrap();
+
+        let text: String = lua
+            .load(r#"return omakure.read_file("note.txt")"#)
+            .eval()
+            .unwrap();
+        assert_eq!(text, "hello");
+        let err = lua
+            .load(r#"return omakure.read_file("../")"#)
+            .eval::<String>()
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the workspace"));
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_returns_output_and_enforces_timeout() {
+        let lua = sandboxed_lua(&context(&std::env::temp_dir())).unwrap();
+        let (stdout, code): (String, i64) = lua
+            .load(r#"local out, _, code = omakure.exec("sh", {"-c", "echo hi; exit 3"}); return out, code"#)
+            .eval()
+            .unwrap();
+        assert_eq!(stdout, "hi\n");
+        assert_eq!(code, 3);
+        let err = lua
+            .load(r#"return omakure.exec("sleep", {"5"}, {timeout = 0.1})"#)
+            .exec()
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+}
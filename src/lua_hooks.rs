@@ -0,0 +1,502 @@
+use crate::domain::PromptRequest;
+use crate::error::{AppResult, ScriptError};
+use crate::lua_api::{describe_error, sandboxed_lua, LuaContext, SandboxPolicy};
+use crate::ports::{RunObserver, ScriptRunOutput};
+use crate::runtime::script_extensions;
+use crate::use_cases::ScriptService;
+use crate::workspace::Workspace;
+use mlua::{Function, Lua, Table, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const HOOKS_FILE: &str = "hooks.lua";
+/// Prompt id used when a `before_run` hook asks for confirmation.
+pub const CONFIRM_PROMPT_ID: &str = "confirm";
+
+/// `hooks.lua` files that apply to one script, from the workspace root down to its folder.
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    workspace_root: PathBuf,
+    env: HashMap<String, String>,
+    files: Vec<(PathBuf, SandboxPolicy)>,
+}
+
+/// Arguments and environment after every `before_run` hook had its say.
+#[derive(Debug, Clone, Default)]
+pub struct PreparedRun {
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub confirm: Vec<String>,
+}
+
+/// Follow-up work requested by `after_run` hooks.
+#[derive(Debug, Clone, Default)]
+pub struct AfterRun {
+    pub notes: Vec<String>,
+    pub triggers: Vec<Trigger>,
+}
+
+/// Another script to run once the current one finishes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigger {
+    pub script: PathBuf,
+    pub args: Vec<String>,
+}
+
+/// Outcome of a run wrapped in hooks; `args` are the ones the script actually received.
+#[derive(Debug)]
+pub struct HookedRun {
+    pub args: Vec<String>,
+    pub result: AppResult<ScriptRunOutput>,
+    pub notes: Vec<String>,
+    pub triggers: Vec<Trigger>,
+}
+
+impl Hooks {
+    pub fn discover(workspace: &Workspace, script: &Path, env: HashMap<String, String>) -> Self {
+        let root = workspace.root();
+        let mut dirs = vec![root.to_path_buf()];
+        if let Some(relative) = script.parent().and_then(|dir| dir.strip_prefix(root).ok()) {
+            let mut dir = root.to_path_buf();
+            for component in relative.components() {
+                dir.push(component);
+                dirs.push(dir.clone());
+            }
+        }
+        let files = dirs
+            .into_iter()
+            .filter_map(|dir| {
+                let path = dir.join(HOOKS_FILE);
+                path.is_file()
+                    .then(|| (path, SandboxPolicy::for_dir(workspace, &dir)))
+            })
+            .collect();
+        Self {
+            workspace_root: root.to_path_buf(),
+            env,
+            files,
+        }
+    }
+
+    /// Runs every `before_run(ctx)`; a `veto` stops at the first hook that returns one.
+    pub fn before_run(&self, script: &Path, args: &[String]) -> Result<PreparedRun, ScriptError> {
+        let script = self.relative(script);
+        let mut prepared = PreparedRun {
+            args: args.to_vec(),
+            ..PreparedRun::default()
+        };
+        for (file, sandbox) in &self.files {
+            let outcome = self
+                .call(file, sandbox, "before_run", |lua, hook| {
+                    let ctx = context_table(lua, &script, &prepared, &self.env)?;
+                    let decision: Value = hook.call(ctx.clone())?;
+                    let args: Vec<String> = ctx.get("args")?;
+                    let env: BTreeMap<String, String> = ctx.get("env")?;
+                    let (veto, confirm) = match decision {
+                        Value::Nil => (None, None),
+                        Value::Table(decision) => (decision.get("veto")?, decision.get("confirm")?),
+                        other => {
+                            return Err(mlua::Error::RuntimeError(format!(
+                                "before_run must return nil or a table, got {}",
+                                other.type_name()
+                            )))
+                        }
+                    };
+                    Ok((args, env, veto, confirm))
+                })
+                .map_err(|message| ScriptError::HookFailed { message })?;
+            let Some((args, env, veto, confirm)) = outcome else {
+                continue;
+            };
+            if let Some(reason) = veto {
+                return Err(ScriptError::BlockedByHook { reason });
+            }
+            prepared.args = args;
+            prepared.env = env.into_iter().collect();
+            prepared.confirm.extend(confirm);
+        }
+        Ok(prepared)
+    }
+
+    /// Runs every `after_run(ctx, result)`; hook failures are kept as notes.
+    pub fn after_run(
+        &self,
+        script: &Path,
+        prepared: &PreparedRun,
+        result: &AppResult<ScriptRunOutput>,
+        duration: Duration,
+    ) -> AfterRun {
+        let script = self.relative(script);
+        let mut after = AfterRun::default();
+        for (file, sandbox) in &self.files {
+            let outcome = self.call(file, sandbox, "after_run", |lua, hook| {
+                let ctx = context_table(lua, &script, prepared, &self.env)?;
+                let summary = result_table(lua, result, duration)?;
+                match hook.call::<_, Value>((ctx, summary))? {
+                    Value::Table(reply) => {
+                        let note: Option<String> = reply.get("note")?;
+                        let run = match reply.get::<_, Option<Table>>("run")? {
+                            Some(run) => Some((
+                                run.get::<_, String>("script")?,
+                                run.get::<_, Option<Vec<String>>>("args")?
+                                    .unwrap_or_default(),
+                            )),
+                            None => None,
+                        };
+                        Ok((note, run))
+                    }
+                    _ => Ok((None, None)),
+                }
+            });
+            match outcome {
+                Ok(Some((note, run))) => {
+                    after.notes.extend(note);
+                    if let Some((script, args)) = run {
+                        match self.resolve_script(&script) {
+                            Ok(script) => after.triggers.push(Trigger { script, args }),
+                            Err(message) => after.notes.push(format!("after_run: {}", message)),
+                        }
+                    }
+                }
+                Ok(None) => {}
+                Err(message) => after.notes.push(format!("after_run failed: {}", message)),
+            }
+        }
+        after
+    }
+
+    /// Loads one hooks file and calls `name` if it defines it, as a global or in a returned table.
+    fn call<R>(
+        &self,
+        file: &Path,
+        sandbox: &SandboxPolicy,
+        name: &str,
+        call: impl FnOnce(&Lua, Function) -> mlua::Result<R>,
+    ) -> Result<Option<R>, String> {
+        let label = file
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(file)
+            .display()
+            .to_string();
+        let source =
+            fs::read_to_string(file).map_err(|err| format!("Failed to read {}: {}", label, err))?;
+        let context = LuaContext {
+            workspace_root: self.workspace_root.clone(),
+            env: self.env.clone(),
+            sandbox: sandbox.clone(),
+        };
+        let run = || -> mlua::Result<Option<R>> {
+            let lua = sandboxed_lua(&context)?;
+            let exports = lua.load(&source).set_name(&label).eval::<Value>()?;
+            let hook = match exports {
+                Value::Table(table) => table.get::<_, Option<Function>>(name)?,
+                _ => lua.globals().get::<_, Option<Function>>(name)?,
+            };
+            hook.map(|hook| call(&lua, hook)).transpose()
+        };
+        run().map_err(|err| format!("{}: {}", label, describe_error(&err, sandbox)))
+    }
+
+    fn relative(&self, script: &Path) -> String {
+        script
+            .strip_prefix(&self.workspace_root)
+            .unwrap_or(script)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Resolves a workspace-relative script, with or without its extension. Paths that end up
+    /// outside the workspace (`..`, absolute paths, symlinks) are refused.
+    fn resolve_script(&self, script: &str) -> Result<PathBuf, String> {
+        let path = self.workspace_root.join(script);
+        let found = if path.is_file() {
+            Some(path)
+        } else {
+            script_extensions()
+                .iter()
+                .map(|ext| path.with_extension(ext))
+                .find(|candidate| candidate.is_file())
+        };
+        let found = found.ok_or_else(|| format!("script not found: {}", script))?;
+        let inside = match (found.canonicalize(), self.workspace_root.canonicalize()) {
+            (Ok(resolved), Ok(root)) => resolved.starts_with(root),
+            _ => false,
+        };
+        if !inside {
+            return Err(format!("script is outside the workspace: {}", script));
+        }
+        Ok(found)
+    }
+}
+
+/// Runs a script through its hooks: `before_run`, an optional confirmation, the run, `after_run`.
+///
+/// Confirmation is asked through `observer.on_prompt` as a bool prompt with id `confirm`.
+pub fn run_with_hooks(
+    service: &ScriptService,
+    hooks: &Hooks,
+    script: &Path,
+    args: &[String],
+    stdin: Option<&str>,
+    observer: &mut dyn RunObserver,
+) -> HookedRun {
+    let prepared = match hooks.before_run(script, args) {
+        Ok(prepared) => prepared,
+        Err(err) => {
+            return HookedRun {
+                args: args.to_vec(),
+                result: Err(err.into()),
+                notes: Vec::new(),
+                triggers: Vec::new(),
+            }
+        }
+    };
+    if !prepared.confirm.is_empty() {
+        let message = prepared.confirm.join("; ");
+        let prompt = PromptRequest {
+            id: CONFIRM_PROMPT_ID.to_string(),
+            kind: "bool".to_string(),
+            text: Some(message.clone()),
+            default: None,
+            choices: None,
+            required: true,
+        };
+        if observer.on_prompt(&prompt).as_deref() != Some("true") {
+            return HookedRun {
+                args: prepared.args,
+                result: Err(ScriptError::NotConfirmed { message }.into()),
+                notes: Vec::new(),
+                triggers: Vec::new(),
+            };
+        }
+    }
+
+    let started = Instant::now();
+    let result = service.run_script(script, &prepared.args, stdin, &prepared.env, &mut *observer);
+    let after = hooks.after_run(script, &prepared, &result, started.elapsed());
+    HookedRun {
+        args: prepared.args,
+        result,
+        notes: after.notes,
+        triggers: after.triggers,
+    }
+}
+
+/// `ctx.env` holds the variables hooks set; reading a key they didn't set falls back to the
+/// active environment, whose values aren't passed to the script.
+fn context_table<'lua>(
+    lua: &'lua Lua,
+    script: &str,
+    prepared: &PreparedRun,
+    active_env: &HashMap<String, String>,
+) -> mlua::Result<Table<'lua>> {
+    let ctx = lua.create_table()?;
+    ctx.set("script", script)?;
+    ctx.set("args", prepared.args.clone())?;
+    let env = lua.create_table()?;
+    for (key, value) in &prepared.env {
+        env.set(key.as_str(), value.as_str())?;
+    }
+    let active = lua.create_table()?;
+    for (key, value) in active_env {
+        active.set(key.as_str(), value.as_str())?;
+    }
+    let meta = lua.create_table()?;
+    meta.set("__index", active)?;
+    env.set_metatable(Some(meta));
+    ctx.set("env", env)?;
+    Ok(ctx)
+}
+
+fn result_table<'lua>(
+    lua: &'lua Lua,
+    result: &AppResult<ScriptRunOutput>,
+    duration: Duration,
+) -> mlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    table.set("duration", duration.as_secs_f64())?;
+    match result {
+        Ok(output) => {
+            table.set("success", output.success)?;
+            table.set("exit_code", output.exit_code)?;
+            table.set("stdout", output.stdout.as_str())?;
+            table.set("stderr", output.stderr.as_str())?;
+        }
+        Err(err) => {
+            table.set("success", false)?;
+            table.set("error", err.to_string())?;
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace_with_hooks(name: &str, hooks: &[(&str, &str)]) -> Workspace {
+        let root =
+            std::env::temp_dir().join(format!("omakure-hooks-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("team")).unwrap();
+        fs::write(root.join("team/deploy.bash"), "echo hi\n").unwrap();
+        for (dir, source) in hooks {
+            fs::write(root.join(dir).join(HOOKS_FILE), source).unwrap();
+        }
+        Workspace::new(root)
+    }
+
+    #[test]
+    fn test_before_run_chains_args_env_and_confirm() {
+        let workspace = workspace_with_hooks(
+            "chain",
+            &[
+                (
+                    ".",
+                    r#"function before_run(ctx)
+                         table.insert(ctx.args, "--tag")
+                         table.insert(ctx.args, "auto")
+                         ctx.env.DEPLOY_USER = "ci-" .. ctx.env.subscription
+                       end"#,
+                ),
+                (
+                    "team",
+                    r#"return {
+                         before_run = function(ctx)
+                           ctx.env.TARGET = ctx.args[#ctx.args]
+                           return { confirm = "Deploy " .. ctx.script .. "?" }
+                         end,
+                       }"#,
+                ),
+            ],
+        );
+        let script = workspace.root().join("team/deploy.bash");
+        let active = HashMap::from([("subscription".to_string(), "prod".to_string())]);
+        let hooks = Hooks::discover(&workspace, &script, active);
+        let prepared = hooks.before_run(&script, &["--name".to_string()]).unwrap();
+        assert_eq!(prepared.args, vec!["--name", "--tag", "auto"]);
+        assert_eq!(
+            prepared.env,
+            vec![
+                ("DEPLOY_USER".to_string(), "ci-prod".to_string()),
+                ("TARGET".to_string(), "auto".to_string()),
+            ]
+        );
+        assert_eq!(prepared.confirm, vec!["Deploy team/deploy.bash?"]);
+        let _ = fs::remove_dir_all(workspace.root());
+    }
+
+    #[test]
+    fn test_before_run_veto_blocks() {
+        let workspace = workspace_with_hooks(
+            "veto",
+            &[(
+                "team",
+                r#"function before_run(ctx) return { veto = "outside change window" } end"#,
+            )],
+        );
+        let script = workspace.root().join("team/deploy.bash");
+        let hooks = Hooks::discover(&workspace, &script, HashMap::new());
+        let err = hooks.before_run(&script, &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Run blocked by hook: outside change window"
+        );
+        let _ = fs::remove_dir_all(workspace.root());
+    }
+
+    #[test]
+    fn test_after_run_notes_and_triggers() {
+        let workspace = workspace_with_hooks(
+            "after",
+            &[(
+                ".",
+                r#"function after_run(ctx, result)
+                     if not result.success then error("boom") end
+                     return {
+                       note = "exit " .. result.exit_code,
+                       run = { script = "team/deploy", args = { "--verify" } },
+                     }
+                   end"#,
+            )],
+        );
+        let script = workspace.root().join("team/deploy.bash");
+        let hooks = Hooks::discover(&workspace, &script, HashMap::new());
+        let output = ScriptRunOutput {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_code: Some(0),
+            success: true,
+            annotations: Vec::new(),
+        };
+        let after = hooks.after_run(
+            &script,
+            &PreparedRun::default(),
+            &Ok(output),
+            Duration::from_millis(5),
+        );
+        assert_eq!(after.notes, vec!["exit 0"]);
+        assert_eq!(
+            after.triggers,
+            vec![Trigger {
+                script: script.clone(),
+                args: vec!["--verify".to_string()],
+            }]
+        );
+
+        let failed = hooks.after_run(
+            &script,
+            &PreparedRun::default(),
+            &Err("crashed".into()),
+            Duration::ZERO,
+        );
+        assert!(failed.notes[0].starts_with("after_run failed: hooks.lua: Lua error"));
+        let _ = fs::remove_dir_all(workspace.root());
+    }
+
+    #[test]
+    fn test_after_run_refuses_scripts_outside_the_workspace() {
+        let outside_dir = std::env::temp_dir().join(format!(
+            "omakure-hooks-escape-target-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&outside_dir).unwrap();
+        fs::write(outside_dir.join("target.bash"), "echo escaped\n").unwrap();
+        let target = format!(
+            "../{}/target.bash",
+            outside_dir.file_name().unwrap().to_string_lossy()
+        );
+        let workspace = workspace_with_hooks(
+            "escape",
+            &[(
+                ".",
+                &format!(
+                    r#"function after_run(ctx, result)
+                         return {{ run = {{ script = "{}" }} }}
+                       end"#,
+                    target
+                ),
+            )],
+        );
+        let script = workspace.root().join("team/deploy.bash");
+        let hooks = Hooks::discover(&workspace, &script, HashMap::new());
+        let after = hooks.after_run(
+            &script,
+            &PreparedRun::default(),
+            &Err("crashed".into()),
+            Duration::ZERO,
+        );
+        assert!(after.triggers.is_empty());
+        assert_eq!(
+            after.notes,
+            vec![format!(
+                "after_run: script is outside the workspace: {}",
+                target
+            )]
+        );
+        let _ = fs::remove_dir_all(outside_dir);
+        let _ = fs::remove_dir_all(workspace.root());
+    }
+}
@@ -0,0 +1,379 @@
+use crate::adapters::workspace_repository::load_field_library;
+use crate::domain::{has_field_refs, parse_output_line, Field, OutputLine, Schema};
+use crate::error::{AppResult, SchemaError};
+use crate::lua_api::{
+    describe_error, lua_to_json, sandboxed_lua, without_side_effects, LuaContext,
+};
+use crate::ports::{RunObserver, ScriptRunOutput};
+use mlua::{Function, Lua, Table, Value, Variadic};
+use std::fs;
+use std::path::Path;
+
+/// Evaluates a `.lua` script without side effects and reads its `schema` table.
+pub fn read_schema(script: &Path, context: &LuaContext) -> AppResult<Schema> {
+    let source = fs::read_to_string(script)?;
+    let lua = schema_lua(context)?;
+    let exports = load(&lua, script, &source, context)?;
+    schema_from(&exports, &context.workspace_root)
+}
+
+/// The `schema` table as written, converted to JSON, with `$ref` fields left as they are.
+pub fn read_schema_document(script: &Path, context: &LuaContext) -> AppResult<serde_json::Value> {
+    let source = fs::read_to_string(script)?;
+    let lua = schema_lua(context)?;
+    let exports = load(&lua, script, &source, context)?;
+    schema_document(&exports)
+}
+
+/// A sandboxed state where `omakure.exec` fails and `print` does nothing.
+fn schema_lua(context: &LuaContext) -> AppResult<Lua> {
+    let lua = sandboxed_lua(context).map_err(|err| describe_error(&err, &context.sandbox))?;
+    without_side_effects(&lua).map_err(|err| describe_error(&err, &context.sandbox))?;
+    Ok(lua)
+}
+
+/// Runs `main(args, stdin)` in-process; `print` output is streamed like a script's stdout.
+///
+/// A table returned by `main` is printed as JSON, a string as-is; raising an error fails the run.
+pub fn run_script(
+    script: &Path,
+    args: &[String],
+    stdin: Option<&str>,
+    context: &LuaContext,
+    observer: &mut dyn RunObserver,
+) -> AppResult<ScriptRunOutput> {
+    let source = fs::read_to_string(script)?;
+    let lua = sandboxed_lua(context).map_err(|err| describe_error(&err, &context.sandbox))?;
+    let exports = load(&lua, script, &source, context)?;
+    let schema = schema_from(&exports, &context.workspace_root)?;
+    let main: Function = exports
+        .get::<_, Option<Function>>("main")
+        .map_err(|err| describe_error(&err, &context.sandbox))?
+        .ok_or("Lua script must define a `main(args)` function")?;
+
+    let mut output = ScriptRunOutput {
+        stdout: String::new(),
+        stderr: String::new(),
+        exit_code: Some(0),
+        success: true,
+        annotations: Vec::new(),
+    };
+    let result = lua.scope(|scope| {
+        let print = scope.create_function_mut(|lua, values: Variadic<Value>| {
+            let tostring: Function = lua.globals().get("tostring")?;
+            let parts = values
+                .into_iter()
+                .map(|value| tostring.call::<_, String>(value))
+                .collect::<mlua::Result<Vec<_>>>()?;
+            emit(&mut output, observer, &parts.join("\t")).map_err(mlua::Error::RuntimeError)
+        })?;
+        lua.globals().set("print", print)?;
+        let args = args_table(&lua, &schema.fields, args)?;
+        main.call::<_, Value>((args, stdin))
+    });
+
+    match result {
+        Ok(Value::Nil) => {}
+        Ok(Value::Table(table)) => {
+            let json = lua_to_json(&Value::Table(table), 0)
+                .map_err(|err| describe_error(&err, &context.sandbox))?;
+            let text = serde_json::to_string_pretty(&json).unwrap_or_default();
+            for line in text.lines() {
+                emit(&mut output, observer, line)?;
+            }
+        }
+        Ok(value) => {
+            let text = lua
+                .coerce_string(value)
+                .ok()
+                .flatten()
+                .map(|text| text.to_string_lossy().to_string())
+                .unwrap_or_default();
+            for line in text.lines() {
+                emit(&mut output, observer, line)?;
+            }
+        }
+        Err(err) => {
+            output.stderr = format!("{}\n", describe_error(&err, &context.sandbox));
+            output.exit_code = Some(1);
+            output.success = false;
+        }
+    }
+    Ok(output)
+}
+
+fn load<'lua>(
+    lua: &'lua Lua,
+    script: &Path,
+    source: &str,
+    context: &LuaContext,
+) -> AppResult<Table<'lua>> {
+    let value = lua
+        .load(source)
+        .set_name(script.to_string_lossy().as_ref())
+        .eval::<Value>()
+        .map_err(|err| describe_error(&err, &context.sandbox))?;
+    match value {
+        Value::Table(table) => Ok(table),
+        _ => Ok(lua.globals()),
+    }
+}
+
+fn schema_document(exports: &Table) -> AppResult<serde_json::Value> {
+    let schema = exports
+        .get::<_, Value>("schema")
+        .map_err(|err| err.to_string())?;
+    if !matches!(schema, Value::Table(_)) {
+        return Err(SchemaError::BlockNotFound.into());
+    }
+    let mut json = lua_to_json(&schema, 0).map_err(|err| err.to_string())?;
+    empty_tables_as_lists(&mut json);
+    Ok(json)
+}
+
+fn schema_from(exports: &Table, workspace_root: &Path) -> AppResult<Schema> {
+    let mut json = schema_document(exports)?;
+    if has_field_refs(&json) {
+        load_field_library(workspace_root).0.resolve(&mut json)?;
+    }
+    Ok(serde_json::from_value(json).map_err(SchemaError::InvalidJson)?)
+}
+
+/// Lua can't tell `{}` apart from an empty list, so list-typed schema keys are fixed up.
+fn empty_tables_as_lists(value: &mut serde_json::Value) {
+    const LIST_KEYS: [&str; 6] = [
+        "Fields",
+        "Tags",
+        "Outputs",
+        "Requires",
+        "Choices",
+        "VersionArgs",
+    ];
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                if LIST_KEYS.contains(&key.as_str())
+                    && item.as_object().is_some_and(|o| o.is_empty())
+                {
+                    *item = serde_json::Value::Array(Vec::new());
+                } else {
+                    empty_tables_as_lists(item);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(empty_tables_as_lists),
+        _ => {}
+    }
+}
+
+/// Mirrors what the script runner does with a stdout line. Lua scripts have no stdin to read
+/// an answer from, so `::prompt` is an error rather than a request nobody answers.
+fn emit(
+    output: &mut ScriptRunOutput,
+    observer: &mut dyn RunObserver,
+    line: &str,
+) -> Result<(), String> {
+    let parsed = parse_output_line(line);
+    match &parsed {
+        OutputLine::Prompt(prompt) => {
+            return Err(format!(
+                "::prompt `{}` is not supported in Lua scripts; declare a field instead",
+                prompt.id
+            ));
+        }
+        OutputLine::Progress { .. } => {}
+        OutputLine::Annotation(annotation) => {
+            output.annotations.push(annotation.clone());
+            output.stdout.push_str(line);
+            output.stdout.push('\n');
+        }
+        _ => {
+            output.stdout.push_str(line);
+            output.stdout.push('\n');
+        }
+    }
+    observer.on_line(&parsed);
+    Ok(())
+}
+
+/// Maps `--flag value` pairs back to field names, typed by the field kind.
+/// Arguments that don't belong to a field stay in the list part of the table.
+fn args_table<'lua>(
+    lua: &'lua Lua,
+    fields: &[Field],
+    args: &[String],
+) -> mlua::Result<Table<'lua>> {
+    let table = lua.create_table()?;
+    let mut rest = Vec::new();
+    let mut index = 0;
+    while index < args.len() {
+        let arg = &args[index];
+        let field = fields.iter().find(|field| field.flag() == *arg);
+        match (field, args.get(index + 1)) {
+            (Some(field), _) if field.is_switch() => {
+                table.set(field.name.as_str(), true)?;
+                index += 1;
+            }
+            (Some(field), Some(value)) => {
+                table.set(field.name.as_str(), typed_value(lua, field, value)?)?;
+                index += 2;
+            }
+            _ => {
+                rest.push(arg.clone());
+                index += 1;
+            }
+        }
+    }
+    for (position, arg) in rest.into_iter().enumerate() {
+        table.set(position + 1, arg)?;
+    }
+    Ok(table)
+}
+
+fn typed_value<'lua>(lua: &'lua Lua, field: &Field, value: &str) -> mlua::Result<Value<'lua>> {
+    Ok(match field.kind.to_lowercase().as_str() {
+        "number" => match value.parse::<i64>() {
+            Ok(int) => Value::Integer(int),
+            Err(_) => value
+                .parse::<f64>()
+                .map(Value::Number)
+                .unwrap_or(Value::String(lua.create_string(value)?)),
+        },
+        "bool" | "boolean" => Value::Boolean(value == "true"),
+        _ => Value::String(lua.create_string(value)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::PromptRequest;
+    use crate::lua_api::SandboxPolicy;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    const SCRIPT: &str = r#"
+        return {
+          schema = {
+            Name = "greet",
+            Tags = {},
+            Fields = {
+              { Name = "name", Type = "string", Order = 1 },
+              { Name = "count", Type = "number", Order = 2, Arg = "-n" },
+              { Name = "loud", Type = "bool", Order = 3 },
+            },
+          },
+          main = function(args, stdin)
+            print("::progress 50")
+            print("hello", args.name, args.count + 1, args.loud, args[1])
+            print("::warning from " .. omakure.env().region)
+            if stdin then error("unexpected stdin") end
+            return { ok = true }
+          end,
+        }
+    "#;
+
+    struct Collect(Vec<OutputLine>);
+
+    impl RunObserver for Collect {
+        fn on_line(&mut self, line: &OutputLine) {
+            self.0.push(line.clone());
+        }
+
+        fn on_prompt(&mut self, _prompt: &PromptRequest) -> Option<String> {
+            None
+        }
+    }
+
+    fn write_script(name: &str, source: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "omakure-lua-script-{}-{}.lua",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn context() -> LuaContext {
+        LuaContext {
+            workspace_root: std::env::temp_dir(),
+            env: HashMap::from([("region".to_string(), "eastus".to_string())]),
+            sandbox: SandboxPolicy {
+                memory_limit: 8 * 1024 * 1024,
+                timeout: Duration::from_millis(500),
+                untrusted_flavor: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_read_schema_from_table() {
+        let path = write_script("schema", SCRIPT);
+        let schema = read_schema(&path, &context()).unwrap();
+        assert_eq!(schema.name, "greet");
+        assert_eq!(schema.tags, Some(Vec::new()));
+        assert_eq!(schema.fields.len(), 3);
+        assert_eq!(schema.fields[1].arg.as_deref(), Some("-n"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_run_script_prints_and_returns_json() {
+        let path = write_script("run", SCRIPT);
+        let args: Vec<String> = ["--name", "ada", "-n", "2", "--loud", "true", "extra"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let mut observer = Collect(Vec::new());
+        let output = run_script(&path, &args, None, &context(), &mut observer).unwrap();
+        assert!(output.success, "{}", output.stderr);
+        assert_eq!(
+            output.stdout,
+            "hello\tada\t3\ttrue\textra\n::warning from eastus\n{\n  \"ok\": true\n}\n"
+        );
+        assert_eq!(output.annotations.len(), 1);
+        assert_eq!(
+            observer.0[0],
+            OutputLine::Progress {
+                percent: 50,
+                message: None
+            }
+        );
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_reading_the_schema_has_no_side_effects() {
+        let source = format!("omakure.exec('true')\n{}", SCRIPT);
+        let path = write_script("side-effects", &source);
+        let err = read_schema(&path, &context()).unwrap_err().to_string();
+        assert!(
+            err.contains("omakure.exec can only be called from main()"),
+            "{}",
+            err
+        );
+        let _ = fs::remove_file(path);
+
+        let path = write_script(
+            "prompt",
+            "schema = { Name = 'ask', Fields = {} }\nfunction main() print('::prompt id=ok') end",
+        );
+        let mut observer = Collect(Vec::new());
+        let output = run_script(&path, &[], None, &context(), &mut observer).unwrap();
+        assert!(!output.success);
+        assert!(output.stderr.contains("not supported in Lua scripts"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_run_script_error_fails_run() {
+        let path = write_script("fail", SCRIPT);
+        let mut observer = Collect(Vec::new());
+        let output = run_script(&path, &[], Some("data"), &context(), &mut observer).unwrap();
+        assert!(!output.success);
+        assert_eq!(output.exit_code, Some(1));
+        assert!(output.stderr.contains("Lua error"));
+        let _ = fs::remove_file(path);
+    }
+}
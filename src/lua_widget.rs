@@ -1,14 +1,87 @@
+use crate::lua_api::{describe_error, sandboxed_lua, LuaContext};
 use mlua::{Lua, Table, Value};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
+
+/// Shortest interval accepted for `refresh`, so a typo can't spin the event loop.
+const MIN_REFRESH_SECS: f64 = 1.0;
 
 #[derive(Debug, Clone)]
 pub struct WidgetData {
     pub title: String,
-    pub lines: Vec<String>,
+    pub lines: Vec<WidgetLine>,
+    /// Re-run interval requested with `refresh = N` (seconds).
+    pub refresh: Option<Duration>,
+}
+
+/// One entry of the widget `lines` list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WidgetLine {
+    Text(Vec<WidgetSpan>),
+    KeyValue {
+        key: String,
+        value: Vec<WidgetSpan>,
+    },
+    Gauge {
+        label: Option<String>,
+        percent: u8,
+        color: Option<WidgetColor>,
+    },
+    Table {
+        header: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WidgetSpan {
+    pub text: String,
+    pub color: Option<WidgetColor>,
+    pub bold: bool,
+}
+
+/// Theme color tokens a widget can reference; resolved against the active theme when drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetColor {
+    Success,
+    Warning,
+    Error,
+    Info,
+    Accent,
+    Muted,
+    Secondary,
+}
+
+impl WidgetColor {
+    fn parse(token: &str) -> Result<Self, String> {
+        match token {
+            "success" => Ok(Self::Success),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            "info" => Ok(Self::Info),
+            "accent" => Ok(Self::Accent),
+            "muted" => Ok(Self::Muted),
+            "secondary" => Ok(Self::Secondary),
+            _ => Err(format!(
+                "Unknown widget color `{}` (use success, warning, error, info, accent, muted or secondary)",
+                token
+            )),
+        }
+    }
 }
 
-pub fn load_widget(dir: &Path) -> Result<Option<WidgetData>, String> {
+impl WidgetSpan {
+    fn plain(text: String) -> Self {
+        Self {
+            text,
+            color: None,
+            bold: false,
+        }
+    }
+}
+
+pub fn load_widget(dir: &Path, context: &LuaContext) -> Result<Option<WidgetData>, String> {
     let script_path = dir.join("index.lua");
     if !script_path.is_file() {
         return Ok(None);
@@ -16,15 +89,19 @@ pub fn load_widget(dir: &Path) -> Result<Option<WidgetData>, String> {
 
     let script = fs::read_to_string(&script_path)
         .map_err(|err| format!("Failed to read {}: {}", script_path.display(), err))?;
-    let lua = Lua::new();
+    let lua = sandboxed_lua(context).map_err(|err| describe_error(&err, &context.sandbox))?;
     let value = lua
         .load(&script)
         .set_name(script_path.to_string_lossy().as_ref())
         .eval::<Value>()
-        .map_err(|err| format!("Lua error: {}", err))?;
+        .map_err(|err| describe_error(&err, &context.sandbox))?;
+
+    read_widget(&lua, value).map(Some)
+}
 
+fn read_widget(lua: &Lua, value: Value) -> Result<WidgetData, String> {
     if let Value::Table(table) = value {
-        return Ok(Some(read_widget_table(table)?));
+        return read_widget_table(table);
     }
 
     let globals = lua.globals();
@@ -32,14 +109,19 @@ pub fn load_widget(dir: &Path) -> Result<Option<WidgetData>, String> {
         .get::<_, Option<Table>>("widget")
         .map_err(|err| err.to_string())?
     {
-        return Ok(Some(read_widget_table(table)?));
+        return read_widget_table(table);
     }
 
     let title: Option<String> = globals.get("title").map_err(|err| err.to_string())?;
     let lines_table: Option<Table> = globals.get("lines").map_err(|err| err.to_string())?;
     if let (Some(title), Some(lines_table)) = (title, lines_table) {
         let lines = read_lines_table(lines_table)?;
-        return Ok(Some(WidgetData { title, lines }));
+        let refresh = read_refresh(globals.get("refresh").map_err(|err| err.to_string())?)?;
+        return Ok(WidgetData {
+            title,
+            lines,
+            refresh,
+        });
     }
 
     Err("Lua widget must return a table with `title` and `lines`".to_string())
@@ -53,14 +135,214 @@ fn read_widget_table(table: Table) -> Result<WidgetData, String> {
         .get("lines")
         .map_err(|_| "Lua widget missing `lines`".to_string())?;
     let lines = read_lines_table(lines_table)?;
-    Ok(WidgetData { title, lines })
+    let refresh = read_refresh(table.get("refresh").map_err(|err| err.to_string())?)?;
+    Ok(WidgetData {
+        title,
+        lines,
+        refresh,
+    })
+}
+
+fn read_refresh(value: Option<f64>) -> Result<Option<Duration>, String> {
+    match value {
+        None => Ok(None),
+        Some(secs) if secs.is_finite() && secs > 0.0 => {
+            Ok(Some(Duration::from_secs_f64(secs.max(MIN_REFRESH_SECS))))
+        }
+        Some(secs) => Err(format!(
+            "Lua widget `refresh` must be a positive number of seconds, got {}",
+            secs
+        )),
+    }
 }
 
-fn read_lines_table(table: Table) -> Result<Vec<String>, String> {
+fn read_lines_table(table: Table) -> Result<Vec<WidgetLine>, String> {
     let mut lines = Vec::new();
-    for pair in table.sequence_values::<String>() {
-        let line = pair.map_err(|err| err.to_string())?;
+    for (index, value) in table.sequence_values::<Value>().enumerate() {
+        let value = value.map_err(|err| err.to_string())?;
+        let line = read_line(value).map_err(|err| format!("lines[{}]: {}", index + 1, err))?;
         lines.push(line);
     }
     Ok(lines)
 }
+
+fn read_line(value: Value) -> Result<WidgetLine, String> {
+    let table = match value {
+        Value::Table(table) => table,
+        other => return Ok(WidgetLine::Text(vec![read_span(other)?])),
+    };
+
+    if let Some(key) = get_field::<String>(&table, "key")? {
+        let value = match get_field::<Value>(&table, "value")? {
+            Some(value) => read_spans(value)?,
+            None => Vec::new(),
+        };
+        return Ok(WidgetLine::KeyValue { key, value });
+    }
+    if let Some(percent) = get_field::<f64>(&table, "gauge")? {
+        let color = get_field::<String>(&table, "color")?
+            .map(|token| WidgetColor::parse(&token))
+            .transpose()?;
+        return Ok(WidgetLine::Gauge {
+            label: get_field(&table, "label")?,
+            percent: percent.clamp(0.0, 100.0).round() as u8,
+            color,
+        });
+    }
+    if let Some(rows) = get_field::<Table>(&table, "table")? {
+        let header = get_field::<Table>(&table, "header")?
+            .map(read_cells)
+            .transpose()?;
+        let rows = rows
+            .sequence_values::<Table>()
+            .map(|row| row.map_err(|err| err.to_string()).and_then(read_cells))
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(WidgetLine::Table { header, rows });
+    }
+
+    Ok(WidgetLine::Text(read_spans(Value::Table(table))?))
+}
+
+/// Reads a string, a single `{ text = ..., color = ... }` span, or a list of either.
+fn read_spans(value: Value) -> Result<Vec<WidgetSpan>, String> {
+    match value {
+        Value::Table(table) if !table.contains_key("text").map_err(|err| err.to_string())? => table
+            .sequence_values::<Value>()
+            .map(|span| span.map_err(|err| err.to_string()).and_then(read_span))
+            .collect(),
+        other => Ok(vec![read_span(other)?]),
+    }
+}
+
+fn read_span(value: Value) -> Result<WidgetSpan, String> {
+    match value {
+        Value::String(text) => Ok(WidgetSpan::plain(text.to_string_lossy().to_string())),
+        Value::Integer(number) => Ok(WidgetSpan::plain(number.to_string())),
+        Value::Number(number) => Ok(WidgetSpan::plain(number.to_string())),
+        Value::Boolean(flag) => Ok(WidgetSpan::plain(flag.to_string())),
+        Value::Table(table) => {
+            let text: String =
+                get_field(&table, "text")?.ok_or_else(|| "span is missing `text`".to_string())?;
+            let color = get_field::<String>(&table, "color")?
+                .map(|token| WidgetColor::parse(&token))
+                .transpose()?;
+            let bold = get_field::<bool>(&table, "bold")?.unwrap_or(false);
+            Ok(WidgetSpan { text, color, bold })
+        }
+        other => Err(format!(
+            "expected a string or span, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+fn read_cells(table: Table) -> Result<Vec<String>, String> {
+    table
+        .sequence_values::<Value>()
+        .map(|cell| {
+            cell.map_err(|err| err.to_string())
+                .and_then(read_span)
+                .map(|span| span.text)
+        })
+        .collect()
+}
+
+fn get_field<'lua, T: mlua::FromLua<'lua>>(
+    table: &Table<'lua>,
+    key: &str,
+) -> Result<Option<T>, String> {
+    table
+        .get::<_, Option<T>>(key)
+        .map_err(|err| format!("invalid `{}`: {}", key, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lua_api::SandboxPolicy;
+    use std::collections::HashMap;
+
+    fn eval(source: &str) -> Result<WidgetData, String> {
+        let context = LuaContext {
+            workspace_root: std::env::temp_dir(),
+            env: HashMap::new(),
+            sandbox: SandboxPolicy {
+                memory_limit: 8 * 1024 * 1024,
+                timeout: Duration::from_millis(300),
+                untrusted_flavor: None,
+            },
+        };
+        let lua = sandboxed_lua(&context).unwrap();
+        let value = lua.load(source).eval::<Value>().unwrap();
+        read_widget(&lua, value)
+    }
+
+    #[test]
+    fn test_plain_string_lines_still_load() {
+        let widget = eval(r#"title = "Azure"; lines = { "rg-prod" }"#).unwrap();
+        assert_eq!(widget.title, "Azure");
+        assert_eq!(
+            widget.lines,
+            vec![WidgetLine::Text(vec![WidgetSpan::plain("rg-prod".into())])]
+        );
+        assert_eq!(widget.refresh, None);
+    }
+
+    #[test]
+    fn test_styled_lines() {
+        let widget = eval(
+            r#"return {
+                title = "Status",
+                refresh = 30,
+                lines = {
+                  { "API ", { text = "up", color = "success", bold = true } },
+                  { key = "Region", value = "eastus" },
+                  { gauge = 42.4, label = "Quota", color = "warning" },
+                  { header = { "Name", "State" }, table = { { "vm-1", "running" } } },
+                },
+              }"#,
+        )
+        .unwrap();
+        assert_eq!(widget.refresh, Some(Duration::from_secs(30)));
+        assert_eq!(
+            widget.lines[0],
+            WidgetLine::Text(vec![
+                WidgetSpan::plain("API ".into()),
+                WidgetSpan {
+                    text: "up".into(),
+                    color: Some(WidgetColor::Success),
+                    bold: true,
+                },
+            ])
+        );
+        assert_eq!(
+            widget.lines[1],
+            WidgetLine::KeyValue {
+                key: "Region".into(),
+                value: vec![WidgetSpan::plain("eastus".into())],
+            }
+        );
+        assert_eq!(
+            widget.lines[2],
+            WidgetLine::Gauge {
+                label: Some("Quota".into()),
+                percent: 42,
+                color: Some(WidgetColor::Warning),
+            }
+        );
+        assert_eq!(
+            widget.lines[3],
+            WidgetLine::Table {
+                header: Some(vec!["Name".into(), "State".into()]),
+                rows: vec![vec!["vm-1".into(), "running".into()]],
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_color_is_reported() {
+        let err = eval(r#"return { title = "x", lines = { { text = "a", color = "pink" } } }"#)
+            .unwrap_err();
+        assert!(err.starts_with("lines[1]: Unknown widget color `pink`"));
+    }
+}
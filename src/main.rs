@@ -1,12 +1,24 @@
 mod adapters;
+mod adopt;
 mod app_meta;
+mod arg_check;
 mod cli;
+mod codegen;
+mod command_stub;
 mod domain;
 mod error;
 mod history;
+mod json_output;
+mod lint;
+mod lua_api;
+mod lua_hooks;
+mod lua_script;
 mod lua_widget;
 mod ports;
+mod presets;
 mod runtime;
+mod script_template;
+mod script_test;
 mod search_index;
 mod theme_config;
 mod use_cases;
@@ -167,10 +179,17 @@ fn main() -> Result<(), Box<dyn Error>> {
         Some(Commands::List) => cli::omaken::run_list(scripts_dir)?,
         Some(Commands::Install(args)) => cli::omaken::run_install(scripts_dir, args)?,
         Some(Commands::Scripts) => cli::list::run(scripts_dir)?,
+        Some(Commands::Lint) => cli::lint::run(scripts_dir)?,
+        Some(Commands::CheckArgs(args)) => cli::check_args::run(scripts_dir, args)?,
+        Some(Commands::Adopt(args)) => cli::adopt::run(scripts_dir, args)?,
+        Some(Commands::Codegen(args)) => cli::codegen::run(scripts_dir, args)?,
+        Some(Commands::Test(args)) => cli::test::run(scripts_dir, args)?,
+        Some(Commands::Schema(args)) => cli::schema::run(scripts_dir, args)?,
         Some(Commands::Run(args)) => cli::run::run(scripts_dir, args)?,
         Some(Commands::Init(args)) => cli::init::run(scripts_dir, args)?,
         Some(Commands::Config) => cli::config::run(scripts_dir)?,
         Some(Commands::Theme(args)) => cli::theme::run(scripts_dir, args)?,
+        Some(Commands::Shim(args)) => cli::shim::run(args),
         Some(Commands::Completion(args)) => generate_completions(args.shell),
         None => run_tui(scripts_dir)?,
     }
@@ -183,7 +202,7 @@ fn run_tui(scripts_dir: PathBuf) -> Result<(), Box<dyn Error>> {
     workspace.ensure_layout()?;
 
     let repo = Box::new(FsWorkspaceRepository::new(scripts_dir));
-    let runner = Box::new(MultiScriptRunner::new());
+    let runner = Box::new(MultiScriptRunner::new(workspace.root()));
     let service = ScriptService::new(repo, runner);
 
     let mut terminal = tui::setup_terminal()?;
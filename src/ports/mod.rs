@@ -1,7 +1,7 @@
 mod environment;
 
-use crate::domain::Schema;
-use crate::error::AppResult;
+use crate::domain::{Annotation, OutputLine, PromptRequest, Requirement, Schema};
+use crate::error::{AppResult, ScriptError};
 use std::io;
 use std::path::{Path, PathBuf};
 
@@ -19,7 +19,7 @@ pub struct WorkspaceEntry {
     pub kind: WorkspaceEntryKind,
 }
 
-pub trait ScriptRepository {
+pub trait ScriptRepository: Send + Sync {
     fn list_entries(&self, dir: &Path) -> io::Result<Vec<WorkspaceEntry>>;
     fn list_scripts_recursive(&self) -> io::Result<Vec<PathBuf>>;
     fn read_schema(&self, script: &Path) -> AppResult<Schema>;
@@ -31,8 +31,34 @@ pub struct ScriptRunOutput {
     pub stderr: String,
     pub exit_code: Option<i32>,
     pub success: bool,
+    pub annotations: Vec<Annotation>,
 }
 
-pub trait ScriptRunner {
-    fn run(&self, script: &Path, args: &[String]) -> AppResult<ScriptRunOutput>;
+/// Receives events from a script while it runs.
+pub trait RunObserver {
+    /// Called for each stdout line as it is read.
+    fn on_line(&mut self, line: &OutputLine);
+
+    /// Answers a `::prompt` request; `None` stops the run.
+    fn on_prompt(&mut self, prompt: &PromptRequest) -> Option<String>;
+}
+
+pub trait ScriptRunner: Send + Sync {
+    /// Runs a script; `stdin` is piped in when given, and prompts can't be answered then.
+    /// Otherwise stdin stays open for `::prompt` answers when `prompts` is set, and is closed
+    /// from the start when it isn't. `env` adds or overrides variables in the inherited
+    /// environment.
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &self,
+        script: &Path,
+        args: &[String],
+        stdin: Option<&str>,
+        prompts: bool,
+        env: &[(String, String)],
+        observer: &mut dyn RunObserver,
+    ) -> AppResult<ScriptRunOutput>;
+
+    /// Checks declared prerequisites, returning one error per unmet requirement.
+    fn check_requirements(&self, requires: &[Requirement]) -> Vec<ScriptError>;
 }
@@ -0,0 +1,349 @@
+//! Named field values saved per script in `.omaken/presets/<script-id>.toml`.
+
+use crate::domain::{normalize_input, Field, Schema};
+use crate::runtime::stdin_payload;
+use crate::workspace::Workspace;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PresetFile {
+    #[serde(default, rename = "preset")]
+    pub presets: Vec<Preset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub name: String,
+    /// Environment file (in `.omaken/envs`) the preset belongs to; unscoped presets apply in
+    /// every environment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    /// Field values by field name. Secret fields are never stored.
+    #[serde(default)]
+    pub values: BTreeMap<String, String>,
+}
+
+impl Preset {
+    /// Captures form values, leaving out secret fields.
+    pub fn from_inputs(
+        name: &str,
+        env: Option<String>,
+        fields: &[Field],
+        inputs: &[String],
+    ) -> Self {
+        let values = fields
+            .iter()
+            .zip(inputs)
+            .filter(|(field, _)| !field.is_secret())
+            .map(|(field, input)| (field.name.clone(), input.clone()))
+            .collect();
+        Self {
+            name: name.to_string(),
+            env,
+            values,
+        }
+    }
+
+    /// Value saved for a field; secret fields never have one.
+    pub fn value_for(&self, field: &Field) -> Option<&str> {
+        if field.is_secret() {
+            return None;
+        }
+        self.values
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&field.name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Name with its environment, e.g. `cleanup (prod.conf)`.
+    pub fn label(&self) -> String {
+        match &self.env {
+            Some(env) => format!("{} ({})", self.name, env),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// File name for a script's presets: its workspace-relative path joined with dots
+/// (`azure/rg-create.sh` → `azure.rg-create.sh`).
+pub fn script_id(workspace: &Workspace, script: &Path) -> String {
+    let relative = script.strip_prefix(workspace.root()).unwrap_or(script);
+    let parts: Vec<String> = relative
+        .components()
+        .filter_map(|part| match part {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    parts.join(".").trim_start_matches('.').to_string()
+}
+
+pub fn presets_path(workspace: &Workspace, script: &Path) -> PathBuf {
+    workspace
+        .presets_dir()
+        .join(format!("{}.toml", script_id(workspace, script)))
+}
+
+/// All presets saved for a script, in file order; none when the file is missing.
+pub fn load_presets(workspace: &Workspace, script: &Path) -> Result<Vec<Preset>, String> {
+    let path = presets_path(workspace, script);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(format!("Failed to read {}: {}", path.display(), err)),
+    };
+    toml::from_str::<PresetFile>(&contents)
+        .map(|file| file.presets)
+        .map_err(|err| format!("Invalid presets file {}: {}", path.display(), err))
+}
+
+/// Presets usable with the active environment: unscoped ones and those scoped to it.
+pub fn available(presets: &[Preset], active_env: Option<&str>) -> Vec<Preset> {
+    presets
+        .iter()
+        .filter(|preset| preset.env.is_none() || preset.env.as_deref() == active_env)
+        .cloned()
+        .collect()
+}
+
+/// Looks a preset up by name, preferring the one scoped to the active environment.
+pub fn find_preset<'a>(
+    presets: &'a [Preset],
+    name: &str,
+    active_env: Option<&str>,
+) -> Option<&'a Preset> {
+    let named = |preset: &&Preset| preset.name == name;
+    presets
+        .iter()
+        .filter(named)
+        .find(|preset| preset.env.is_some() && preset.env.as_deref() == active_env)
+        .or_else(|| {
+            presets
+                .iter()
+                .filter(named)
+                .find(|preset| preset.env.is_none())
+        })
+}
+
+/// Adds a preset, replacing one with the same name and environment, and returns the file path.
+pub fn save_preset(
+    workspace: &Workspace,
+    script: &Path,
+    preset: Preset,
+) -> Result<PathBuf, String> {
+    let mut presets = load_presets(workspace, script)?;
+    match presets
+        .iter_mut()
+        .find(|saved| saved.name == preset.name && saved.env == preset.env)
+    {
+        Some(saved) => *saved = preset,
+        None => presets.push(preset),
+    }
+    write_presets(workspace, script, presets)
+}
+
+/// Removes the preset with this name and environment.
+pub fn delete_preset(
+    workspace: &Workspace,
+    script: &Path,
+    name: &str,
+    env: Option<&str>,
+) -> Result<PathBuf, String> {
+    let mut presets = load_presets(workspace, script)?;
+    presets.retain(|saved| !(saved.name == name && saved.env.as_deref() == env));
+    write_presets(workspace, script, presets)
+}
+
+fn write_presets(
+    workspace: &Workspace,
+    script: &Path,
+    presets: Vec<Preset>,
+) -> Result<PathBuf, String> {
+    let path = presets_path(workspace, script);
+    let contents = toml::to_string_pretty(&PresetFile { presets })
+        .map_err(|err| format!("Failed to encode presets: {}", err))?;
+    fs::create_dir_all(workspace.presets_dir())
+        .and_then(|_| fs::write(&path, contents))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))?;
+    Ok(path)
+}
+
+/// Arguments and stdin for a run with a preset, built the way the TUI form builds them. Fields
+/// the preset has no value for use the environment defaults; fields whose flag is already in
+/// `explicit_args` are left to those arguments.
+pub fn preset_inputs(
+    schema: &Schema,
+    preset: &Preset,
+    env_defaults: &HashMap<String, String>,
+    explicit_args: &[String],
+    root: &Path,
+) -> Result<(Vec<String>, Option<String>), String> {
+    let mut fields: Vec<&Field> = schema.fields.iter().collect();
+    fields.sort_by_key(|field| field.order);
+    let mut args = Vec::new();
+    let mut stdin = None;
+    for field in fields {
+        let arg = field.flag();
+        if explicit_args.contains(&arg) {
+            continue;
+        }
+        let input = preset
+            .value_for(field)
+            .map(str::to_string)
+            .or_else(|| env_defaults.get(&field.name.to_ascii_lowercase()).cloned())
+            .unwrap_or_default();
+        let value = normalize_input(field, &input).map_err(|err| {
+            if field.is_secret() {
+                format!(
+                    "{}: {} (secret fields aren't saved in presets; pass {})",
+                    field.name, err, arg
+                )
+            } else {
+                format!("{}: {}", field.name, err)
+            }
+        })?;
+        let Some(value) = value else {
+            continue;
+        };
+        match schema
+            .stdin
+            .as_ref()
+            .filter(|spec| spec.field.eq_ignore_ascii_case(&field.name))
+        {
+            Some(spec) => {
+                let payload = stdin_payload(spec.source, &value, root)
+                    .map_err(|err| format!("{}: {}", field.name, err))?;
+                stdin = Some(payload);
+            }
+            None => args.extend(field.cli_args(value)),
+        }
+    }
+    Ok((args, stdin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, secret: bool) -> Field {
+        Field {
+            name: name.to_string(),
+            prompt: None,
+            kind: "string".to_string(),
+            order: 1,
+            required: Some(true),
+            default: None,
+            choices: None,
+            arg: None,
+            secret: Some(secret),
+            switch: None,
+        }
+    }
+
+    #[test]
+    fn test_secret_fields_are_never_saved() {
+        let root = std::env::temp_dir().join(format!("omakure-presets-{}", std::process::id()));
+        let workspace = Workspace::new(root.clone());
+        let script = root.join("azure").join("cleanup.sh");
+        let fields = vec![field("region", false), field("token", true)];
+        let inputs = vec!["eastus".to_string(), "hunter2".to_string()];
+
+        let preset = Preset::from_inputs(
+            "prod-eastus",
+            Some("prod.conf".to_string()),
+            &fields,
+            &inputs,
+        );
+        let path = save_preset(&workspace, &script, preset).unwrap();
+        assert!(path.ends_with(".omaken/presets/azure.cleanup.sh.toml"));
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("eastus"));
+        assert!(!contents.contains("hunter2"));
+
+        let presets = load_presets(&workspace, &script).unwrap();
+        assert_eq!(presets[0].value_for(&fields[0]), Some("eastus"));
+        assert_eq!(presets[0].value_for(&fields[1]), None);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_sensitive_names_are_secret_unless_declared_otherwise() {
+        let undeclared = |name: &str| Field {
+            secret: None,
+            ..field(name, false)
+        };
+        let fields = vec![
+            undeclared("api_token"),
+            undeclared("region"),
+            field("db_password", false),
+        ];
+        let inputs = vec!["t0k3n".to_string(), "eastus".to_string(), "pw".to_string()];
+        let preset = Preset::from_inputs("p", None, &fields, &inputs);
+        assert_eq!(
+            preset.values.keys().collect::<Vec<_>>(),
+            vec!["db_password", "region"]
+        );
+    }
+
+    #[test]
+    fn test_find_preset_prefers_the_active_environment() {
+        let preset = |env: Option<&str>, region: &str| Preset {
+            name: "cleanup".to_string(),
+            env: env.map(str::to_string),
+            values: BTreeMap::from([("region".to_string(), region.to_string())]),
+        };
+        let presets = vec![
+            preset(None, "westeurope"),
+            preset(Some("prod.conf"), "eastus"),
+        ];
+        let found = |env| {
+            find_preset(&presets, "cleanup", env).map(|preset| preset.values["region"].as_str())
+        };
+        assert_eq!(found(Some("prod.conf")), Some("eastus"));
+        assert_eq!(found(Some("dev.conf")), Some("westeurope"));
+        assert_eq!(found(None), Some("westeurope"));
+        assert_eq!(available(&presets, Some("dev.conf")).len(), 1);
+        assert!(find_preset(&presets, "other", None).is_none());
+    }
+
+    #[test]
+    fn test_preset_inputs_leave_explicit_flags_alone() {
+        let schema = Schema {
+            name: "cleanup".to_string(),
+            description: None,
+            tags: None,
+            fields: vec![field("region", false), field("token", true)],
+            outputs: None,
+            queue: None,
+            output_format: None,
+            stdin: None,
+            requires: None,
+            prompts: None,
+        };
+        let preset = Preset {
+            name: "p".to_string(),
+            env: None,
+            values: BTreeMap::from([("region".to_string(), "eastus".to_string())]),
+        };
+        let root = Path::new(".");
+        let err = preset_inputs(&schema, &preset, &HashMap::new(), &[], root).unwrap_err();
+        assert!(err.contains("secret fields aren't saved"));
+
+        let explicit = vec!["--token".to_string(), "x".to_string()];
+        let (args, stdin) =
+            preset_inputs(&schema, &preset, &HashMap::new(), &explicit, root).unwrap();
+        assert_eq!(args, vec!["--region", "eastus"]);
+        assert!(stdin.is_none());
+
+        let env = HashMap::from([("token".to_string(), "from-env".to_string())]);
+        let (args, _) = preset_inputs(&schema, &preset, &env, &[], root).unwrap();
+        assert_eq!(args, vec!["--region", "eastus", "--token", "from-env"]);
+    }
+}
@@ -1,26 +1,99 @@
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::process::Command;
 
+use crate::adapters::system_checks::is_executable;
+use crate::adapters::workspace_repository::sidecar_schema_paths;
+use crate::domain::StdinSource;
 use crate::error::ScriptError;
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ScriptKind {
     Bash,
     PowerShell,
     Python,
+    /// Runs in-process through the embedded Lua runtime.
+    Lua,
+    /// Any other executable file with a sidecar schema, such as a compiled binary; run directly.
+    Executable,
 }
 
+impl ScriptKind {
+    /// Lowercase runtime name, as matched by the `kind:` search qualifier.
+    pub fn name(self) -> &'static str {
+        match self {
+            ScriptKind::Bash => "bash",
+            ScriptKind::PowerShell => "powershell",
+            ScriptKind::Python => "python",
+            ScriptKind::Lua => "lua",
+            ScriptKind::Executable => "executable",
+        }
+    }
+
+    /// Accepts a runtime name (`python`) or one of its extensions (`py`).
+    pub fn from_name(value: &str) -> Option<Self> {
+        let value = value.to_ascii_lowercase();
+        [
+            ScriptKind::Bash,
+            ScriptKind::PowerShell,
+            ScriptKind::Python,
+            ScriptKind::Lua,
+            ScriptKind::Executable,
+        ]
+        .into_iter()
+        .find(|kind| kind.name() == value)
+        .or_else(|| match value.as_str() {
+            "pwsh" => Some(ScriptKind::PowerShell),
+            _ => kind_by_extension(Path::new(&format!("script.{}", value))),
+        })
+    }
+}
+
+/// Lua files with a fixed role that are never listed as scripts.
+const RESERVED_LUA_FILES: [&str; 2] = ["index.lua", "hooks.lua"];
+
+/// Kind of a script file: by extension, or `Executable` for an executable file of any other
+/// type that has a sidecar schema next to it.
 pub fn script_kind(path: &Path) -> Option<ScriptKind> {
+    kind_by_extension(path).or_else(|| {
+        let runnable = path.extension().is_none_or(|ext| !is_script_extension(ext))
+            && !sidecar_schema_paths(path).is_empty()
+            && is_executable(path);
+        runnable.then_some(ScriptKind::Executable)
+    })
+}
+
+fn is_script_extension(ext: &std::ffi::OsStr) -> bool {
+    ext.to_str()
+        .is_some_and(|ext| script_extensions().contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn kind_by_extension(path: &Path) -> Option<ScriptKind> {
     let ext = path.extension()?.to_str()?.to_ascii_lowercase();
     match ext.as_str() {
         "bash" | "sh" => Some(ScriptKind::Bash),
         "ps1" => Some(ScriptKind::PowerShell),
         "py" => Some(ScriptKind::Python),
+        "lua" => {
+            let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+            (!RESERVED_LUA_FILES.contains(&name.as_str())).then_some(ScriptKind::Lua)
+        }
         _ => None,
     }
 }
 
+/// Line comment markers a schema block may use; Lua scripts declare their schema as a table.
+pub fn schema_comment_prefixes(kind: ScriptKind) -> &'static [&'static str] {
+    match kind {
+        ScriptKind::Bash | ScriptKind::Python => &["#"],
+        ScriptKind::PowerShell => &["#", ";"],
+        ScriptKind::Lua | ScriptKind::Executable => &[],
+    }
+}
+
 pub fn script_extensions() -> &'static [&'static str] {
-    &["bash", "sh", "ps1", "py"]
+    &["bash", "sh", "ps1", "py", "lua"]
 }
 
 pub fn command_for_script(script: &Path) -> Result<Command, ScriptError> {
@@ -29,6 +102,12 @@ pub fn command_for_script(script: &Path) -> Result<Command, ScriptError> {
         ScriptKind::Bash => Command::new("bash"),
         ScriptKind::PowerShell => Command::new(powershell_program()),
         ScriptKind::Python => Command::new(python_program()),
+        ScriptKind::Lua => return Err(ScriptError::UnsupportedType),
+        // A bare file name would be looked up on PATH instead.
+        ScriptKind::Executable if script.parent() == Some(Path::new("")) => {
+            Command::new(Path::new(".").join(script))
+        }
+        ScriptKind::Executable => Command::new(script),
     };
 
     match kind {
@@ -38,6 +117,7 @@ pub fn command_for_script(script: &Path) -> Result<Command, ScriptError> {
         ScriptKind::PowerShell => {
             command.arg("-NoProfile").arg("-File").arg(script);
         }
+        ScriptKind::Lua | ScriptKind::Executable => {}
     }
 
     Ok(command)
@@ -58,3 +138,11 @@ pub fn python_program() -> &'static str {
         "python3"
     }
 }
+
+/// Resolves a stdin field value to the payload piped into the script.
+pub fn stdin_payload(source: StdinSource, value: &str, root: &Path) -> io::Result<String> {
+    match source {
+        StdinSource::Text => Ok(value.to_string()),
+        StdinSource::File => fs::read_to_string(root.join(value)),
+    }
+}
@@ -0,0 +1,468 @@
+use crate::codegen::{apply_block, args_block};
+use crate::domain::{locate_schema_block, parse_schema_as, Field, FieldLibrary};
+use crate::runtime::{schema_comment_prefixes, script_kind, ScriptKind};
+use crate::theme_config::config_dir;
+use crate::util::read_dir_or_empty;
+use crate::workspace::Workspace;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a template comes from; earlier sources win when names collide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateSource {
+    /// `~/.config/omakure/templates/`.
+    User,
+    /// `.omaken/<flavor>/templates/`.
+    Flavor(String),
+    BuiltIn,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptTemplate {
+    pub name: String,
+    pub kind: ScriptKind,
+    /// Extension new scripts get when their path doesn't name one.
+    pub extension: String,
+    pub source: TemplateSource,
+    pub contents: String,
+}
+
+impl ScriptTemplate {
+    /// Name to pass to `--template`: flavor templates can be qualified as `<flavor>/<name>`.
+    pub fn qualified_name(&self) -> String {
+        match &self.source {
+            TemplateSource::Flavor(flavor) => format!("{}/{}", flavor, self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// What a new script's schema starts with; placeholders are filled from it.
+#[derive(Debug, Clone)]
+pub struct TemplateAnswers {
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub fields: Vec<Field>,
+}
+
+impl TemplateAnswers {
+    /// Answers used without the wizard: a placeholder description and an optional `target`.
+    pub fn defaults(name: &str, kind: ScriptKind) -> Self {
+        let arg = match kind {
+            ScriptKind::PowerShell => Some("-Target".to_string()),
+            ScriptKind::Lua => None,
+            _ => Some("--target".to_string()),
+        };
+        Self {
+            name: name.to_string(),
+            description: "Describe what this script does.".to_string(),
+            tags: Vec::new(),
+            fields: vec![Field {
+                name: "target".to_string(),
+                prompt: Some("Target (optional)".to_string()),
+                kind: "string".to_string(),
+                order: 1,
+                required: Some(false),
+                default: None,
+                choices: None,
+                arg,
+                secret: None,
+                switch: None,
+            }],
+        }
+    }
+}
+
+/// Built-in templates: name, runtime, extension and contents.
+const BUILTIN_TEMPLATES: [(&str, ScriptKind, &str, &str); 4] = [
+    ("bash", ScriptKind::Bash, "bash", BASH_TEMPLATE),
+    (
+        "powershell",
+        ScriptKind::PowerShell,
+        "ps1",
+        POWERSHELL_TEMPLATE,
+    ),
+    ("python", ScriptKind::Python, "py", PYTHON_TEMPLATE),
+    ("lua", ScriptKind::Lua, "lua", LUA_TEMPLATE),
+];
+
+const BASH_TEMPLATE: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+
+# 1) Schema for the TUI
+{{schema}}
+
+# 2) Args (regenerate with `omakure codegen` after editing the schema)
+{{args}}
+
+# 3) Main
+echo "TODO: implement {{name}}"
+"#;
+
+const POWERSHELL_TEMPLATE: &str = r#"# PowerShell script template
+
+{{schema}}
+
+# Args (regenerate with `omakure codegen` after editing the schema)
+{{args}}
+
+Write-Output "TODO: implement {{name}}"
+"#;
+
+const PYTHON_TEMPLATE: &str = r#"#!/usr/bin/env python3
+
+{{schema}}
+
+# Args (regenerate with `omakure codegen` after editing the schema)
+{{args}}
+
+print("TODO: implement {{name}}")
+"#;
+
+const LUA_TEMPLATE: &str = r#"return {
+  schema = {{schema}},
+
+  main = function(args)
+    print("TODO: implement {{name}}")
+  end,
+}
+"#;
+
+/// Built-in template for a runtime.
+pub fn builtin_template(kind: ScriptKind) -> ScriptTemplate {
+    let (name, kind, extension, contents) = BUILTIN_TEMPLATES
+        .iter()
+        .find(|(_, template_kind, _, _)| *template_kind == kind)
+        .expect("every runtime has a built-in template");
+    ScriptTemplate {
+        name: name.to_string(),
+        kind: *kind,
+        extension: extension.to_string(),
+        source: TemplateSource::BuiltIn,
+        contents: contents.to_string(),
+    }
+}
+
+pub fn user_templates_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("templates"))
+}
+
+/// Every template: user ones, then flavor ones (by flavor name), then the built-ins.
+pub fn list_templates(workspace: &Workspace) -> Vec<ScriptTemplate> {
+    let mut templates = Vec::new();
+    if let Some(dir) = user_templates_dir() {
+        templates.extend(templates_in(&dir, TemplateSource::User));
+    }
+    let mut flavors: Vec<PathBuf> = read_dir_or_empty(workspace.omaken_dir())
+        .map(|entries| entries.into_iter().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    flavors.sort();
+    for flavor in flavors {
+        if let Some(name) = flavor.file_name().and_then(|name| name.to_str()) {
+            let source = TemplateSource::Flavor(name.to_string());
+            templates.extend(templates_in(&flavor.join("templates"), source));
+        }
+    }
+    templates.extend(
+        BUILTIN_TEMPLATES
+            .iter()
+            .map(|(_, kind, _, _)| builtin_template(*kind)),
+    );
+    templates
+}
+
+/// Script files in `dir`, named after their stem (`azure-rg.bash` -> `azure-rg`).
+fn templates_in(dir: &Path, source: TemplateSource) -> Vec<ScriptTemplate> {
+    let mut paths: Vec<PathBuf> = read_dir_or_empty(dir)
+        .map(|entries| entries.into_iter().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let kind = script_kind(&path).filter(|kind| *kind != ScriptKind::Executable)?;
+            let name = path.file_stem()?.to_str()?.to_string();
+            let extension = path.extension()?.to_str()?.to_string();
+            let contents = fs::read_to_string(&path).ok()?;
+            Some(ScriptTemplate {
+                name,
+                kind,
+                extension,
+                source: source.clone(),
+                contents,
+            })
+        })
+        .collect()
+}
+
+/// Finds a template by name (or `<flavor>/<name>`), preferring one for `kind` when a name
+/// exists for several runtimes.
+pub fn find_template(
+    templates: &[ScriptTemplate],
+    name: &str,
+    kind: Option<ScriptKind>,
+) -> Result<ScriptTemplate, String> {
+    let matches: Vec<&ScriptTemplate> = templates
+        .iter()
+        .filter(|template| template.qualified_name() == name || template.name == name)
+        .collect();
+    matches
+        .iter()
+        .find(|template| kind.is_none_or(|kind| template.kind == kind))
+        .or_else(|| matches.first())
+        .map(|template| (*template).clone())
+        .ok_or_else(|| {
+            let names: Vec<String> = templates
+                .iter()
+                .map(ScriptTemplate::qualified_name)
+                .collect();
+            format!(
+                "Unknown template `{}`. Available: {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// Fills `{{name}}`, `{{description}}`, `{{schema}}` and `{{args}}`; other `{{...}}` text is
+/// left alone. Lines of a multi-line value take the indentation of the placeholder's line. When
+/// the result has an `OMAKURE_ARGS` region, it is generated from the script's schema.
+pub fn render(template: &ScriptTemplate, answers: &TemplateAnswers) -> Result<String, String> {
+    let kind = template.kind;
+    let args = match kind {
+        ScriptKind::Lua => String::new(),
+        _ => {
+            let prefix = schema_comment_prefixes(kind)[0];
+            format!("{prefix} OMAKURE_ARGS_START\n{prefix} OMAKURE_ARGS_END")
+        }
+    };
+    let values = [
+        ("name", answers.name.clone()),
+        ("description", answers.description.clone()),
+        ("schema", schema_text(kind, answers)),
+        ("args", args),
+    ];
+
+    let mut out = String::new();
+    for line in template.contents.split_inclusive('\n') {
+        let indent: String = line
+            .chars()
+            .take_while(|ch| ch.is_whitespace() && *ch != '\n')
+            .collect();
+        let mut rest = line;
+        while let Some(open) = rest.find("{{") {
+            let Some(close) = rest[open..].find("}}").map(|close| open + close) else {
+                break;
+            };
+            let key = rest[open + 2..close].trim();
+            out.push_str(&rest[..open]);
+            match values.iter().find(|(name, _)| *name == key) {
+                Some((_, value)) => out.push_str(&value.replace('\n', &format!("\n{}", indent))),
+                None => out.push_str(&rest[open..close + 2]),
+            }
+            rest = &rest[close + 2..];
+        }
+        out.push_str(rest);
+    }
+
+    if kind == ScriptKind::Lua || !out.contains("OMAKURE_ARGS_START") {
+        return Ok(out);
+    }
+    let block = locate_schema_block(&out, schema_comment_prefixes(kind))
+        .map_err(|err| format!("Template has an args region but no schema: {}", err))?;
+    let schema = parse_schema_as(&block.text, block.format, &FieldLibrary::default())
+        .map_err(|err| err.to_string())?;
+    apply_block(kind, &out, &args_block(kind, &schema)?)
+}
+
+/// The schema as a commented JSON block, or as a Lua table for Lua scripts.
+fn schema_text(kind: ScriptKind, answers: &TemplateAnswers) -> String {
+    if kind == ScriptKind::Lua {
+        return lua_schema(answers);
+    }
+    let mut json = vec![
+        "{".to_string(),
+        format!("  \"Name\": {},", json_string(&answers.name)),
+        format!("  \"Description\": {},", json_string(&answers.description)),
+        format!("  \"Tags\": {},", json_list(&answers.tags)),
+    ];
+    if answers.fields.is_empty() {
+        json.push("  \"Fields\": []".to_string());
+    } else {
+        json.push("  \"Fields\": [".to_string());
+        for (index, field) in answers.fields.iter().enumerate() {
+            let mut properties = vec![
+                format!("\"Name\": {}", json_string(&field.name)),
+                format!(
+                    "\"Prompt\": {}",
+                    json_string(field.prompt.as_deref().unwrap_or(&field.name))
+                ),
+                format!("\"Type\": {}", json_string(&field.kind)),
+                format!("\"Order\": {}", field.order),
+                format!("\"Required\": {}", field.required.unwrap_or(false)),
+            ];
+            if let Some(default) = &field.default {
+                properties.push(format!("\"Default\": {}", json_string(default)));
+            }
+            if let Some(choices) = &field.choices {
+                properties.push(format!("\"Choices\": {}", json_list(choices)));
+            }
+            if let Some(arg) = &field.arg {
+                properties.push(format!("\"Arg\": {}", json_string(arg)));
+            }
+            json.push("    {".to_string());
+            let count = properties.len();
+            for (position, property) in properties.into_iter().enumerate() {
+                let comma = if position + 1 < count { "," } else { "" };
+                json.push(format!("      {}{}", property, comma));
+            }
+            let comma = if index + 1 < answers.fields.len() {
+                ","
+            } else {
+                ""
+            };
+            json.push(format!("    }}{}", comma));
+        }
+        json.push("  ]".to_string());
+    }
+    json.push("}".to_string());
+
+    let prefix = schema_comment_prefixes(kind)[0];
+    let mut lines = vec![format!("{} OMAKURE_SCHEMA_START", prefix)];
+    lines.extend(json.iter().map(|line| format!("{} {}", prefix, line)));
+    lines.push(format!("{} OMAKURE_SCHEMA_END", prefix));
+    lines.join("\n")
+}
+
+fn lua_schema(answers: &TemplateAnswers) -> String {
+    let mut lines = vec![
+        "{".to_string(),
+        format!("  Name = {},", lua_string(&answers.name)),
+        format!("  Description = {},", lua_string(&answers.description)),
+        format!("  Tags = {},", lua_list(&answers.tags)),
+        "  Fields = {".to_string(),
+    ];
+    for field in &answers.fields {
+        lines.push("    {".to_string());
+        lines.push(format!("      Name = {},", lua_string(&field.name)));
+        lines.push(format!(
+            "      Prompt = {},",
+            lua_string(field.prompt.as_deref().unwrap_or(&field.name))
+        ));
+        lines.push(format!("      Type = {},", lua_string(&field.kind)));
+        lines.push(format!("      Order = {},", field.order));
+        lines.push(format!(
+            "      Required = {},",
+            field.required.unwrap_or(false)
+        ));
+        if let Some(default) = &field.default {
+            lines.push(format!("      Default = {},", lua_string(default)));
+        }
+        if let Some(choices) = &field.choices {
+            lines.push(format!("      Choices = {},", lua_list(choices)));
+        }
+        lines.push("    },".to_string());
+    }
+    lines.push("  },".to_string());
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+fn json_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| json_string(value)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+fn lua_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+fn lua_list(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|value| lua_string(value)).collect();
+    format!("{{{}}}", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers() -> TemplateAnswers {
+        let mut answers = TemplateAnswers::defaults("deploy", ScriptKind::Bash);
+        answers.tags = vec!["azure".to_string()];
+        answers.fields.push(Field {
+            name: "env".to_string(),
+            prompt: Some("Environment".to_string()),
+            kind: "string".to_string(),
+            order: 2,
+            required: Some(true),
+            default: None,
+            choices: Some(vec!["dev".to_string(), "prod".to_string()]),
+            arg: Some("--env".to_string()),
+            secret: None,
+            switch: None,
+        });
+        answers
+    }
+
+    #[test]
+    fn test_render_fills_placeholders_and_args() {
+        let template = ScriptTemplate {
+            name: "azure-rg".to_string(),
+            kind: ScriptKind::Bash,
+            extension: "sh".to_string(),
+            source: TemplateSource::User,
+            contents: "#!/bin/bash\n  {{ schema }}\n{{args}}\necho \"{{name}}: ${{ env.X }}\"\n"
+                .to_string(),
+        };
+        let rendered = render(&template, &answers()).unwrap();
+        assert!(rendered.starts_with(
+            "#!/bin/bash\n  # OMAKURE_SCHEMA_START\n  # {\n  #   \"Name\": \"deploy\",\n"
+        ));
+        assert!(rendered.contains("  #   \"Tags\": [\"azure\"],\n"));
+        assert!(rendered.contains("  #       \"Choices\": [\"dev\", \"prod\"],\n"));
+        assert!(rendered.contains("    --env)\n      [[ $# -ge 2 ]] || { echo \"Missing value for $1\" >&2; exit 1; }\n      ARG_ENV=\"$2\"\n"));
+        assert!(rendered.ends_with("# OMAKURE_ARGS_END\necho \"deploy: ${{ env.X }}\"\n"));
+
+        let schema = locate_schema_block(&rendered, &["#"]).unwrap();
+        let schema =
+            parse_schema_as(&schema.text, schema.format, &FieldLibrary::default()).unwrap();
+        assert_eq!(schema.fields.len(), 2);
+    }
+
+    #[test]
+    fn test_builtin_lua_template_and_lookup() {
+        let template = builtin_template(ScriptKind::Lua);
+        let rendered = render(&template, &answers()).unwrap();
+        assert!(rendered.contains("  schema = {\n    Name = \"deploy\",\n"));
+        assert!(rendered.contains("        Choices = {\"dev\", \"prod\"},\n"));
+
+        let templates = vec![
+            ScriptTemplate {
+                name: "bash".to_string(),
+                kind: ScriptKind::Bash,
+                extension: "sh".to_string(),
+                source: TemplateSource::Flavor("azure".to_string()),
+                contents: String::new(),
+            },
+            builtin_template(ScriptKind::Bash),
+        ];
+        let found = find_template(&templates, "azure/bash", None).unwrap();
+        assert_eq!(found.source, TemplateSource::Flavor("azure".to_string()));
+        let found = find_template(&templates, "bash", Some(ScriptKind::Bash)).unwrap();
+        assert_eq!(found.source, TemplateSource::Flavor("azure".to_string()));
+        assert!(find_template(&templates, "nope", None)
+            .unwrap_err()
+            .starts_with("Unknown template `nope`"));
+    }
+}
@@ -0,0 +1,584 @@
+use crate::adapters::environments::parse_env_defaults;
+use crate::command_stub::{Cassette, ShimDir, StubResponse};
+use crate::domain::{normalize_input, OutputLine, PromptRequest, Schema};
+use crate::ports::{RunObserver, ScriptRunOutput, ScriptRunner};
+use crate::runtime::stdin_payload;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Suffix of test files: `deploy.sh` is tested by `deploy.sh.omakure-test.toml`.
+const TEST_SUFFIX: &str = ".omakure-test.toml";
+
+/// A `*.omakure-test.toml` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestFile {
+    #[serde(default, rename = "case")]
+    pub cases: Vec<TestCase>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    pub name: String,
+    /// Field values by field name; omitted fields fall back to the env file, then `Default`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, toml::Value>,
+    /// `key=value` file (like `.omaken/envs/*.conf`), relative to the test file.
+    pub env_file: Option<PathBuf>,
+    /// Extra environment variables for the script.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Text piped into stdin, instead of a `Stdin` field's value.
+    pub stdin: Option<String>,
+    /// Answers to `::prompt` requests by prompt id.
+    #[serde(default)]
+    pub answers: BTreeMap<String, String>,
+    #[serde(default, rename = "stub")]
+    pub stubs: Vec<Stub>,
+    /// Cassette from `run --record`, relative to the test file; its commands are replayed.
+    pub cassette: Option<PathBuf>,
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Stub {
+    pub command: String,
+    #[serde(flatten)]
+    pub response: StubResponse,
+}
+
+/// What a run must produce. Patterns are regular expressions matched anywhere in the stream.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectation {
+    /// Defaults to 0.
+    pub exit_code: Option<i32>,
+    #[serde(default)]
+    pub stdout: Vec<String>,
+    #[serde(default)]
+    pub stderr: Vec<String>,
+    #[serde(default)]
+    pub stdout_not: Vec<String>,
+    #[serde(default)]
+    pub stderr_not: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    /// Script path relative to the workspace.
+    pub script: String,
+    pub name: String,
+    /// Empty when the case passed.
+    pub failures: Vec<String>,
+    pub duration: Duration,
+}
+
+pub fn test_file_for(script: &Path) -> PathBuf {
+    let name = script
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    script.with_file_name(format!("{}{}", name, TEST_SUFFIX))
+}
+
+pub fn load_test_file(path: &Path) -> Result<TestFile, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    toml::from_str(&contents).map_err(|err| err.to_string())
+}
+
+/// Runs one case with its stubs first on `PATH`, through the same runner as real runs.
+/// Declared `Requires` are checked, except for stubbed and replayed commands.
+pub fn run_case(
+    runner: &dyn ScriptRunner,
+    schema: Option<&Schema>,
+    script: &Path,
+    test_file: &Path,
+    case: &TestCase,
+) -> Vec<String> {
+    let base = test_file.parent().unwrap_or(Path::new("."));
+    let (args, stdin) = match case_inputs(schema, base, case) {
+        Ok(inputs) => inputs,
+        Err(err) => return vec![err],
+    };
+
+    let shims = match ShimDir::create() {
+        Ok(shims) => shims,
+        Err(err) => return vec![format!("Failed to create stubs: {}", err)],
+    };
+    for stub in &case.stubs {
+        if let Err(err) = shims.add_stub(&stub.command, &stub.response) {
+            return vec![err.to_string()];
+        }
+    }
+    let mut shimmed: Vec<String> = case.stubs.iter().map(|stub| stub.command.clone()).collect();
+    if let Some(path) = &case.cassette {
+        let cassette = match Cassette::load(&base.join(path)) {
+            Ok(cassette) => cassette,
+            Err(err) => return vec![err],
+        };
+        for command in cassette.commands() {
+            if let Err(err) = shims.add_replayer(&command, &cassette.calls) {
+                return vec![err.to_string()];
+            }
+            shimmed.push(command);
+        }
+    }
+    let mut env: Vec<(String, String)> = case
+        .env
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    env.push(shims.path_env());
+
+    if let Some(requires) = schema.and_then(|schema| schema.requires.as_ref()) {
+        let unstubbed: Vec<_> = requires
+            .iter()
+            .filter(|requirement| !shimmed.contains(&requirement.command))
+            .cloned()
+            .collect();
+        let unmet = runner.check_requirements(&unstubbed);
+        if !unmet.is_empty() {
+            return unmet.iter().map(|err| err.to_string()).collect();
+        }
+    }
+
+    let mut observer = TestObserver {
+        answers: &case.answers,
+    };
+    let prompts = schema.and_then(|schema| schema.prompts).unwrap_or(false);
+    match runner.run(
+        script,
+        &args,
+        stdin.as_deref(),
+        prompts,
+        &env,
+        &mut observer,
+    ) {
+        Ok(output) => check_output(&case.expect, &output),
+        Err(err) => vec![format!("Run failed: {}", err)],
+    }
+}
+
+/// Arguments built from the case's field values the way the TUI builds them, plus stdin.
+fn case_inputs(
+    schema: Option<&Schema>,
+    base: &Path,
+    case: &TestCase,
+) -> Result<(Vec<String>, Option<String>), String> {
+    let env_defaults = match &case.env_file {
+        Some(path) => {
+            let path = base.join(path);
+            let contents = fs::read_to_string(&path)
+                .map_err(|err| format!("Failed to read env file {}: {}", path.display(), err))?;
+            parse_env_defaults(&contents)
+        }
+        None => HashMap::new(),
+    };
+    let values: HashMap<String, String> = case
+        .fields
+        .iter()
+        .map(|(name, value)| {
+            let text = match value {
+                toml::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            (name.to_ascii_lowercase(), text)
+        })
+        .collect();
+
+    let Some(schema) = schema else {
+        if values.is_empty() {
+            return Ok((Vec::new(), case.stdin.clone()));
+        }
+        return Err("Script has no schema to map field values to".to_string());
+    };
+    if let Some(unknown) = values.keys().find(|name| {
+        !schema
+            .fields
+            .iter()
+            .any(|field| field.name.eq_ignore_ascii_case(name))
+    }) {
+        return Err(format!("Unknown field `{}`", unknown));
+    }
+
+    let mut fields: Vec<_> = schema.fields.iter().collect();
+    fields.sort_by_key(|field| field.order);
+    let mut args = Vec::new();
+    let mut stdin = case.stdin.clone();
+    for field in fields {
+        let key = field.name.to_ascii_lowercase();
+        let input = values
+            .get(&key)
+            .or_else(|| env_defaults.get(&key))
+            .cloned()
+            .unwrap_or_default();
+        let value =
+            normalize_input(field, &input).map_err(|err| format!("{}: {}", field.name, err))?;
+        let Some(value) = value else {
+            continue;
+        };
+        match schema
+            .stdin
+            .as_ref()
+            .filter(|spec| spec.field.eq_ignore_ascii_case(&field.name))
+        {
+            Some(spec) => {
+                let payload = stdin_payload(spec.source, &value, base)
+                    .map_err(|err| format!("{}: {}", field.name, err))?;
+                stdin.get_or_insert(payload);
+            }
+            None => args.extend(field.cli_args(value)),
+        }
+    }
+    Ok((args, stdin))
+}
+
+/// Compares a run's output with the case's expectations, one message per mismatch.
+fn check_output(expect: &Expectation, output: &ScriptRunOutput) -> Vec<String> {
+    let mut failures = Vec::new();
+    let expected_code = expect.exit_code.unwrap_or(0);
+    if output.exit_code != Some(expected_code) {
+        let actual = output
+            .exit_code
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "none (killed)".to_string());
+        failures.push(format!("Exit code {}, expected {}", actual, expected_code));
+    }
+    let streams = [
+        ("stdout", &output.stdout, &expect.stdout, true),
+        ("stderr", &output.stderr, &expect.stderr, true),
+        ("stdout", &output.stdout, &expect.stdout_not, false),
+        ("stderr", &output.stderr, &expect.stderr_not, false),
+    ];
+    for (stream, text, patterns, wanted) in streams {
+        for pattern in patterns.iter() {
+            match Regex::new(pattern) {
+                Ok(regex) if regex.is_match(text) != wanted => failures.push(format!(
+                    "{} {} /{}/",
+                    stream,
+                    if wanted {
+                        "does not match"
+                    } else {
+                        "unexpectedly matches"
+                    },
+                    pattern
+                )),
+                Ok(_) => {}
+                Err(err) => failures.push(format!("Invalid pattern /{}/: {}", pattern, err)),
+            }
+        }
+    }
+    failures
+}
+
+struct TestObserver<'a> {
+    answers: &'a BTreeMap<String, String>,
+}
+
+impl RunObserver for TestObserver<'_> {
+    fn on_line(&mut self, _line: &OutputLine) {}
+
+    fn on_prompt(&mut self, prompt: &PromptRequest) -> Option<String> {
+        self.answers.get(&prompt.id).cloned()
+    }
+}
+
+/// Runs every case in `test_file` against `script`. A schema that failed to load fails every
+/// case with its error.
+pub fn run_test_file(
+    runner: &dyn ScriptRunner,
+    schema: Result<Option<&Schema>, &str>,
+    label: &str,
+    script: &Path,
+    test_file: &Path,
+) -> Vec<CaseResult> {
+    let file = match load_test_file(test_file) {
+        Ok(file) => file,
+        Err(err) => {
+            return vec![CaseResult {
+                script: label.to_string(),
+                name: "load test file".to_string(),
+                failures: vec![err],
+                duration: Duration::ZERO,
+            }]
+        }
+    };
+    file.cases
+        .iter()
+        .map(|case| {
+            let started = Instant::now();
+            let failures = match schema {
+                Ok(schema) => run_case(runner, schema, script, test_file, case),
+                Err(err) => vec![format!("Invalid schema: {}", err)],
+            };
+            CaseResult {
+                script: label.to_string(),
+                name: case.name.clone(),
+                failures,
+                duration: started.elapsed(),
+            }
+        })
+        .collect()
+}
+
+/// Test Anything Protocol (version 13) report.
+pub fn tap_report(results: &[CaseResult]) -> String {
+    let mut out = format!("TAP version 13\n1..{}\n", results.len());
+    for (index, result) in results.iter().enumerate() {
+        let status = if result.failures.is_empty() {
+            "ok"
+        } else {
+            "not ok"
+        };
+        out.push_str(&format!(
+            "{} {} - {}: {}\n",
+            status,
+            index + 1,
+            result.script,
+            result.name
+        ));
+        if !result.failures.is_empty() {
+            out.push_str("  ---\n  failures:\n");
+            for failure in &result.failures {
+                out.push_str(&format!(
+                    "    - {}\n",
+                    serde_json::to_string(failure).unwrap_or_default()
+                ));
+            }
+            out.push_str("  ...\n");
+        }
+    }
+    out
+}
+
+/// JUnit XML report with one `<testsuite>` per script.
+pub fn junit_report(results: &[CaseResult]) -> String {
+    let mut suites: BTreeMap<&str, Vec<&CaseResult>> = BTreeMap::new();
+    for result in results {
+        suites.entry(&result.script).or_default().push(result);
+    }
+    let failures = results
+        .iter()
+        .filter(|result| !result.failures.is_empty())
+        .count();
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"omakure\" tests=\"{}\" failures=\"{}\">\n",
+        results.len(),
+        failures
+    );
+    for (script, cases) in suites {
+        let suite_failures = cases
+            .iter()
+            .filter(|result| !result.failures.is_empty())
+            .count();
+        let time: f64 = cases
+            .iter()
+            .map(|result| result.duration.as_secs_f64())
+            .sum();
+        out.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(script),
+            cases.len(),
+            suite_failures,
+            time
+        ));
+        for case in cases {
+            let open = format!(
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(script),
+                case.duration.as_secs_f64()
+            );
+            match case.failures.first() {
+                None => out.push_str(&format!("{}/>\n", open)),
+                Some(first) => out.push_str(&format!(
+                    "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                    open,
+                    xml_escape(first),
+                    xml_escape(&case.failures.join("\n"))
+                )),
+            }
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{parse_schema_as, FieldLibrary, SchemaFormat};
+
+    #[test]
+    fn test_case_inputs_and_expectations() {
+        let file: TestFile = toml::from_str(
+            r#"
+            [[case]]
+            name = "creates the group"
+            fields = { name = "rg-dev", count = 2 }
+            env_file = "dev.conf"
+
+            [[case.stub]]
+            command = "az"
+            stdout = '{"id": 1}'
+
+            [case.expect]
+            stdout = ["created rg-\\w+"]
+            stderr_not = ["error"]
+            "#,
+        )
+        .unwrap();
+        let case = &file.cases[0];
+        assert_eq!(case.stubs[0].response.stdout, r#"{"id": 1}"#);
+
+        let base = std::env::temp_dir().join(format!("omakure-script-test-{}", std::process::id()));
+        fs::create_dir_all(&base).unwrap();
+        fs::write(base.join("dev.conf"), "LOCATION=westeurope\n").unwrap();
+        let schema = parse_schema_as(
+            r#"{"Name": "rg", "Fields": [
+                {"Name": "location", "Type": "string", "Order": 2, "Arg": "-l"},
+                {"Name": "name", "Type": "string", "Order": 1, "Required": true},
+                {"Name": "count", "Type": "number", "Order": 3},
+                {"Name": "tag", "Type": "string", "Order": 4}
+            ]}"#,
+            SchemaFormat::Json,
+            &FieldLibrary::default(),
+        )
+        .unwrap();
+        let (args, stdin) = case_inputs(Some(&schema), &base, case).unwrap();
+        assert_eq!(
+            args,
+            vec!["--name", "rg-dev", "-l", "westeurope", "--count", "2"]
+        );
+        assert_eq!(stdin, None);
+        let _ = fs::remove_dir_all(base);
+
+        let output = |stdout: &str, stderr: &str, code: i32| ScriptRunOutput {
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            exit_code: Some(code),
+            success: code == 0,
+            annotations: Vec::new(),
+        };
+        assert!(check_output(&case.expect, &output("created rg-dev\n", "", 0)).is_empty());
+        assert_eq!(
+            check_output(&case.expect, &output("nothing\n", "error: boom", 2)),
+            vec![
+                "Exit code 2, expected 0",
+                "stdout does not match /created rg-\\w+/",
+                "stderr unexpectedly matches /error/",
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bash_case_runs_against_stubbed_command() {
+        use crate::adapters::script_runner::MultiScriptRunner;
+        use crate::adapters::workspace_repository::FsWorkspaceRepository;
+        use crate::ports::ScriptRepository;
+
+        let root = std::env::temp_dir().join(format!("omakure-e2e-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let script = root.join("rg.sh");
+        fs::write(
+            &script,
+            r#"#!/usr/bin/env bash
+# OMAKURE_SCHEMA_START
+# {"Name": "rg", "Fields": [{"Name": "name", "Type": "string", "Order": 1, "Required": true}],
+#  "Requires": [{"Command": "az"}]}
+# OMAKURE_SCHEMA_END
+echo "name=$2"
+az group create --name "$2" --query id
+"#,
+        )
+        .unwrap();
+        let test_file = test_file_for(&script);
+        fs::write(
+            &test_file,
+            r#"
+            [[case]]
+            name = "creates the group"
+            fields = { name = "rg-dev" }
+
+            [[case.stub]]
+            command = "az"
+            stdout = "/groups/rg-dev\n"
+
+            [case.expect]
+            stdout = ["name=rg-dev\n/groups/rg-dev"]
+
+            [[case]]
+            name = "az fails"
+            fields = { name = "rg-dev" }
+
+            [[case.stub]]
+            command = "az"
+            stderr = "quota exceeded"
+            exit_code = 1
+
+            [case.expect]
+            exit_code = 1
+            stderr = ["quota"]
+            "#,
+        )
+        .unwrap();
+
+        let runner = MultiScriptRunner::new(&root);
+        let schema = FsWorkspaceRepository::new(root.clone())
+            .read_schema(&script)
+            .unwrap();
+        let results = run_test_file(&runner, Ok(Some(&schema)), "rg.sh", &script, &test_file);
+        let failures: Vec<&Vec<String>> = results.iter().map(|result| &result.failures).collect();
+        assert_eq!(failures, vec![&Vec::<String>::new(), &Vec::new()]);
+
+        let results = run_test_file(&runner, Err("bad Type"), "rg.sh", &script, &test_file);
+        assert_eq!(results[0].failures, vec!["Invalid schema: bad Type"]);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_reports() {
+        let results = vec![
+            CaseResult {
+                script: "rg.sh".to_string(),
+                name: "ok".to_string(),
+                failures: Vec::new(),
+                duration: Duration::from_millis(5),
+            },
+            CaseResult {
+                script: "rg.sh".to_string(),
+                name: "fails <loudly>".to_string(),
+                failures: vec!["Exit code 1, expected 0".to_string()],
+                duration: Duration::ZERO,
+            },
+        ];
+        assert_eq!(
+            tap_report(&results),
+            "TAP version 13\n1..2\nok 1 - rg.sh: ok\nnot ok 2 - rg.sh: fails <loudly>\n  ---\n  failures:\n    - \"Exit code 1, expected 0\"\n  ...\n"
+        );
+        let junit = junit_report(&results);
+        assert!(
+            junit.contains("<testsuite name=\"rg.sh\" tests=\"2\" failures=\"1\" time=\"0.005\">")
+        );
+        assert!(junit.contains(
+            "<testcase name=\"fails &lt;loudly&gt;\" classname=\"rg.sh\" time=\"0.000\">\n      <failure message=\"Exit code 1, expected 0\">"
+        ));
+    }
+}
@@ -1,8 +1,14 @@
-use crate::adapters::workspace_repository::FsWorkspaceRepository;
+use crate::adapters::workspace_repository::{
+    field_library_files, sidecar_schema_paths, FsWorkspaceRepository,
+};
+use crate::domain::Schema;
 use crate::ports::ScriptRepository;
+use crate::runtime::{script_kind, ScriptKind};
 use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -10,11 +16,21 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SearchStatus {
     Idle,
-    Indexing,
+    Indexing { done: usize, total: usize },
     Ready { script_count: usize },
     Error(String),
 }
 
+/// Bumped whenever the tables change; older caches are dropped and rebuilt.
+const SCHEMA_VERSION: i64 = 5;
+/// bm25 weights for `script_path` (unindexed), name, tags, description, fields and path.
+const COLUMN_WEIGHTS: &str = "0.0, 10.0, 5.0, 3.0, 2.0, 1.0";
+/// Share of a term's trigrams a script must contain to count as a fuzzy match.
+const FUZZY_THRESHOLD: f64 = 0.5;
+const FUZZY_CANDIDATES: usize = 200;
+const HIGHLIGHT_START: char = '\u{1}';
+const HIGHLIGHT_END: char = '\u{2}';
+
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub script_path: PathBuf,
@@ -22,6 +38,71 @@ pub struct SearchResult {
     pub description: Option<String>,
     pub tags: Vec<String>,
     pub schema_error: Option<String>,
+    pub name_highlight: Highlighted,
+    pub path_highlight: Highlighted,
+    /// Found by the typo-tolerant fallback rather than a direct match.
+    pub fuzzy: bool,
+}
+
+/// Text split into segments, flagged when they matched the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Highlighted {
+    pub segments: Vec<(String, bool)>,
+}
+
+impl Highlighted {
+    /// Parses the markers produced by FTS5 `highlight()`.
+    fn from_marked(text: &str) -> Self {
+        let mut segments = Vec::new();
+        let mut current = String::new();
+        let mut matched = false;
+        for ch in text.chars() {
+            if ch == HIGHLIGHT_START || ch == HIGHLIGHT_END {
+                if !current.is_empty() {
+                    segments.push((std::mem::take(&mut current), matched));
+                }
+                matched = ch == HIGHLIGHT_START;
+            } else {
+                current.push(ch);
+            }
+        }
+        if !current.is_empty() {
+            segments.push((current, matched));
+        }
+        Self { segments }
+    }
+}
+
+/// Search box input split into free-text terms and `qualifier:value` filters.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ParsedQuery {
+    terms: Vec<String>,
+    filters: Vec<Filter>,
+}
+
+/// A qualifier such as `tag:azure`; a leading `-` negates it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Filter {
+    negated: bool,
+    kind: FilterKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FilterKind {
+    Tag(String),
+    /// Runtime name, see [`ScriptKind::name`].
+    Kind(String),
+    Field(String),
+    /// Prefix of the script path relative to the workspace root.
+    Path(String),
+    Has(HasFlag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HasFlag {
+    Queue,
+    Outputs,
+    Error,
 }
 
 #[derive(Debug, Clone)]
@@ -45,6 +126,8 @@ pub struct SearchDetails {
 pub struct SearchIndex {
     db_path: PathBuf,
     status: Arc<Mutex<SearchStatus>>,
+    running: Arc<AtomicBool>,
+    rerun: Arc<AtomicBool>,
 }
 
 impl SearchIndex {
@@ -52,6 +135,8 @@ impl SearchIndex {
         Self {
             db_path,
             status: Arc::new(Mutex::new(SearchStatus::Idle)),
+            running: Arc::new(AtomicBool::new(false)),
+            rerun: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -64,77 +149,94 @@ impl SearchIndex {
             ))
     }
 
+    /// Updates the index on a background thread. A request made while a pass is running
+    /// is folded into one more pass once it finishes, so writers never overlap.
     pub fn start_background_rebuild(&self, root: PathBuf) {
-        let status = self.status.clone();
-        let db_path = self.db_path.clone();
-        thread::spawn(move || {
-            let _ = update_status(&status, SearchStatus::Indexing);
-            match rebuild_index(&db_path, &root) {
-                Ok(count) => {
-                    let _ = update_status(
-                        &status,
-                        SearchStatus::Ready {
-                            script_count: count,
-                        },
-                    );
-                }
-                Err(err) => {
-                    let _ = update_status(&status, SearchStatus::Error(err));
-                }
+        self.rerun.store(true, Ordering::SeqCst);
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let index = self.clone();
+        thread::spawn(move || loop {
+            while index.rerun.swap(false, Ordering::SeqCst) {
+                index.rebuild(&root);
+            }
+            index.running.store(false, Ordering::SeqCst);
+            // A request may have landed between the last check and clearing `running`.
+            if !index.rerun.load(Ordering::SeqCst) || index.running.swap(true, Ordering::SeqCst) {
+                return;
             }
         });
     }
 
+    fn rebuild(&self, root: &Path) {
+        let _ = update_status(&self.status, SearchStatus::Indexing { done: 0, total: 0 });
+        let mut progress = |done, total| {
+            let _ = update_status(&self.status, SearchStatus::Indexing { done, total });
+        };
+        let next = match rebuild_index(&self.db_path, root, &mut progress) {
+            Ok(count) => SearchStatus::Ready {
+                script_count: count,
+            },
+            Err(err) => SearchStatus::Error(err),
+        };
+        let _ = update_status(&self.status, next);
+    }
+
+    /// Ranks scripts with FTS5 bm25 and prefix matching, falling back to trigram similarity
+    /// when nothing matches directly. Qualifiers such as `tag:azure` or `-has:error` narrow
+    /// the results in every mode.
     pub fn query(&self, query: &str) -> Result<Vec<SearchResult>, String> {
         let conn = open_connection(&self.db_path)?;
         init_db(&conn)?;
 
-        let tokens = split_query(query);
-        let mut sql = String::from(
-            "SELECT script_path, display_name, description, tags, schema_error \
-             FROM script_index",
-        );
-        if !tokens.is_empty() {
-            sql.push_str(" WHERE ");
-            for (idx, _) in tokens.iter().enumerate() {
-                if idx > 0 {
-                    sql.push_str(" AND ");
-                }
-                sql.push_str("search_blob LIKE ? ESCAPE '\\'");
-            }
+        let query = parse_query(query);
+        if query.terms.is_empty() {
+            return list_all(&conn, &query.filters);
         }
-        sql.push_str(" ORDER BY display_name COLLATE NOCASE, script_path COLLATE NOCASE");
-
-        let mut stmt = conn
-            .prepare(&sql)
-            .map_err(|err| format!("Search prepare failed: {}", err))?;
-
-        let params: Vec<String> = tokens
-            .iter()
-            .map(|token| format!("%{}%", escape_like(token)))
-            .collect();
-        let rows = stmt
-            .query_map(params_from_iter(params), |row| {
-                let script_path: String = row.get(0)?;
-                let display_name: String = row.get(1)?;
-                let description: Option<String> = row.get(2)?;
-                let tags_raw: Option<String> = row.get(3)?;
-                let schema_error: Option<String> = row.get(4)?;
-                Ok(SearchResult {
-                    script_path: PathBuf::from(script_path),
-                    display_name,
-                    description,
-                    tags: parse_tags(tags_raw),
-                    schema_error,
-                })
-            })
-            .map_err(|err| format!("Search query failed: {}", err))?;
+        let results = match_query(&conn, &query.terms, &query.filters)?;
+        if !results.is_empty() {
+            return Ok(results);
+        }
+        fuzzy_query(&conn, &query.terms, &query.filters)
+    }
 
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(row.map_err(|err| format!("Search row failed: {}", err))?);
+    /// Schema stored by the last indexing pass, if the file hasn't changed since.
+    ///
+    /// Returns `None` when the script isn't indexed yet or its size or mtime moved, so the
+    /// caller falls back to parsing the file; a cached parse failure comes back as `Err`.
+    pub fn cached_schema(&self, script: &Path, root: &Path) -> Option<Result<Schema, String>> {
+        let stamp = FileStamp::of(script)?;
+        let relative = script.strip_prefix(root).unwrap_or(script);
+        let conn = open_connection(&self.db_path).ok()?;
+        init_db(&conn).ok()?;
+        let (cached, schema_json, schema_error) = conn
+            .query_row(
+                "SELECT mtime, size, schema_json, schema_error \
+                 FROM script_index WHERE script_path = ?",
+                [relative.to_string_lossy()],
+                |row| {
+                    let cached = FileStamp {
+                        mtime: row.get(0)?,
+                        size: row.get(1)?,
+                    };
+                    Ok((
+                        cached,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                    ))
+                },
+            )
+            .optional()
+            .ok()??;
+        if cached != stamp {
+            return None;
+        }
+        match (schema_json, schema_error) {
+            (Some(json), _) => serde_json::from_str(&json).ok().map(Ok),
+            (None, Some(err)) => Some(Err(err)),
+            (None, None) => None,
         }
-        Ok(results)
     }
 
     pub fn load_details(&self, script_path: &Path) -> Result<Option<SearchDetails>, String> {
@@ -199,7 +301,51 @@ impl SearchIndex {
     }
 }
 
-fn rebuild_index(db_path: &Path, root: &Path) -> Result<usize, String> {
+/// Size and modification time as seen by the last indexing pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    mtime: i64,
+    size: i64,
+}
+
+impl FileStamp {
+    /// Stamp of a script together with its sidecar schemas, so editing, adding or removing
+    /// a sidecar counts as a change.
+    fn of(script: &Path) -> Option<Self> {
+        let mut stamp = Self::of_file(script)?;
+        for sidecar in sidecar_schema_paths(script) {
+            let Some(other) = Self::of_file(&sidecar) else {
+                continue;
+            };
+            stamp.mtime = stamp.mtime.max(other.mtime);
+            stamp.size += other.size + 1;
+        }
+        Some(stamp)
+    }
+
+    fn of_file(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let mtime = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as i64;
+        Some(Self {
+            mtime,
+            size: metadata.len() as i64,
+        })
+    }
+}
+
+/// Brings the index up to date with the workspace: unchanged files are skipped, files whose
+/// stamp moved but whose content hash matches only get a new stamp, and rows for deleted
+/// scripts are dropped. `progress` is called with `(done, total)` after every script.
+fn rebuild_index(
+    db_path: &Path,
+    root: &Path,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<usize, String> {
     let repo = FsWorkspaceRepository::new(root.to_path_buf());
     let scripts = repo
         .list_scripts_recursive()
@@ -209,102 +355,262 @@ fn rebuild_index(db_path: &Path, root: &Path) -> Result<usize, String> {
     init_db(&conn)?;
     conn.execute("PRAGMA foreign_keys = ON", [])
         .map_err(|err| format!("Enable foreign keys failed: {}", err))?;
+    let mut known = indexed_stamps(&conn)?;
 
     let tx = conn
         .transaction()
         .map_err(|err| format!("Begin transaction failed: {}", err))?;
-    tx.execute("DELETE FROM script_fields", [])
-        .map_err(|err| format!("Clear fields failed: {}", err))?;
-    tx.execute("DELETE FROM script_index", [])
-        .map_err(|err| format!("Clear scripts failed: {}", err))?;
 
-    for script in &scripts {
+    // Schemas can pull fields from the shared library, and `[schema] strict` decides which
+    // ones load at all, so an edit to either reindexes everything.
+    let mut inputs_changed = false;
+    for (key, value) in [
+        ("field_library", library_hash(root)),
+        ("schema_strict", repo.strict().to_string()),
+    ] {
+        let previous: Option<String> = tx
+            .query_row("SELECT value FROM index_meta WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(|err| format!("Read index metadata failed: {}", err))?;
+        if previous.as_deref() != Some(value.as_str()) {
+            inputs_changed = true;
+            tx.execute(
+                "INSERT OR REPLACE INTO index_meta (key, value) VALUES (?, ?)",
+                [key, value.as_str()],
+            )
+            .map_err(|err| format!("Write index metadata failed: {}", err))?;
+        }
+    }
+
+    let total = scripts.len();
+    for (done, script) in scripts.iter().enumerate() {
         let relative = script.strip_prefix(root).unwrap_or(script);
         let relative_str = relative.to_string_lossy().to_string();
-        let file_name = script
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("script");
-
-        let mut schema_error = None;
-        let mut display_name = file_name.to_string();
-        let mut description: Option<String> = None;
-        let mut tags: Vec<String> = Vec::new();
-        let mut fields: Vec<SearchField> = Vec::new();
-
-        match repo.read_schema(script) {
-            Ok(schema) => {
-                display_name = schema.name.clone();
-                description = schema.description.clone();
-                tags = schema.tags.clone().unwrap_or_default();
-                fields = schema
-                    .fields
-                    .iter()
-                    .map(|field| SearchField {
-                        name: field.name.clone(),
-                        prompt: field.prompt.clone(),
-                        kind: field.kind.clone(),
-                        required: field.required.unwrap_or(false),
-                    })
-                    .collect();
+        let previous = known.remove(&relative_str);
+        let stamp = FileStamp::of(script).unwrap_or(FileStamp { mtime: 0, size: 0 });
+
+        match previous {
+            Some((previous_stamp, _)) if previous_stamp == stamp && !inputs_changed => {}
+            Some((_, previous_hash)) => {
+                let hash = file_hash(script);
+                if previous_hash == hash && !inputs_changed {
+                    tx.execute(
+                        "UPDATE script_index SET mtime = ?, size = ? WHERE script_path = ?",
+                        params![stamp.mtime, stamp.size, relative_str.as_str()],
+                    )
+                    .map_err(|err| format!("Update script stamp failed: {}", err))?;
+                } else {
+                    remove_script(&tx, &relative_str)?;
+                    index_script(&tx, &repo, script, &relative_str, stamp, &hash)?;
+                }
             }
-            Err(err) => {
-                schema_error = Some(err.to_string());
+            None => {
+                let hash = file_hash(script);
+                index_script(&tx, &repo, script, &relative_str, stamp, &hash)?;
             }
         }
+        progress(done + 1, total);
+    }
 
-        let search_blob = build_search_blob(
-            &relative_str,
-            &display_name,
-            description.as_deref(),
-            &tags,
-            &fields,
-        );
+    for removed in known.keys() {
+        remove_script(&tx, removed)?;
+    }
 
-        let tags_raw = if tags.is_empty() {
-            None
-        } else {
-            Some(tags.join(","))
-        };
-        let indexed_at = timestamp_ms();
+    tx.commit()
+        .map_err(|err| format!("Commit search index failed: {}", err))?;
+    Ok(total)
+}
+
+fn indexed_stamps(conn: &Connection) -> Result<HashMap<String, (FileStamp, String)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT script_path, mtime, size, hash FROM script_index")
+        .map_err(|err| format!("Read indexed scripts failed: {}", err))?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                (
+                    FileStamp {
+                        mtime: row.get(1)?,
+                        size: row.get(2)?,
+                    },
+                    row.get::<_, String>(3)?,
+                ),
+            ))
+        })
+        .map_err(|err| format!("Read indexed scripts failed: {}", err))?;
+    let mut stamps = HashMap::new();
+    for row in rows {
+        let (path, stamp) = row.map_err(|err| format!("Search row failed: {}", err))?;
+        stamps.insert(path, stamp);
+    }
+    Ok(stamps)
+}
+
+fn remove_script(conn: &Connection, relative_str: &str) -> Result<(), String> {
+    for table in [
+        "script_fields",
+        "script_index",
+        "script_fts",
+        "script_fuzzy",
+    ] {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE script_path = ?", table),
+            [relative_str],
+        )
+        .map_err(|err| format!("Remove script failed: {}", err))?;
+    }
+    Ok(())
+}
 
-        tx.execute(
-            "INSERT OR REPLACE INTO script_index \
-             (script_path, display_name, description, tags, search_blob, schema_error, indexed_at) \
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
+fn index_script(
+    conn: &Connection,
+    repo: &FsWorkspaceRepository,
+    script: &Path,
+    relative_str: &str,
+    stamp: FileStamp,
+    hash: &str,
+) -> Result<(), String> {
+    let file_name = script
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("script");
+
+    let mut schema_error = None;
+    let mut schema_json = None;
+    let mut has_queue = false;
+    let mut has_outputs = false;
+    let mut display_name = file_name.to_string();
+    let mut description: Option<String> = None;
+    let mut tags: Vec<String> = Vec::new();
+    let mut fields: Vec<SearchField> = Vec::new();
+
+    match repo.read_schema(script) {
+        Ok(schema) => {
+            display_name = schema.name.clone();
+            description = schema.description.clone();
+            tags = schema.tags.clone().unwrap_or_default();
+            fields = schema
+                .fields
+                .iter()
+                .map(|field| SearchField {
+                    name: field.name.clone(),
+                    prompt: field.prompt.clone(),
+                    kind: field.kind.clone(),
+                    required: field.required.unwrap_or(false),
+                })
+                .collect();
+            has_queue = schema.queue.is_some();
+            has_outputs = schema
+                .outputs
+                .as_ref()
+                .is_some_and(|outputs| !outputs.is_empty());
+            schema_json = serde_json::to_string(&schema).ok();
+        }
+        Err(err) => {
+            schema_error = Some(err.to_string());
+        }
+    }
+
+    let tags_raw = if tags.is_empty() {
+        None
+    } else {
+        Some(tags.join(","))
+    };
+    let kind = script_kind(script).map(|kind| kind.name());
+    let indexed_at = timestamp_ms();
+
+    conn.execute(
+        "INSERT OR REPLACE INTO script_index \
+         (script_path, display_name, description, tags, schema_error, schema_json, \
+         kind, has_queue, has_outputs, mtime, size, hash, indexed_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            relative_str,
+            display_name,
+            description,
+            tags_raw,
+            schema_error,
+            schema_json,
+            kind,
+            has_queue,
+            has_outputs,
+            stamp.mtime,
+            stamp.size,
+            hash,
+            indexed_at
+        ],
+    )
+    .map_err(|err| format!("Insert script failed: {}", err))?;
+
+    let fields_text = fields_text(&fields);
+    for table in ["script_fts", "script_fuzzy"] {
+        conn.execute(
+            &format!(
+                "INSERT INTO {} (script_path, name, tags, description, fields, path) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+                table
+            ),
             params![
-                relative_str.as_str(),
+                relative_str,
                 display_name,
-                description,
-                tags_raw,
-                search_blob,
-                schema_error,
-                indexed_at
+                tags.join(" "),
+                description.as_deref().unwrap_or(""),
+                fields_text,
+                relative_str
             ],
         )
-        .map_err(|err| format!("Insert script failed: {}", err))?;
+        .map_err(|err| format!("Insert search text failed: {}", err))?;
+    }
 
-        for (order, field) in fields.iter().enumerate() {
-            tx.execute(
-                "INSERT INTO script_fields \
-                 (script_path, field_order, name, prompt, kind, required) \
-                 VALUES (?, ?, ?, ?, ?, ?)",
-                params![
-                    &relative_str,
-                    order as i64,
-                    &field.name,
-                    field.prompt.clone(),
-                    &field.kind,
-                    if field.required { 1 } else { 0 }
-                ],
-            )
-            .map_err(|err| format!("Insert field failed: {}", err))?;
-        }
+    for (order, field) in fields.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO script_fields \
+             (script_path, field_order, name, prompt, kind, required) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                relative_str,
+                order as i64,
+                &field.name,
+                field.prompt.clone(),
+                &field.kind,
+                if field.required { 1 } else { 0 }
+            ],
+        )
+        .map_err(|err| format!("Insert field failed: {}", err))?;
+    }
+    Ok(())
+}
+
+/// FNV-1a over the script and sidecar bytes; stable across builds, unlike `DefaultHasher`.
+fn file_hash(script: &Path) -> String {
+    let bytes: Vec<u8> = std::iter::once(script.to_path_buf())
+        .chain(sidecar_schema_paths(script))
+        .flat_map(|file| fs::read(file).unwrap_or_default())
+        .collect();
+    fnv_hash_bytes(&bytes)
+}
+
+/// Hash of every field library file and where it lives.
+fn library_hash(root: &Path) -> String {
+    let mut bytes = Vec::new();
+    for (namespace, path) in field_library_files(root) {
+        bytes.extend(namespace.as_bytes());
+        bytes.push(0);
+        bytes.extend(fs::read(path).unwrap_or_default());
     }
+    fnv_hash_bytes(&bytes)
+}
 
-    tx.commit()
-        .map_err(|err| format!("Commit search index failed: {}", err))?;
-    Ok(scripts.len())
+fn fnv_hash_bytes(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
 }
 
 fn open_connection(db_path: &Path) -> Result<Connection, String> {
@@ -323,14 +629,33 @@ fn open_connection(db_path: &Path) -> Result<Connection, String> {
 }
 
 fn init_db(conn: &Connection) -> Result<(), String> {
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(|err| format!("Read search db version failed: {}", err))?;
+    if version < SCHEMA_VERSION {
+        conn.execute_batch(
+            "DROP TABLE IF EXISTS script_fields;\
+            DROP TABLE IF EXISTS script_index;\
+            DROP TABLE IF EXISTS script_fts;\
+            DROP TABLE IF EXISTS script_fuzzy;\
+            DROP TABLE IF EXISTS index_meta;",
+        )
+        .map_err(|err| format!("Reset search db failed: {}", err))?;
+    }
     conn.execute_batch(
         "CREATE TABLE IF NOT EXISTS script_index (\
             script_path TEXT PRIMARY KEY,\
             display_name TEXT NOT NULL,\
             description TEXT,\
             tags TEXT,\
-            search_blob TEXT NOT NULL,\
             schema_error TEXT,\
+            schema_json TEXT,\
+            kind TEXT,\
+            has_queue INTEGER NOT NULL,\
+            has_outputs INTEGER NOT NULL,\
+            mtime INTEGER NOT NULL,\
+            size INTEGER NOT NULL,\
+            hash TEXT NOT NULL,\
             indexed_at INTEGER NOT NULL\
         );\
         CREATE TABLE IF NOT EXISTS script_fields (\
@@ -342,28 +667,153 @@ fn init_db(conn: &Connection) -> Result<(), String> {
             required INTEGER NOT NULL,\
             FOREIGN KEY(script_path) REFERENCES script_index(script_path) ON DELETE CASCADE\
         );\
-        CREATE INDEX IF NOT EXISTS idx_script_search ON script_index(search_blob);\
+        CREATE VIRTUAL TABLE IF NOT EXISTS script_fts USING fts5(\
+            script_path UNINDEXED, name, tags, description, fields, path,\
+            tokenize = 'unicode61 remove_diacritics 2'\
+        );\
+        CREATE VIRTUAL TABLE IF NOT EXISTS script_fuzzy USING fts5(\
+            script_path UNINDEXED, name, tags, description, fields, path,\
+            tokenize = 'trigram'\
+        );\
+        CREATE TABLE IF NOT EXISTS index_meta (\
+            key TEXT PRIMARY KEY,\
+            value TEXT NOT NULL\
+        );\
         CREATE INDEX IF NOT EXISTS idx_script_fields ON script_fields(script_path);",
     )
-    .map_err(|err| format!("Init search db failed: {}", err))
+    .map_err(|err| format!("Init search db failed: {}", err))?;
+    if version < SCHEMA_VERSION {
+        conn.execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+            .map_err(|err| format!("Write search db version failed: {}", err))?;
+    }
+    Ok(())
 }
 
-fn build_search_blob(
-    script_path: &str,
-    display_name: &str,
-    description: Option<&str>,
-    tags: &[String],
-    fields: &[SearchField],
-) -> String {
-    let mut parts = Vec::new();
-    parts.push(script_path.to_string());
-    parts.push(display_name.to_string());
-    if let Some(description) = description {
-        parts.push(description.to_string());
+fn list_all(conn: &Connection, filters: &[Filter]) -> Result<Vec<SearchResult>, String> {
+    let (clause, values) = filter_clause(filters);
+    let sql = format!(
+        "SELECT s.script_path, s.display_name, s.description, s.tags, s.schema_error, \
+         s.display_name, s.script_path \
+         FROM script_index s WHERE 1 = 1{} \
+         ORDER BY s.display_name COLLATE NOCASE, s.script_path COLLATE NOCASE",
+        clause
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|err| format!("Search prepare failed: {}", err))?;
+    collect_results(&mut stmt, params_from_iter(values), false)
+}
+
+fn match_query(
+    conn: &Connection,
+    tokens: &[String],
+    filters: &[Filter],
+) -> Result<Vec<SearchResult>, String> {
+    let (clause, values) = filter_clause(filters);
+    let sql = format!(
+        "SELECT s.script_path, s.display_name, s.description, s.tags, s.schema_error, \
+         highlight(script_fts, 1, char(1), char(2)), highlight(script_fts, 5, char(1), char(2)) \
+         FROM script_fts JOIN script_index s ON s.script_path = script_fts.script_path \
+         WHERE script_fts MATCH ?{} \
+         ORDER BY bm25(script_fts, {}), s.display_name COLLATE NOCASE",
+        clause, COLUMN_WEIGHTS
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|err| format!("Search prepare failed: {}", err))?;
+    let params = std::iter::once(fts_match_expression(tokens)).chain(values);
+    collect_results(&mut stmt, params_from_iter(params), false)
+}
+
+/// Finds scripts sharing enough trigrams with every term, so `delte` still finds `delete`.
+fn fuzzy_query(
+    conn: &Connection,
+    tokens: &[String],
+    filters: &[Filter],
+) -> Result<Vec<SearchResult>, String> {
+    let trigrams: Vec<String> = tokens.iter().flat_map(|token| trigrams(token)).collect();
+    if trigrams.is_empty() {
+        return Ok(Vec::new());
+    }
+    let expression = trigrams
+        .iter()
+        .map(|trigram| quote_fts(trigram))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+    let (clause, values) = filter_clause(filters);
+    let sql = format!(
+        "SELECT s.script_path, s.display_name, s.description, s.tags, s.schema_error, \
+         highlight(script_fuzzy, 1, char(1), char(2)), highlight(script_fuzzy, 5, char(1), char(2)), \
+         lower(f.name || ' ' || f.tags || ' ' || f.description || ' ' || f.fields || ' ' || f.path) \
+         FROM script_fuzzy f JOIN script_index s ON s.script_path = f.script_path \
+         WHERE script_fuzzy MATCH ?{} \
+         ORDER BY bm25(script_fuzzy, {}) LIMIT {}",
+        clause, COLUMN_WEIGHTS, FUZZY_CANDIDATES
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|err| format!("Fuzzy search prepare failed: {}", err))?;
+    let rows = stmt
+        .query_map(
+            params_from_iter(std::iter::once(expression).chain(values)),
+            |row| Ok((read_result(row, true)?, row.get::<_, String>(7)?)),
+        )
+        .map_err(|err| format!("Fuzzy search failed: {}", err))?;
+
+    let mut scored = Vec::new();
+    for row in rows {
+        let (result, haystack) = row.map_err(|err| format!("Search row failed: {}", err))?;
+        let scores: Vec<f64> = tokens
+            .iter()
+            .map(|token| similarity(token, &haystack))
+            .collect();
+        if scores.iter().all(|score| *score >= FUZZY_THRESHOLD) {
+            let average = scores.iter().sum::<f64>() / scores.len() as f64;
+            scored.push((average, result));
+        }
     }
-    for tag in tags {
-        parts.push(tag.clone());
+    // Stable sort keeps bm25 order between equally similar scripts.
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(scored.into_iter().map(|(_, result)| result).collect())
+}
+
+fn collect_results<P: rusqlite::Params>(
+    stmt: &mut rusqlite::Statement,
+    params: P,
+    fuzzy: bool,
+) -> Result<Vec<SearchResult>, String> {
+    let rows = stmt
+        .query_map(params, |row| read_result(row, fuzzy))
+        .map_err(|err| format!("Search query failed: {}", err))?;
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row.map_err(|err| format!("Search row failed: {}", err))?);
     }
+    Ok(results)
+}
+
+fn read_result(row: &rusqlite::Row, fuzzy: bool) -> rusqlite::Result<SearchResult> {
+    let script_path: String = row.get(0)?;
+    let display_name: String = row.get(1)?;
+    let description: Option<String> = row.get(2)?;
+    let tags_raw: Option<String> = row.get(3)?;
+    let schema_error: Option<String> = row.get(4)?;
+    let name_marked: String = row.get(5)?;
+    let path_marked: String = row.get(6)?;
+    Ok(SearchResult {
+        script_path: PathBuf::from(script_path),
+        display_name,
+        description,
+        tags: parse_tags(tags_raw),
+        schema_error,
+        name_highlight: Highlighted::from_marked(&name_marked),
+        path_highlight: Highlighted::from_marked(&path_marked),
+        fuzzy,
+    })
+}
+
+fn fields_text(fields: &[SearchField]) -> String {
+    let mut parts = Vec::new();
     for field in fields {
         parts.push(field.name.clone());
         if let Some(prompt) = &field.prompt {
@@ -371,22 +821,116 @@ fn build_search_blob(
         }
         parts.push(field.kind.clone());
     }
-    parts.join(" ").to_lowercase()
+    parts.join(" ")
+}
+
+fn parse_query(query: &str) -> ParsedQuery {
+    let mut parsed = ParsedQuery::default();
+    for token in query.split_whitespace() {
+        let token = token.to_lowercase();
+        match parse_filter(&token) {
+            Some(filter) => parsed.filters.push(filter),
+            None => parsed.terms.push(token),
+        }
+    }
+    parsed
+}
+
+/// Unknown qualifiers and empty values stay free text, so `http://` or `a:` still search.
+fn parse_filter(token: &str) -> Option<Filter> {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, token),
+    };
+    let (qualifier, value) = token.split_once(':')?;
+    if value.is_empty() {
+        return None;
+    }
+    let kind = match qualifier {
+        "tag" => FilterKind::Tag(value.to_string()),
+        "kind" => FilterKind::Kind(
+            ScriptKind::from_name(value)
+                .map(|kind| kind.name().to_string())
+                .unwrap_or_else(|| value.to_string()),
+        ),
+        "field" => FilterKind::Field(value.to_string()),
+        "path" => FilterKind::Path(value.trim_start_matches("./").replace('\\', "/")),
+        "has" => FilterKind::Has(match value {
+            "queue" => HasFlag::Queue,
+            "outputs" => HasFlag::Outputs,
+            "error" => HasFlag::Error,
+            _ => return None,
+        }),
+        _ => return None,
+    };
+    Some(Filter { negated, kind })
+}
+
+/// SQL conditions (against `script_index s`) appended to a `WHERE`, with their parameters.
+fn filter_clause(filters: &[Filter]) -> (String, Vec<String>) {
+    let mut clause = String::new();
+    let mut values = Vec::new();
+    for filter in filters {
+        let condition = match &filter.kind {
+            FilterKind::Tag(tag) => {
+                values.push(tag.clone());
+                "instr(',' || lower(coalesce(s.tags, '')) || ',', ',' || ? || ',') > 0"
+            }
+            FilterKind::Kind(kind) => {
+                values.push(kind.clone());
+                "coalesce(s.kind, '') = ?"
+            }
+            FilterKind::Field(name) => {
+                values.push(name.clone());
+                "EXISTS (SELECT 1 FROM script_fields sf \
+                 WHERE sf.script_path = s.script_path AND lower(sf.name) = ?)"
+            }
+            FilterKind::Path(prefix) => {
+                values.push(prefix.clone());
+                "instr(lower(replace(s.script_path, '\\', '/')), ?) = 1"
+            }
+            FilterKind::Has(HasFlag::Queue) => "s.has_queue = 1",
+            FilterKind::Has(HasFlag::Outputs) => "s.has_outputs = 1",
+            FilterKind::Has(HasFlag::Error) => "s.schema_error IS NOT NULL",
+        };
+        let negation = if filter.negated { "NOT " } else { "" };
+        clause.push_str(&format!(" AND {}({})", negation, condition));
+    }
+    (clause, values)
+}
+
+/// Every term must match, each as a prefix: `rg del` becomes `"rg"* "del"*`.
+fn fts_match_expression(tokens: &[String]) -> String {
+    tokens
+        .iter()
+        .map(|token| format!("{}*", quote_fts(token)))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
-fn split_query(query: &str) -> Vec<String> {
-    query
-        .split_whitespace()
-        .filter(|token| !token.is_empty())
-        .map(|token| token.to_lowercase())
+fn quote_fts(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+fn trigrams(token: &str) -> Vec<String> {
+    let chars: Vec<char> = token.chars().collect();
+    chars
+        .windows(3)
+        .map(|window| window.iter().collect())
         .collect()
 }
 
-fn escape_like(input: &str) -> String {
-    input
-        .replace('\\', "\\\\")
-        .replace('%', "\\%")
-        .replace('_', "\\_")
+/// Share of the term's trigrams found in `haystack`; short terms need a substring match.
+fn similarity(token: &str, haystack: &str) -> f64 {
+    let grams = trigrams(token);
+    if grams.is_empty() {
+        return if haystack.contains(token) { 1.0 } else { 0.0 };
+    }
+    let found = grams
+        .iter()
+        .filter(|gram| haystack.contains(gram.as_str()))
+        .count();
+    found as f64 / grams.len() as f64
 }
 
 fn parse_tags(tags_raw: Option<String>) -> Vec<String> {
@@ -416,3 +960,230 @@ fn timestamp_ms() -> i64 {
         .unwrap_or_default();
     duration.as_millis() as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(name: &str, description: &str, prompt: &str) -> String {
+        format!(
+            "# OMAKURE_SCHEMA_START\n# {{\"Name\":\"{}\",\"Description\":\"{}\",\"Fields\":[{{\"Name\":\"target\",\"Prompt\":\"{}\",\"Type\":\"string\",\"Order\":1}}]}}\n# OMAKURE_SCHEMA_END\n",
+            name, description, prompt
+        )
+    }
+
+    fn indexed_workspace(name: &str) -> (PathBuf, SearchIndex) {
+        let root =
+            std::env::temp_dir().join(format!("omakure-search-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("cleanup.bash"),
+            script("cleanup", "Remove old disks", "Resource group to delete"),
+        )
+        .unwrap();
+        fs::write(
+            root.join("rg-delete.bash"),
+            script("rg-delete", "Delete a resource group", "Name"),
+        )
+        .unwrap();
+        let db_path = root.join(".history/search.sqlite");
+        rebuild_index(&db_path, &root, &mut |_, _| {}).unwrap();
+        (root, SearchIndex::new(db_path))
+    }
+
+    fn names(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .map(|result| result.display_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_name_match_outranks_field_prompt() {
+        let (root, index) = indexed_workspace("rank");
+        let results = index.query("delete").unwrap();
+        assert_eq!(names(&results), vec!["rg-delete", "cleanup"]);
+        assert!(!results[0].fuzzy);
+        assert_eq!(
+            results[0].name_highlight.segments,
+            vec![("rg-".to_string(), false), ("delete".to_string(), true)]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_prefix_and_all_terms_required() {
+        let (root, index) = indexed_workspace("prefix");
+        assert_eq!(names(&index.query("dis").unwrap()), vec!["cleanup"]);
+        assert_eq!(names(&index.query("old group").unwrap()), vec!["cleanup"]);
+        assert_eq!(index.query("").unwrap().len(), 2);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_typo_falls_back_to_fuzzy() {
+        let (root, index) = indexed_workspace("fuzzy");
+        let results = index.query("cleanpu").unwrap();
+        assert_eq!(names(&results), vec!["cleanup"]);
+        assert!(results[0].fuzzy);
+        assert!(index.query("zzzqqq").unwrap().is_empty());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_rebuild_only_reparses_changed_scripts() {
+        let (root, index) = indexed_workspace("incremental");
+        let db_path = root.join(".history/search.sqlite");
+        let conn = open_connection(&db_path).unwrap();
+        conn.execute(
+            "UPDATE script_index SET display_name = 'stale' WHERE script_path = 'cleanup.bash'",
+            [],
+        )
+        .unwrap();
+
+        fs::write(
+            root.join("rg-delete.bash"),
+            script("rg-remove", "Delete a resource group", "Name"),
+        )
+        .unwrap();
+        fs::remove_file(root.join("cleanup.bash")).unwrap();
+        fs::write(root.join("new.bash"), script("new", "Fresh", "Name")).unwrap();
+        let mut ticks = Vec::new();
+        let count = rebuild_index(&db_path, &root, &mut |done, total| {
+            ticks.push((done, total))
+        })
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(ticks, vec![(1, 2), (2, 2)]);
+        assert_eq!(names(&index.query("").unwrap()), vec!["new", "rg-remove"]);
+        let fields: i64 = conn
+            .query_row("SELECT COUNT(*) FROM script_fields", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(fields, 2);
+
+        fs::write(root.join("cleanup.bash"), script("cleanup", "Old", "Name")).unwrap();
+        rebuild_index(&db_path, &root, &mut |_, _| {}).unwrap();
+        conn.execute(
+            "UPDATE script_index SET display_name = 'stale' WHERE script_path = 'new.bash'",
+            [],
+        )
+        .unwrap();
+        rebuild_index(&db_path, &root, &mut |_, _| {}).unwrap();
+        assert_eq!(
+            names(&index.query("").unwrap()),
+            vec!["cleanup", "rg-remove", "stale"]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_cached_schema_tracks_file_changes() {
+        let (root, index) = indexed_workspace("cached");
+        let path = root.join("cleanup.bash");
+        let schema = index.cached_schema(&path, &root).unwrap().unwrap();
+        assert_eq!(schema.name, "cleanup");
+        assert_eq!(
+            schema.fields[0].prompt.as_deref(),
+            Some("Resource group to delete")
+        );
+
+        fs::write(&path, "# not a schema\n").unwrap();
+        assert!(index.cached_schema(&path, &root).is_none());
+        rebuild_index(&root.join(".history/search.sqlite"), &root, &mut |_, _| {}).unwrap();
+        assert!(index.cached_schema(&path, &root).unwrap().is_err());
+
+        fs::write(
+            root.join("cleanup.bash.omakure.toml"),
+            "Name = \"cleanup-sidecar\"\nFields = []\n",
+        )
+        .unwrap();
+        assert!(index.cached_schema(&path, &root).is_none());
+        rebuild_index(&root.join(".history/search.sqlite"), &root, &mut |_, _| {}).unwrap();
+        let schema = index.cached_schema(&path, &root).unwrap().unwrap();
+        assert_eq!(schema.name, "cleanup-sidecar");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_strict_mode_change_reindexes() {
+        let (root, index) = indexed_workspace("strict");
+        let db_path = root.join(".history/search.sqlite");
+        let path = root.join("loose.bash");
+        fs::write(
+            &path,
+            "# OMAKURE_SCHEMA_START\n# {\"Name\": \"loose\", \"Colour\": \"red\", \"Fields\": []}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        rebuild_index(&db_path, &root, &mut |_, _| {}).unwrap();
+        assert!(index.cached_schema(&path, &root).unwrap().is_ok());
+
+        fs::write(root.join("omakure.toml"), "[schema]\nstrict = true\n").unwrap();
+        rebuild_index(&db_path, &root, &mut |_, _| {}).unwrap();
+        assert!(index.cached_schema(&path, &root).unwrap().is_err());
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_parse_query_qualifiers() {
+        let parsed = parse_query("rg -tag:Deprecated kind:py has:nope path:./.omaken/Azure");
+        assert_eq!(parsed.terms, vec!["rg", "has:nope"]);
+        assert_eq!(
+            parsed.filters,
+            vec![
+                Filter {
+                    negated: true,
+                    kind: FilterKind::Tag("deprecated".into()),
+                },
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Kind("python".into()),
+                },
+                Filter {
+                    negated: false,
+                    kind: FilterKind::Path(".omaken/azure".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_qualifiers_filter_results() {
+        let (root, index) = indexed_workspace("qualifiers");
+        fs::create_dir_all(root.join("azure")).unwrap();
+        fs::write(
+            root.join("azure/vm-list.py"),
+            "# OMAKURE_SCHEMA_START\n# {\"Name\":\"vm-list\",\"Tags\":[\"azure\",\"deprecated\"],\"Fields\":[{\"Name\":\"subscription_id\",\"Type\":\"string\",\"Order\":1}],\"Outputs\":[{\"Name\":\"id\",\"Type\":\"string\"}],\"Queue\":{\"Cases\":[]}}\n# OMAKURE_SCHEMA_END\n",
+        )
+        .unwrap();
+        fs::write(root.join("broken.sh"), "# no schema\n").unwrap();
+        rebuild_index(&root.join(".history/search.sqlite"), &root, &mut |_, _| {}).unwrap();
+
+        let query = |text: &str| names(&index.query(text).unwrap()).join(",");
+        assert_eq!(query("tag:azure"), "vm-list");
+        assert_eq!(
+            query("-tag:deprecated kind:bash"),
+            "broken.sh,cleanup,rg-delete"
+        );
+        assert_eq!(query("field:subscription_id"), "vm-list");
+        assert_eq!(query("field:target delete"), "rg-delete,cleanup");
+        assert_eq!(query("path:azure/"), "vm-list");
+        assert_eq!(query("has:queue has:outputs"), "vm-list");
+        assert_eq!(query("has:error"), "broken.sh");
+        assert_eq!(query("-has:error cleanpu"), "cleanup");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_highlight_markers() {
+        let marked = format!("a{}b{}c", HIGHLIGHT_START, HIGHLIGHT_END);
+        assert_eq!(
+            Highlighted::from_marked(&marked).segments,
+            vec![
+                ("a".to_string(), false),
+                ("b".to_string(), true),
+                ("c".to_string(), false)
+            ]
+        );
+    }
+}
@@ -89,6 +89,6 @@ fn ensure_builtin_themes(themes_dir: &Path) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn config_dir() -> Option<PathBuf> {
+pub(crate) fn config_dir() -> Option<PathBuf> {
     dirs::config_dir().map(|dir| dir.join("omakure"))
 }
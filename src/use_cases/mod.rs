@@ -1,8 +1,8 @@
 mod environment;
 
-use crate::domain::Schema;
-use crate::error::AppResult;
-use crate::ports::{ScriptRepository, ScriptRunOutput, ScriptRunner, WorkspaceEntry};
+use crate::domain::{Requirement, Schema};
+use crate::error::{AppError, AppResult, SchemaError, ScriptError};
+use crate::ports::{RunObserver, ScriptRepository, ScriptRunOutput, ScriptRunner, WorkspaceEntry};
 use std::io;
 use std::path::Path;
 
@@ -26,7 +26,53 @@ impl ScriptService {
         self.repo.read_schema(script)
     }
 
-    pub fn run_script(&self, script: &Path, args: &[String]) -> AppResult<ScriptRunOutput> {
-        self.runner.run(script, args)
+    pub fn run_script(
+        &self,
+        script: &Path,
+        args: &[String],
+        stdin: Option<&str>,
+        env: &[(String, String)],
+        observer: &mut dyn RunObserver,
+    ) -> AppResult<ScriptRunOutput> {
+        // Scripts without a schema still run; a schema rejected by strict mode doesn't.
+        let mut prompts = false;
+        match self.repo.read_schema(script) {
+            Err(err @ AppError::Schema(SchemaError::Strict(_))) => return Err(err),
+            Ok(schema) => {
+                prompts = schema.prompts.unwrap_or(false);
+                let unmet = self.unmet_requirements(&schema);
+                if !unmet.is_empty() {
+                    let details: Vec<String> =
+                        unmet.iter().map(|(_, err)| err.to_string()).collect();
+                    return Err(ScriptError::RequirementsUnmet {
+                        details: details.join("; "),
+                    }
+                    .into());
+                }
+            }
+            Err(_) => {}
+        }
+        self.runner.run(script, args, stdin, prompts, env, observer)
+    }
+
+    /// Reports the schema's `Requires` entries that are missing or too old.
+    pub fn unmet_requirements<'s>(
+        &self,
+        schema: &'s Schema,
+    ) -> Vec<(&'s Requirement, ScriptError)> {
+        schema
+            .requires
+            .iter()
+            .flatten()
+            .filter_map(|requirement| Some((requirement, self.check_requirement(requirement)?)))
+            .collect()
+    }
+
+    /// Checks one `Requires` entry, which may run `<command> --version`.
+    pub fn check_requirement(&self, requirement: &Requirement) -> Option<ScriptError> {
+        self.runner
+            .check_requirements(std::slice::from_ref(requirement))
+            .into_iter()
+            .next()
     }
 }
@@ -1,6 +1,42 @@
+use serde::Deserialize;
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+
+/// Settings read from `omakure.toml`; missing sections fall back to defaults.
+#[derive(Debug, Default, Deserialize)]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub lua: LuaConfig,
+    #[serde(default)]
+    pub schema: SchemaConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SchemaConfig {
+    /// Refuse to load schemas that `omakure schema check` would flag.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HistoryConfig {
+    /// Maximum stdin bytes stored per history entry.
+    pub stdin_limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LuaConfig {
+    /// Flavors under `.omaken/` whose Lua code may call `omakure.exec`.
+    #[serde(default)]
+    pub trusted_flavors: Vec<String>,
+    pub memory_limit_mb: Option<usize>,
+    pub timeout_secs: Option<f64>,
+    /// Time limit for `.lua` scripts, which usually run longer than widgets and hooks.
+    pub script_timeout_secs: Option<f64>,
+}
 
 pub struct Workspace {
     root: PathBuf,
@@ -9,6 +45,7 @@ pub struct Workspace {
     config_path: PathBuf,
     envs_dir: PathBuf,
     envs_active_path: PathBuf,
+    presets_dir: PathBuf,
 }
 
 impl Workspace {
@@ -18,6 +55,7 @@ impl Workspace {
         let config_path = root.join("omakure.toml");
         let envs_dir = omaken_dir.join("envs");
         let envs_active_path = envs_dir.join("active");
+        let presets_dir = omaken_dir.join("presets");
         Self {
             root,
             omaken_dir,
@@ -25,6 +63,7 @@ impl Workspace {
             config_path,
             envs_dir,
             envs_active_path,
+            presets_dir,
         }
     }
 
@@ -56,6 +95,27 @@ impl Workspace {
         &self.envs_active_path
     }
 
+    pub fn presets_dir(&self) -> &Path {
+        &self.presets_dir
+    }
+
+    /// Loads `omakure.toml`, using defaults when it is missing or invalid.
+    pub fn load_config(&self) -> WorkspaceConfig {
+        fs::read_to_string(&self.config_path)
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Name of the `.omaken` flavor that contains `path`, if any.
+    pub fn flavor_of(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.omaken_dir).ok()?;
+        match relative.components().next()? {
+            Component::Normal(name) => name.to_str().map(str::to_string),
+            _ => None,
+        }
+    }
+
     pub fn ensure_layout(&self) -> io::Result<()> {
         fs::create_dir_all(&self.root)?;
         fs::create_dir_all(&self.omaken_dir)?;
@@ -0,0 +1,94 @@
+//! Records a run's external calls through the shims and replays them without the real tool.
+#![cfg(unix)]
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+fn omakure(workspace: &Path, path: &str, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_omakure"))
+        .args(args)
+        .env("OMAKURE_SCRIPTS_DIR", workspace)
+        .env("PATH", path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn write_executable(path: &Path, contents: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    fs::write(path, contents).unwrap();
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_record_then_replay_without_the_real_tool() {
+    let root = std::env::temp_dir().join(format!("omakure-record-replay-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let workspace = root.join("ws");
+    let bin = root.join("bin");
+    fs::create_dir_all(&workspace).unwrap();
+    fs::create_dir_all(&bin).unwrap();
+    write_executable(&bin.join("greet"), "#!/bin/sh\necho \"hello $1\"\n");
+    // The script reads stdin after the recorded call, which must not have drained it.
+    write_executable(
+        &workspace.join("tool.sh"),
+        "#!/usr/bin/env bash\ngreet \"$1\"\nread -r line\necho \"read $line\"\n",
+    );
+
+    let system_path = std::env::var("PATH").unwrap_or_default();
+    let with_tool = format!("{}:{}", bin.display(), system_path);
+    let recorded = omakure(
+        &workspace,
+        &with_tool,
+        &[
+            "run",
+            "--record",
+            "--stub",
+            "greet",
+            "--stdin-from",
+            "-",
+            "tool.sh",
+            "world",
+        ],
+        "payload\n",
+    );
+    assert!(recorded.status.success(), "{:?}", recorded);
+    assert_eq!(
+        String::from_utf8_lossy(&recorded.stdout),
+        "hello world\nread payload\n"
+    );
+
+    let cassettes: Vec<PathBuf> = fs::read_dir(workspace.join(".history").join("cassettes"))
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    assert_eq!(cassettes.len(), 1);
+    let cassette = cassettes[0].to_string_lossy().to_string();
+
+    // `greet` is no longer on PATH, so only the replaying shim can answer.
+    let replayed = omakure(
+        &workspace,
+        &system_path,
+        &["run", "--replay", &cassette],
+        "",
+    );
+    assert!(replayed.status.success(), "{:?}", replayed);
+    assert_eq!(
+        String::from_utf8_lossy(&replayed.stdout),
+        "hello world\nread payload\n"
+    );
+
+    let _ = fs::remove_dir_all(root);
+}